@@ -7,4 +7,25 @@
 
 fn main() {
     println!("cargo:rerun-if-changed=json/config.json");
-}
\ No newline at end of file
+
+    #[cfg(feature = "ffi")]
+    generate_header();
+}
+
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    /* Checked in header at include/smartcalc.h is compared with this one in the ffi tests */
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir)).unwrap();
+
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("C header could not be generated")
+        .write_to_file(format!("{}/smartcalc.h", out_dir));
+}
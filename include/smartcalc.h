@@ -0,0 +1,188 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+#ifndef SMARTCALC_H
+#define SMARTCALC_H
+
+/* Generated with cbindgen, do not edit manually */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+typedef enum SmartCalcLineStatus {
+  SMART_CALC_LINE_STATUS_EMPTY,
+  SMART_CALC_LINE_STATUS_SUCCESS,
+  SMART_CALC_LINE_STATUS_ERROR,
+} SmartCalcLineStatus;
+
+typedef enum SmartCalcUiTokenType {
+  SMART_CALC_UI_TOKEN_TYPE_TEXT,
+  SMART_CALC_UI_TOKEN_TYPE_NUMBER,
+  SMART_CALC_UI_TOKEN_TYPE_SYMBOL1,
+  SMART_CALC_UI_TOKEN_TYPE_SYMBOL2,
+  SMART_CALC_UI_TOKEN_TYPE_DATE_TIME,
+  SMART_CALC_UI_TOKEN_TYPE_OPERATOR,
+  SMART_CALC_UI_TOKEN_TYPE_COMMENT,
+  SMART_CALC_UI_TOKEN_TYPE_VARIABLE_DEFINATION,
+  SMART_CALC_UI_TOKEN_TYPE_VARIABLE_USE,
+  SMART_CALC_UI_TOKEN_TYPE_MONTH,
+  SMART_CALC_UI_TOKEN_TYPE_CONSTANT,
+} SmartCalcUiTokenType;
+
+typedef struct Session Session;
+
+typedef struct SmartCalc SmartCalc;
+
+// Execution result of all lines.
+typedef struct SmartCalcResult SmartCalcResult;
+
+// Error of the line, `message` is in the session language.
+typedef struct SmartCalcLineError {
+  const char *message;
+  size_t start;
+  size_t end;
+  size_t char_start;
+  size_t char_end;
+} SmartCalcLineError;
+
+typedef struct SmartCalcUiToken {
+  size_t start;
+  size_t end;
+  enum SmartCalcUiTokenType ui_type;
+} SmartCalcUiToken;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Create a calculator with the default configuration.
+struct SmartCalc *smartcalc_new(void);
+
+// Create a calculator from JSON configuration. Returns null if the text is not valid UTF-8 or not a valid configuration.
+//
+// # Safety
+// `json_data` must be a null terminated string.
+struct SmartCalc *smartcalc_load_from_json(const char *json_data);
+
+// # Safety
+// `smartcalc` must be created with `smartcalc_new` or `smartcalc_load_from_json`, or null.
+void smartcalc_free(struct SmartCalc *smartcalc);
+
+// # Safety
+// `smartcalc` must be a valid handle and `currency` a null terminated string.
+bool smartcalc_update_currency(struct SmartCalc *smartcalc, const char *currency, double rate);
+
+// Update the currency rates from ECB XML, CSV or JSON content, returns false if the content could not be loaded.
+//
+// # Safety
+// `smartcalc` must be a valid handle and `data` a null terminated string.
+bool smartcalc_update_currency_rates(struct SmartCalc *smartcalc,
+                                     const char *data);
+
+// Set the dated currency rates used in historical conversions from ECB XML, CSV or JSON content, returns false if the content could not be loaded.
+//
+// # Safety
+// `smartcalc` must be a valid handle and `data` a null terminated string.
+bool smartcalc_set_currency_rate_history(struct SmartCalc *smartcalc,
+                                         const char *data);
+
+// # Safety
+// `smartcalc` must be a valid handle and `timezone` a null terminated string.
+bool smartcalc_set_timezone(struct SmartCalc *smartcalc, const char *timezone);
+
+// # Safety
+// `smartcalc` must be a valid handle and `separator` a null terminated string.
+bool smartcalc_set_decimal_separator(struct SmartCalc *smartcalc, const char *separator);
+
+// # Safety
+// `smartcalc` must be a valid handle and `separator` a null terminated string.
+bool smartcalc_set_thousand_separator(struct SmartCalc *smartcalc, const char *separator);
+
+// Execute the text, lines are separated with new line. Returns null if the arguments are not valid.
+//
+// # Safety
+// `smartcalc` must be a valid handle, `language` and `text` null terminated strings.
+struct SmartCalcResult *smartcalc_execute(const struct SmartCalc *smartcalc,
+                                          const char *language,
+                                          const char *text);
+
+// Create an empty session.
+struct Session *smartcalc_session_new(void);
+
+// # Safety
+// `session` must be created with `smartcalc_session_new`, or null.
+void smartcalc_session_free(struct Session *session);
+
+// # Safety
+// `session` must be a valid handle and `text` a null terminated string.
+bool smartcalc_session_set_text(struct Session *session, const char *text);
+
+// # Safety
+// `session` must be a valid handle and `language` a null terminated string.
+bool smartcalc_session_set_language(struct Session *session, const char *language);
+
+// Execute all lines of the session. Returns null if the arguments are not valid.
+//
+// # Safety
+// `smartcalc` and `session` must be valid handles.
+struct SmartCalcResult *smartcalc_execute_session(const struct SmartCalc *smartcalc,
+                                                  const struct Session *session);
+
+// # Safety
+// `result` must be returned from an execute function, or null.
+void smartcalc_result_free(struct SmartCalcResult *result);
+
+// # Safety
+// `result` must be a valid result, or null.
+size_t smartcalc_result_line_count(const struct SmartCalcResult *result);
+
+// Empty lines and the lines out of range are `Empty`.
+//
+// # Safety
+// `result` must be a valid result, or null.
+enum SmartCalcLineStatus smartcalc_result_line_status(const struct SmartCalcResult *result,
+                                                      size_t index);
+
+// Formatted output of the line, null if the line does not have a result.
+//
+// # Safety
+// `result` must be a valid result, or null.
+const char *smartcalc_result_line_output(const struct SmartCalcResult *result, size_t index);
+
+// Date of the oldest currency rate used in the line like `2024-05-17T00:00:00Z`, null if the line does not have a currency conversion or the date is not known.
+//
+// # Safety
+// `result` must be a valid result, or null.
+const char *smartcalc_result_line_rate_date(const struct SmartCalcResult *result,
+                                            size_t index);
+
+// Fill the error of the line, returns false if the line does not have an error.
+//
+// # Safety
+// `result` must be a valid result, or null. `error` must point to writable memory.
+bool smartcalc_result_line_error(const struct SmartCalcResult *result,
+                                 size_t index,
+                                 struct SmartCalcLineError *error);
+
+// # Safety
+// `result` must be a valid result, or null.
+size_t smartcalc_result_ui_token_count(const struct SmartCalcResult *result, size_t index);
+
+// Fill the ui token of the line, returns false if the token does not exist.
+//
+// # Safety
+// `result` must be a valid result, or null. `token` must point to writable memory.
+bool smartcalc_result_ui_token(const struct SmartCalcResult *result,
+                               size_t index,
+                               size_t token_index,
+                               struct SmartCalcUiToken *token);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* SMARTCALC_H */
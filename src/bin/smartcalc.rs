@@ -0,0 +1,397 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::process;
+
+use smartcalc::{CurrencyRateHistory, CurrencyRates, ExecuteLine, JsonExecuteResult, LineResult, Session, SmartCalc, UiToken, UiTokenType};
+
+const USAGE: &str = "Usage: smartcalc [OPTIONS] [FILE]
+
+Calculates the worksheet in FILE, or standard input if FILE is '-'.
+Starts an interactive session if FILE is not given and the input is a terminal.
+
+Options:
+  -l, --language <CODE>              Language of the input (default: en)
+  -t, --timezone <NAME>              Timezone used in date and time calculations, like Europe/Istanbul or EST
+  -d, --decimal-separator <TEXT>     Decimal separator of the numbers
+  -s, --thousand-separator <TEXT>    Thousand separator of the numbers
+  -r, --rates <FILE>                 Currency rates, ECB XML or CSV file, or JSON like {\"eur\": 0.92}
+      --rate-history <FILE>          Daily currency rates for conversions like '100 usd to eur on 2021-03-01'
+  -o, --output <plain|color|json>    Output mode (default: plain)
+  -h, --help                         Print this help";
+
+/* Exit codes, a line could not be calculated or the arguments and files are not valid */
+const EXIT_LINE_ERROR: i32 = 1;
+const EXIT_USAGE_ERROR: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputMode {
+    Plain,
+    Color,
+    Json
+}
+
+struct Options {
+    language: String,
+    timezone: Option<String>,
+    decimal_separator: Option<String>,
+    thousand_separator: Option<String>,
+    rates: Option<String>,
+    rate_history: Option<String>,
+    output: OutputMode,
+    file: Option<String>,
+    help: bool
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            language: "en".to_string(),
+            timezone: None,
+            decimal_separator: None,
+            thousand_separator: None,
+            rates: None,
+            rate_history: None,
+            output: OutputMode::Plain,
+            file: None,
+            help: false
+        }
+    }
+}
+
+fn parse_arguments(arguments: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut arguments = arguments.peekable();
+
+    while let Some(argument) = arguments.next() {
+        let mut value = |name: &str| arguments.next().ok_or_else(|| format!("'{}' requires a value", name));
+
+        match argument.as_str() {
+            "-l" | "--language" => options.language = value(&argument)?,
+            "-t" | "--timezone" => options.timezone = Some(value(&argument)?),
+            "-d" | "--decimal-separator" => options.decimal_separator = Some(value(&argument)?),
+            "-s" | "--thousand-separator" => options.thousand_separator = Some(value(&argument)?),
+            "-r" | "--rates" => options.rates = Some(value(&argument)?),
+            "--rate-history" => options.rate_history = Some(value(&argument)?),
+            "-o" | "--output" => options.output = match value(&argument)?.as_str() {
+                "plain" => OutputMode::Plain,
+                "color" => OutputMode::Color,
+                "json" => OutputMode::Json,
+                mode => return Err(format!("Unknown output mode '{}'", mode))
+            },
+            "-h" | "--help" => options.help = true,
+            file if options.file.is_none() && (file == "-" || !file.starts_with('-')) => options.file = Some(file.to_string()),
+            argument => return Err(format!("Unknown argument '{}'", argument))
+        }
+    }
+
+    Ok(options)
+}
+
+fn create_smartcalc(options: &Options) -> Result<SmartCalc, String> {
+    let mut smartcalc = SmartCalc::default();
+
+    /* Library logs are written to the standard output */
+    log::set_max_level(log::LevelFilter::Off);
+
+    if let Some(timezone) = &options.timezone {
+        smartcalc.set_timezone(timezone.to_string())?;
+    }
+
+    if let Some(separator) = &options.decimal_separator {
+        smartcalc.set_decimal_seperator(separator.to_string());
+    }
+
+    if let Some(separator) = &options.thousand_separator {
+        smartcalc.set_thousand_separator(separator.to_string());
+    }
+
+    if let Some(path) = &options.rates {
+        let content = fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+        let rates = CurrencyRates::parse(&content).map_err(|error| format!("{}: {}", path, error))?;
+        smartcalc.update_currency_rates(&rates).map_err(|error| format!("{}: {}", path, error))?;
+    }
+
+    if let Some(path) = &options.rate_history {
+        let content = fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+        let history = CurrencyRateHistory::parse(&content).map_err(|error| format!("{}: {}", path, error))?;
+        smartcalc.set_currency_rate_history(&history).map_err(|error| format!("{}: {}", path, error))?;
+    }
+
+    Ok(smartcalc)
+}
+
+fn color_code(ui_type: &UiTokenType) -> &'static str {
+    match ui_type {
+        UiTokenType::Number => "33",
+        UiTokenType::Symbol1 | UiTokenType::Symbol2 => "36",
+        UiTokenType::DateTime | UiTokenType::Month => "35",
+        UiTokenType::Operator => "1",
+        UiTokenType::Comment => "2",
+        UiTokenType::VariableDefination | UiTokenType::VariableUse => "34",
+        UiTokenType::Constant => "32",
+        UiTokenType::Text => "0"
+    }
+}
+
+/* Ui token positions are character positions */
+fn colorize(text: &str, ui_tokens: &[UiToken]) -> String {
+    let mut tokens = ui_tokens.iter().collect::<Vec<_>>();
+    tokens.sort_by_key(|token| token.start);
+
+    let mut result = String::new();
+    let mut tokens = tokens.into_iter().peekable();
+    let mut current: Option<&UiToken> = None;
+
+    for (index, ch) in text.chars().enumerate() {
+        if current.is_some_and(|token| token.end <= index) {
+            result.push_str("\x1b[0m");
+            current = None;
+        }
+
+        if current.is_none() {
+            while tokens.peek().is_some_and(|token| token.end <= index) {
+                tokens.next();
+            }
+
+            if tokens.peek().is_some_and(|token| token.start <= index) {
+                current = tokens.next();
+                result.push_str(&format!("\x1b[{}m", color_code(&current.unwrap().ui_type)));
+            }
+        }
+
+        result.push(ch);
+    }
+
+    if current.is_some() {
+        result.push_str("\x1b[0m");
+    }
+    result
+}
+
+fn format_line(mode: OutputMode, text: &str, line: &Option<ExecuteLine>) -> String {
+    match (mode, line) {
+        (OutputMode::Json, line) => serde_json::to_string(&line.as_ref().map(LineResult::from)).unwrap_or_default(),
+        (OutputMode::Plain, None) => String::new(),
+        (OutputMode::Plain, Some(line)) => match &line.result {
+            Ok(result) => result.output.to_string(),
+            Err(error) => format!("error: {}", error)
+        },
+        (OutputMode::Color, None) => text.to_string(),
+        (OutputMode::Color, Some(line)) => match &line.result {
+            Ok(result) => format!("{}  \x1b[1;32m= {}\x1b[0m", colorize(text, &line.ui_tokens), result.output),
+            Err(error) => format!("{}  \x1b[31merror: {}\x1b[0m", colorize(text, &line.ui_tokens), error)
+        }
+    }
+}
+
+fn run_batch(smartcalc: &SmartCalc, options: &Options, text: String) -> bool {
+    let mut session = Session::new();
+    session.set_language(options.language.to_string());
+    session.set_text(text.to_string());
+
+    let results = smartcalc.execute_session(&session);
+    let status = !results.lines.iter().any(|line| matches!(line, Some(line) if line.result.is_err()));
+
+    match options.output {
+        OutputMode::Json => println!("{}", JsonExecuteResult::from(&results).to_json()),
+        mode => {
+            for (line_text, line) in text.lines().zip(results.lines.iter()) {
+                println!("{}", format_line(mode, line_text, line));
+            }
+        }
+    }
+
+    status
+}
+
+/* All inputs are kept in the session, so variables and line references of the previous inputs could be used. Only the new input is calculated */
+fn run_repl(smartcalc: &SmartCalc, options: &Options) {
+    let mut session = Session::new();
+    session.set_language(options.language.to_string());
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+
+    loop {
+        print!("> ");
+        let _ = io::stdout().flush();
+
+        let mut line = String::new();
+        match input.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => ()
+        }
+
+        let line = line.trim_end_matches(&['\r', '\n'][..]).to_string();
+        match line.trim() {
+            ":quit" | ":q" | "exit" => break,
+            ":clear" => {
+                session = Session::new();
+                session.set_language(options.language.to_string());
+                continue;
+            },
+            _ => ()
+        }
+
+        session.add_line(line.to_string());
+        let results = smartcalc.reexecute(&session);
+
+        /* New line is the last changed line */
+        if let Some((_, result)) = results.changed_lines.last() {
+            println!("{}", format_line(options.output, &line, result));
+        }
+    }
+}
+
+fn run(arguments: impl Iterator<Item = String>) -> i32 {
+    let options = match parse_arguments(arguments) {
+        Ok(options) if options.help => {
+            println!("{}", USAGE);
+            return 0;
+        },
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            return EXIT_USAGE_ERROR;
+        }
+    };
+
+    let smartcalc = match create_smartcalc(&options) {
+        Ok(smartcalc) => smartcalc,
+        Err(error) => {
+            eprintln!("{}", error);
+            return EXIT_USAGE_ERROR;
+        }
+    };
+
+    let text = match options.file.as_deref() {
+        Some("-") => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text).map(|_| text)
+        },
+        Some(path) => fs::read_to_string(path),
+        None if io::stdin().is_terminal() => {
+            run_repl(&smartcalc, &options);
+            return 0;
+        },
+        None => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text).map(|_| text)
+        }
+    };
+
+    match text {
+        Ok(text) => match run_batch(&smartcalc, &options, text) {
+            true => 0,
+            false => EXIT_LINE_ERROR
+        },
+        Err(error) => {
+            eprintln!("{}", error);
+            EXIT_USAGE_ERROR
+        }
+    }
+}
+
+fn main() {
+    process::exit(run(std::env::args().skip(1)));
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use smartcalc::{SmartCalc, UiToken, UiTokenType};
+
+    use super::*;
+
+    fn arguments(items: &[&str]) -> impl Iterator<Item = String> {
+        items.iter().map(|item| item.to_string()).collect::<Vec<_>>().into_iter()
+    }
+
+    fn worksheet(name: &str, text: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("smartcalc-cli-{}-{}", std::process::id(), name));
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn parse_arguments_test() {
+        let options = parse_arguments(arguments(&[])).unwrap();
+        assert_eq!((options.language.as_str(), options.output, options.file, options.help), ("en", OutputMode::Plain, None, false));
+
+        let options = parse_arguments(arguments(&["-l", "tr", "--timezone", "EST", "-d", ".", "-s", ",", "-r", "rates.json", "--rate-history", "history.csv", "-o", "json", "worksheet.txt"])).unwrap();
+        assert_eq!(options.language, "tr");
+        assert_eq!(options.timezone.as_deref(), Some("EST"));
+        assert_eq!(options.decimal_separator.as_deref(), Some("."));
+        assert_eq!(options.thousand_separator.as_deref(), Some(","));
+        assert_eq!(options.rates.as_deref(), Some("rates.json"));
+        assert_eq!(options.rate_history.as_deref(), Some("history.csv"));
+        assert_eq!(options.output, OutputMode::Json);
+        assert_eq!(options.file.as_deref(), Some("worksheet.txt"));
+
+        assert_eq!(parse_arguments(arguments(&["--output", "color", "-"])).map(|options| (options.output, options.file)), Ok((OutputMode::Color, Some("-".to_string()))));
+        assert!(parse_arguments(arguments(&["--help"])).unwrap().help);
+
+        assert_eq!(parse_arguments(arguments(&["--language"])).err().as_deref(), Some("'--language' requires a value"));
+        assert_eq!(parse_arguments(arguments(&["-o", "html"])).err().as_deref(), Some("Unknown output mode 'html'"));
+        assert_eq!(parse_arguments(arguments(&["--verbose"])).err().as_deref(), Some("Unknown argument '--verbose'"));
+        assert_eq!(parse_arguments(arguments(&["a.txt", "b.txt"])).err().as_deref(), Some("Unknown argument 'b.txt'"));
+    }
+
+    #[test]
+    fn colorize_test() {
+        let tokens = [
+            UiToken { start: 5, end: 6, ui_type: UiTokenType::Operator },
+            UiToken { start: 0, end: 4, ui_type: UiTokenType::Number }
+        ];
+        assert_eq!(colorize("1000 + x", &tokens), "\x1b[33m1000\x1b[0m \x1b[1m+\x1b[0m x");
+
+        /* Positions are character positions, token at the end of the text is closed */
+        let tokens = [UiToken { start: 2, end: 3, ui_type: UiTokenType::Symbol1 }];
+        assert_eq!(colorize("10€", &tokens), "10\x1b[36m€\x1b[0m");
+        assert_eq!(colorize("10 eur", &[]), "10 eur");
+    }
+
+    #[test]
+    fn format_line_test() {
+        let smartcalc = SmartCalc::default();
+        let results = smartcalc.execute("en", "10 + 5\n\n1 / 0");
+
+        assert_eq!(format_line(OutputMode::Plain, "10 + 5", &results.lines[0]), "15");
+        assert_eq!(format_line(OutputMode::Plain, "", &results.lines[1]), "");
+        assert_eq!(format_line(OutputMode::Plain, "1 / 0", &results.lines[2]), "error: Division by zero");
+
+        assert_eq!(format_line(OutputMode::Color, "10 + 5", &results.lines[0]), "\x1b[33m10\x1b[0m \x1b[1m+\x1b[0m \x1b[33m5\x1b[0m  \x1b[1;32m= 15\x1b[0m");
+        assert_eq!(format_line(OutputMode::Color, "", &results.lines[1]), "");
+        assert!(format_line(OutputMode::Color, "1 / 0", &results.lines[2]).ends_with("  \x1b[31merror: Division by zero\x1b[0m"));
+
+        assert_eq!(format_line(OutputMode::Json, "", &results.lines[1]), "null");
+        assert!(format_line(OutputMode::Json, "10 + 5", &results.lines[0]).contains("\"output\":\"15\""));
+    }
+
+    #[test]
+    fn exit_codes() {
+        let valid = worksheet("valid.txt", "price = 10\nprice * 2");
+        let invalid = worksheet("invalid.txt", "10\n1 / 0");
+        let rates = worksheet("rates.json", "{ not json");
+
+        assert_eq!(run(arguments(&["--help"])), 0);
+        assert_eq!(run(arguments(&[valid.to_str().unwrap()])), 0);
+        assert_eq!(run(arguments(&["-o", "json", invalid.to_str().unwrap()])), EXIT_LINE_ERROR);
+        assert_eq!(run(arguments(&["--unknown"])), EXIT_USAGE_ERROR);
+        assert_eq!(run(arguments(&["missing-worksheet.txt"])), EXIT_USAGE_ERROR);
+        assert_eq!(run(arguments(&["-r", rates.to_str().unwrap(), valid.to_str().unwrap()])), EXIT_USAGE_ERROR);
+        assert_eq!(run(arguments(&["-t", "Mars/Base", valid.to_str().unwrap()])), EXIT_USAGE_ERROR);
+
+        for path in [valid, invalid, rates] {
+            fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,440 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use core::any::{Any, TypeId};
+use core::ops::Deref;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::ToString;
+use alloc::string::String;
+use alloc::vec::Vec;
+use chrono::Duration;
+use crate::session::Session;
+use crate::config::{DynamicType, SmartCalcConfig, UnitBase};
+use crate::types::{CurrencyInfo, NumberType, TokenType};
+use crate::compiler::dynamic_type::DynamicTypeItem;
+use crate::compiler::duration::DurationItem;
+use crate::compiler::money::MoneyItem;
+use crate::compiler::number::NumberItem;
+use crate::tokinizer::read_currency;
+use super::{DataItem, OperationType, UnaryType};
+use crate::formatter::format_number;
+use crate::tools::{do_calculation, percent_of, round_number};
+
+/* Dynamic type group that durations are converted into, "100 km / 2 hours" is "50 km/h" */
+pub const TIME_GROUP: &str = "time";
+
+const SUPERSCRIPTS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+
+#[derive(Debug, Clone)]
+pub enum UnitKind {
+    Dynamic(Rc<DynamicType>),
+    Currency(Rc<CurrencyInfo>)
+}
+
+impl PartialEq for UnitKind {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (UnitKind::Dynamic(l_type), UnitKind::Dynamic(r_type)) => l_type.group_name == r_type.group_name && l_type.index == r_type.index,
+            (UnitKind::Currency(l_currency), UnitKind::Currency(r_currency)) => l_currency == r_currency,
+            (_, _) => false
+        }
+    }
+}
+
+impl UnitKind {
+    pub fn name(&self) -> String {
+        match self {
+            UnitKind::Dynamic(dynamic_type) => dynamic_type.symbol().to_string(),
+            UnitKind::Currency(currency) => currency.code.to_uppercase()
+        }
+    }
+
+    /* How many target units are in one unit, None if they measure different things. Units with offset are scaled as differences */
+    fn factor(&self, config: &SmartCalcConfig, target: &UnitKind) -> Option<f64> {
+        match (self, target) {
+            (UnitKind::Dynamic(source), UnitKind::Dynamic(target)) if source == target => Some(1.0),
+            (UnitKind::Dynamic(source), UnitKind::Dynamic(target)) => DynamicTypeItem::convert_difference(config, 1.0, source.clone(), target.names[0].to_string()).ok().map(|(factor, _)| factor),
+            (UnitKind::Currency(source), UnitKind::Currency(target)) if source == target => Some(1.0),
+            (UnitKind::Currency(source), UnitKind::Currency(target)) => {
+                let source_rate = config.currency_rate.get(source)?;
+                let target_rate = config.currency_rate.get(target)?;
+                Some(do_calculation(config, *target_rate, *source_rate, OperationType::Div))
+            },
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitTerm {
+    pub kind: UnitKind,
+    pub exponent: i32
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CompoundUnit {
+    pub terms: Vec<UnitTerm>
+}
+
+impl CompoundUnit {
+    pub fn new(kind: UnitKind, exponent: i32) -> Self {
+        CompoundUnit { terms: alloc::vec![UnitTerm { kind, exponent }] }
+    }
+
+    /* Unit name with an optional exponent, like "km", "m²", "s⁻¹" or "usd" */
+    pub fn parse(config: &SmartCalcConfig, text: &str) -> Option<Self> {
+        let name = text.trim_end_matches(|ch| SUPERSCRIPTS.contains(&ch) || ch == '⁻');
+        let exponent = match &text[name.len()..] {
+            "" => 1,
+            exponent => {
+                let (sign, digits) = match exponent.strip_prefix('⁻') {
+                    Some(digits) => (-1, digits),
+                    None => (1, exponent)
+                };
+                sign * digits.chars().try_fold(0_i32, |number, ch| number.checked_mul(10)?.checked_add(SUPERSCRIPTS.iter().position(|digit| *digit == ch)? as i32))?
+            }
+        };
+
+        if exponent == 0 {
+            return None;
+        }
+
+        if let Some(dynamic_type) = config.find_dynamic_type(name) {
+            return Some(CompoundUnit::new(UnitKind::Dynamic(dynamic_type), exponent));
+        }
+
+        read_currency(config, name).map(|currency| CompoundUnit::new(UnitKind::Currency(currency), exponent))
+    }
+
+    /* Units written with "/" and "·", like "m/s", "kg·m²", "J/s" or "kg/(s·m)". Every unit written by format is parsed */
+    pub fn parse_text(config: &SmartCalcConfig, text: &str) -> Option<Self> {
+        /* Names could have "/" like "kB/s" */
+        if let Some(unit) = CompoundUnit::parse(config, text.trim()) {
+            return Some(unit);
+        }
+
+        let mut unit = CompoundUnit::default();
+        for (index, part) in text.split('/').enumerate() {
+            let part = part.trim();
+            let part = part.strip_prefix('(').and_then(|part| part.strip_suffix(')')).unwrap_or(part);
+            for name in part.split('·') {
+                let term = CompoundUnit::parse(config, name.trim())?;
+                unit.terms.extend(match index {
+                    0 => term.terms,
+                    _ => term.inverse().terms
+                });
+            }
+        }
+
+        match unit.is_empty() {
+            true => None,
+            false => Some(unit)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn powi(&self, exponent: i32) -> Self {
+        CompoundUnit { terms: self.terms.iter().map(|term| UnitTerm { kind: term.kind.clone(), exponent: term.exponent * exponent }).filter(|term| term.exponent != 0).collect() }
+    }
+
+    pub fn inverse(&self) -> Self {
+        self.powi(-1)
+    }
+
+    /* Multiplies units, other's terms are converted into the matching terms. Returned factor is applied to other's number */
+    pub fn combine(&self, config: &SmartCalcConfig, other: &CompoundUnit) -> (f64, CompoundUnit) {
+        let mut terms = self.terms.clone();
+        let mut factor = 1.0;
+
+        for term in other.terms.iter() {
+            let matched = terms.iter().enumerate().find_map(|(index, item)| term.kind.factor(config, &item.kind).map(|unit_factor| (index, unit_factor)));
+            match matched {
+                Some((index, unit_factor)) => {
+                    factor = do_calculation(config, factor, unit_factor.powi(term.exponent), OperationType::Mul);
+                    terms[index].exponent += term.exponent;
+                },
+                None => terms.push(term.clone())
+            }
+        }
+
+        terms.retain(|term| term.exponent != 0);
+        (factor, CompoundUnit { terms })
+    }
+
+    /* Units of groups with a base unit written in their base unit, "hectare" is "10.000 m²". Returned factor is applied to the number */
+    fn expand(&self, config: &SmartCalcConfig) -> (f64, CompoundUnit) {
+        let mut factor = 1.0;
+        let mut unit = CompoundUnit::default();
+
+        for term in self.terms.iter() {
+            let base = match &term.kind {
+                UnitKind::Dynamic(dynamic_type) if dynamic_type.is_linear() => match (config.type_units.get(&dynamic_type.group_name), dynamic_type.base) {
+                    (Some(base_unit), Some(UnitBase::Affine { scale, .. })) => Some((scale.powi(term.exponent), base_unit.powi(term.exponent))),
+                    _ => None
+                },
+                _ => None
+            };
+
+            let (term_factor, term_unit) = base.unwrap_or_else(|| (1.0, CompoundUnit { terms: alloc::vec![term.clone()] }));
+            let (unit_factor, combined) = unit.combine(config, &term_unit);
+            factor = do_calculation(config, factor, do_calculation(config, term_factor, unit_factor, OperationType::Mul), OperationType::Mul);
+            unit = combined;
+        }
+
+        (factor, unit)
+    }
+
+    /* Number in target unit, None if units do not have the same dimensions */
+    pub fn convert(&self, config: &SmartCalcConfig, number: f64, target: &CompoundUnit) -> Option<f64> {
+        let (factor, rest) = target.inverse().combine(config, self);
+        if rest.is_empty() {
+            return Some(do_calculation(config, number, factor, OperationType::Mul));
+        }
+
+        let (source_factor, source) = self.expand(config);
+        let (target_factor, target) = target.expand(config);
+        let (factor, rest) = target.inverse().combine(config, &source);
+        match rest.is_empty() {
+            true => Some(do_calculation(config, do_calculation(config, number, source_factor * factor, OperationType::Mul), target_factor, OperationType::Div)),
+            false => None
+        }
+    }
+
+    fn format_terms<'a>(terms: impl Iterator<Item = (&'a UnitKind, i32)>) -> String {
+        terms.map(|(kind, exponent)| match exponent {
+            1 => kind.name(),
+            _ => format!("{}{}", kind.name(), superscript(exponent))
+        }).collect::<Vec<_>>().join("·")
+    }
+
+    /* Unit as "km/h", "m²", "kg·m/s²" or "s⁻¹" */
+    pub fn format(&self) -> String {
+        let numerator = CompoundUnit::format_terms(self.terms.iter().filter(|term| term.exponent > 0).map(|term| (&term.kind, term.exponent)));
+        let denominator_count = self.terms.iter().filter(|term| term.exponent < 0).count();
+        let denominator = CompoundUnit::format_terms(self.terms.iter().filter(|term| term.exponent < 0).map(|term| (&term.kind, -term.exponent)));
+
+        match (numerator.is_empty(), denominator_count) {
+            (_, 0) => numerator,
+            (true, _) => CompoundUnit::format_terms(self.terms.iter().map(|term| (&term.kind, term.exponent))),
+            (false, 1) => format!("{}/{}", numerator, denominator),
+            (false, _) => format!("{}/({})", numerator, denominator)
+        }
+    }
+}
+
+fn superscript(number: i32) -> String {
+    let digits = number.unsigned_abs().to_string().chars().map(|digit| SUPERSCRIPTS[digit.to_digit(10).unwrap() as usize]).collect::<String>();
+    match number < 0 {
+        true => format!("⁻{}", digits),
+        false => digits
+    }
+}
+
+/* Number and unit of the item, durations are written in the biggest fitting time unit */
+pub(crate) fn as_compound(config: &SmartCalcConfig, item: &dyn DataItem) -> Option<(f64, CompoundUnit)> {
+    if let Some(dynamic_type) = item.as_any().downcast_ref::<DynamicTypeItem>() {
+        return Some((dynamic_type.get_number(), CompoundUnit::new(UnitKind::Dynamic(dynamic_type.get_type()), 1)));
+    }
+
+    if let Some(money) = item.as_any().downcast_ref::<MoneyItem>() {
+        return Some((money.get_price(), CompoundUnit::new(UnitKind::Currency(money.get_currency()), 1)));
+    }
+
+    if let Some(compound) = item.as_any().downcast_ref::<CompoundTypeItem>() {
+        return Some((compound.0, compound.1.deref().clone()));
+    }
+
+    let duration = item.as_any().downcast_ref::<DurationItem>()?;
+    let group = config.types.get(TIME_GROUP)?;
+    let (_, second) = group.iter().next()?;
+    let seconds = duration.get_duration().num_milliseconds() as f64 / 1000.0;
+
+    for (_, time_type) in group.iter().rev() {
+        let (number, _) = DynamicTypeItem::convert(config, seconds, second.clone(), time_type.names[0].to_string())?;
+        if number.abs() >= 1.0 || time_type.index == second.index {
+            return Some((number, CompoundUnit::new(UnitKind::Dynamic(time_type.clone()), 1)));
+        }
+    }
+
+    None
+}
+
+/* Simplest item for the unit, "m/m" is a number and "$/h * h" is money */
+pub(crate) fn from_compound(config: &SmartCalcConfig, number: f64, unit: CompoundUnit) -> Rc<dyn DataItem> {
+    match unit.terms.as_slice() {
+        [] => Rc::new(NumberItem(number.into(), NumberType::Decimal)),
+        [UnitTerm { kind: UnitKind::Currency(currency), exponent: 1 }] => Rc::new(MoneyItem(number.into(), currency.clone())),
+        [UnitTerm { kind: UnitKind::Dynamic(dynamic_type), exponent: 1 }] if dynamic_type.group_name == TIME_GROUP => {
+            let seconds = config.types.get(TIME_GROUP)
+                .and_then(|group| group.values().next())
+                .and_then(|second| DynamicTypeItem::convert(config, number, dynamic_type.clone(), second.names[0].to_string()));
+
+            match seconds {
+                Some((seconds, _)) => Rc::new(DurationItem(Duration::milliseconds((seconds * 1000.0).round() as i64))),
+                None => Rc::new(DynamicTypeItem(number, dynamic_type.clone()))
+            }
+        },
+        [UnitTerm { kind: UnitKind::Dynamic(dynamic_type), exponent: 1 }] => Rc::new(DynamicTypeItem(number, dynamic_type.clone())),
+        _ => Rc::new(CompoundTypeItem(number, Rc::new(unit)))
+    }
+}
+
+/* Calculation between two items with units, left and right are in the expression order */
+pub(crate) fn calculate_units(config: &SmartCalcConfig, left: (f64, CompoundUnit), right: (f64, CompoundUnit), operation_type: OperationType) -> Option<Rc<dyn DataItem>> {
+    let (left_number, left_unit) = left;
+    let (right_number, right_unit) = right;
+
+    match operation_type {
+        OperationType::Mul | OperationType::Div => {
+            let (right_number, right_unit) = match operation_type {
+                OperationType::Div => (do_calculation(config, 1.0, right_number, OperationType::Div), right_unit.inverse()),
+                _ => (right_number, right_unit)
+            };
+
+            let (factor, unit) = left_unit.combine(config, &right_unit);
+            let number = do_calculation(config, left_number, do_calculation(config, right_number, factor, OperationType::Mul), OperationType::Mul);
+            Some(from_compound(config, number, unit))
+        },
+        OperationType::Pow => None,
+        OperationType::IntDiv => {
+            let right_number = right_unit.convert(config, right_number, &left_unit)?;
+            Some(Rc::new(NumberItem(do_calculation(config, left_number, right_number, operation_type).into(), NumberType::Decimal)))
+        },
+        OperationType::Add | OperationType::Sub | OperationType::Mod => {
+            let right_number = right_unit.convert(config, right_number, &left_unit)?;
+            Some(from_compound(config, do_calculation(config, left_number, right_number, operation_type), left_unit))
+        }
+    }
+}
+
+/* Every exponent needs to stay whole, "(2 m) ^ 2" is "4 m²" and "4 m² ^ 0,5" is "2 m" */
+pub(crate) fn power_unit(config: &SmartCalcConfig, number: f64, unit: &CompoundUnit, exponent: f64) -> Option<Rc<dyn DataItem>> {
+    let mut terms = Vec::with_capacity(unit.terms.len());
+    for term in unit.terms.iter() {
+        let term_exponent = term.exponent as f64 * exponent;
+        if term_exponent.fract() != 0.0 || term_exponent.abs() > i32::MAX as f64 {
+            return None;
+        }
+
+        if term_exponent != 0.0 {
+            terms.push(UnitTerm { kind: term.kind.clone(), exponent: term_exponent as i32 });
+        }
+    }
+
+    Some(from_compound(config, do_calculation(config, number, exponent, OperationType::Pow), CompoundUnit { terms }))
+}
+
+#[derive(Debug)]
+
+pub struct CompoundTypeItem(pub f64, pub Rc<CompoundUnit>);
+
+impl CompoundTypeItem {
+    pub fn get_unit(&self) -> Rc<CompoundUnit> {
+        self.1.clone()
+    }
+}
+
+impl DataItem for CompoundTypeItem {
+    fn as_token_type(&self) -> TokenType {
+        TokenType::CompoundType(self.0, self.1.clone())
+    }
+    fn is_same(&self, other: &dyn Any) -> bool {
+        match other.downcast_ref::<(f64, Rc<CompoundUnit>)>() {
+            Some((l_value, l_unit)) => (l_value - self.0).abs() < f64::EPSILON && l_unit.deref() == self.1.deref(),
+            None => false
+        }
+    }
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn calculate(&self, config: &SmartCalcConfig, on_left: bool, other: &dyn DataItem, operation_type: OperationType) -> Option<Rc<dyn DataItem>> {
+        let other_number = match other.type_name() {
+            "NUMBER" => other.get_underlying_number(),
+            "PERCENT" => match operation_type {
+                OperationType::Add | OperationType::Sub => percent_of(config, self.0, other.get_underlying_number()),
+                _ => return None
+            },
+            _ => {
+                let other_unit = as_compound(config, other)?;
+                let this_unit = (self.0, self.1.deref().clone());
+                return match on_left {
+                    true => calculate_units(config, this_unit, other_unit, operation_type),
+                    false => calculate_units(config, other_unit, this_unit, operation_type)
+                };
+            }
+        };
+
+        match (operation_type, on_left) {
+            (OperationType::Pow, true) => power_unit(config, self.0, &self.1, other_number),
+            (OperationType::Pow, false) => None,
+            (OperationType::Div, false) => Some(from_compound(config, do_calculation(config, other_number, self.0, operation_type), self.1.inverse())),
+            (_, true) => Some(from_compound(config, do_calculation(config, self.0, other_number, operation_type), self.1.deref().clone())),
+            (_, false) => Some(from_compound(config, do_calculation(config, other_number, self.0, operation_type), self.1.deref().clone()))
+        }
+    }
+
+    fn get_number(&self, other: &dyn DataItem) -> f64 {
+       if self.type_name() == other.type_name() {
+           return self.0
+       }
+
+       other.get_underlying_number() * self.0
+    }
+
+    fn get_underlying_number(&self) -> f64 { self.0 }
+    fn type_name(&self) -> &'static str { "COMPOUND_TYPE" }
+    fn type_id(&self) -> TypeId { TypeId::of::<CompoundTypeItem>() }
+    fn print(&self, config: &SmartCalcConfig, session: &Session) -> String {
+        /* Rates are written with the money format, like "$12,50/h" */
+        let currency = self.1.terms.iter().position(|term| term.exponent == 1 && matches!(term.kind, UnitKind::Currency(_)));
+        if let Some(index) = currency {
+            if let UnitKind::Currency(currency) = &self.1.terms[index].kind {
+                let mut rest = self.1.deref().clone();
+                rest.terms.remove(index);
+
+                let money = MoneyItem(self.0.into(), currency.clone()).print(config, session);
+                return match rest.terms.iter().any(|term| term.exponent > 0) {
+                    true => format!("{}·{}", money, rest.format()),
+                    false if rest.terms.len() == 1 => format!("{}/{}", money, rest.inverse().format()),
+                    false => format!("{}/({})", money, rest.inverse().format())
+                };
+            }
+        }
+
+        let formated_number = format_number(round_number(config, self.0, 2), config.thousand_separator.to_string(), config.decimal_seperator.to_string(), 2, true, true);
+        format!("{} {}", formated_number, self.1.format())
+    }
+    fn unary(&self, unary: UnaryType) -> Rc<dyn DataItem> {
+        match unary {
+            UnaryType::Minus => Rc::new(Self(-self.0, self.1.clone())),
+            UnaryType::Plus => Rc::new(Self(self.0, self.1.clone()))
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn format_unit_test() {
+    let config = SmartCalcConfig::default();
+    let unit = |text: &str| CompoundUnit::parse(&config, text).unwrap();
+
+    assert_eq!(unit("km").combine(&config, &unit("h").inverse()).1.format(), "km/h".to_string());
+    assert_eq!(unit("m²").format(), "m²".to_string());
+    assert_eq!(unit("kg").combine(&config, &unit("m")).1.combine(&config, &unit("s").powi(-2)).1.format(), "kg·m/s²".to_string());
+    assert_eq!(unit("kg").combine(&config, &unit("s").combine(&config, &unit("m")).1.inverse()).1.format(), "kg/(s·m)".to_string());
+    assert_eq!(unit("s").inverse().format(), "s⁻¹".to_string());
+    assert_eq!(unit("m").powi(12).format(), "m¹²".to_string());
+    assert_eq!(unit("km").combine(&config, &unit("m")).1, unit("km²"));
+    assert!(CompoundUnit::parse(&config, "apple").is_none());
+
+    /* Formatted units are parsed back */
+    for text in ["km/h", "m²", "kg·m/s²", "kg/(s·m)", "s⁻¹", "m¹²", "kW·h", "J/s", "kB/s", "USD⁻¹", "K"] {
+        assert_eq!(CompoundUnit::parse_text(&config, text).map(|unit| unit.format()), Some(text.to_string()), "{}", text);
+    }
+}
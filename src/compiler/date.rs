@@ -8,7 +8,7 @@ use core::any::{Any, TypeId};
 use alloc::rc::Rc;
 use alloc::string::ToString;
 use alloc::string::String;
-use chrono::{Datelike, Duration, NaiveDate, Utc, TimeZone};
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Utc};
 use crate::session::Session;
 use crate::compiler::duration::DurationItem;
 use crate::config::SmartCalcConfig;
@@ -134,9 +134,8 @@ impl DataItem for DateItem {
             false => format.date.get("full_date")
         };
 
-        let tz_offset = chrono::FixedOffset::east(self.1.offset * 60);
-        let datetime = tz_offset.from_utc_date(&self.0);
-        
+        let datetime = self.0;
+
         match date_format {
             Some(data) => {
                 match get_month_info(config, &format.language, datetime.month() as u8) {
@@ -148,7 +147,7 @@ impl DataItem for DateItem {
                         .replace("{month_long}", &uppercase_first_letter(&month_info.long))
                         .replace("{month_short}", &uppercase_first_letter(&month_info.short))
                         .replace("{year}", &datetime.year().to_string())
-                        .replace("{timezone}", &self.1.name),
+                        .replace("{timezone}", &self.1.name_at(&self.0.and_time(NaiveTime::MIN))),
                     None => datetime.to_string()
                 }
             },
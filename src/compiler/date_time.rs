@@ -9,8 +9,7 @@ use alloc::rc::Rc;
 use alloc::string::ToString;
 use alloc::string::String;
 use alloc::format;
-use chrono::{Datelike, NaiveDateTime, Timelike, Utc};
-use chrono::TimeZone;
+use chrono::{Datelike, Duration, NaiveDateTime, Timelike, Utc};
 use crate::session::Session;
 use crate::compiler::duration::DurationItem;
 use crate::config::SmartCalcConfig;
@@ -51,13 +50,16 @@ impl DataItem for DateTimeItem {
             return None;
         }
 
-        let date = self.0;
-        let duration = other.as_any().downcast_ref::<DurationItem>().unwrap().get_duration();
-        match operation_type {
-            OperationType::Add => Some(Rc::new(DateTimeItem(date + duration, self.1.clone()))),
-            OperationType::Sub => Some(Rc::new(DateTimeItem(date - duration, self.1.clone()))),
-            _ => None
-        }
+        let duration = match operation_type {
+            OperationType::Add => other.as_any().downcast_ref::<DurationItem>().unwrap().get_duration(),
+            OperationType::Sub => -other.as_any().downcast_ref::<DurationItem>().unwrap().get_duration(),
+            _ => return None
+        };
+
+        /* Days are added to the local date, so the clock time does not change at the DST transitions */
+        let days = Duration::days(duration.num_days());
+        let date = self.1.to_utc(&(self.1.to_local(&self.0) + days)) + (duration - days);
+        Some(Rc::new(DateTimeItem(date, self.1.clone())))
     }
     
     fn get_number(&self, _: &dyn DataItem) -> f64 {
@@ -77,8 +79,7 @@ impl DataItem for DateTimeItem {
             }
         };
         
-        let tz_offset = chrono::FixedOffset::east(self.1.offset * 60);
-        let datetime = tz_offset.from_utc_datetime(&self.0);
+        let datetime = self.1.to_local(&self.0);
         
         let date_format = match datetime.year() == Utc::now().date().year() {
             true => format.date.get("current_year_with_time"),
@@ -102,7 +103,7 @@ impl DataItem for DateTimeItem {
                         .replace("{month_long}", &uppercase_first_letter(&month_info.long))
                         .replace("{month_short}", &uppercase_first_letter(&month_info.short))
                         .replace("{year}", &datetime.year().to_string())
-                        .replace("{timezone}", &self.1.name),
+                        .replace("{timezone}", &self.1.name_at(&self.0)),
                     None => datetime.to_string()
                 }
             },
@@ -6,21 +6,22 @@
 
 use core::any::{Any, TypeId};
 use alloc::collections::BTreeMap;
+use alloc::format;
 use alloc::rc::Rc;
 use alloc::string::ToString;
 use alloc::string::String;
-use alloc::vec::Vec;
 use core::ops::Deref;
-use crate::SmartCalc;
 use crate::session::Session;
-use crate::config::DynamicType;
+use crate::config::{DynamicType, UnitBase};
 use crate::config::SmartCalcConfig;
+use crate::error::ErrorKind;
 use crate::types::TokenType;
 use crate::compiler::number::NumberItem;
 use crate::types::NumberType;
 use super::{DataItem, OperationType, UnaryType};
+use super::compound_type::{as_compound, calculate_units, from_compound, power_unit, CompoundUnit, UnitKind};
 use crate::formatter::format_number;
-use crate::tools::do_divition;
+use crate::tools::{do_calculation, percent_of, round_number};
 
 #[derive(Debug)]
 
@@ -34,17 +35,28 @@ impl DynamicTypeItem {
     pub fn get_number(&self) -> f64 {
         self.0
     }
+
+    fn is_linear_group(&self, config: &SmartCalcConfig) -> bool {
+        config.types.get(&self.1.group_name).is_none_or(|group| group.values().all(|dynamic_type| dynamic_type.is_linear()))
+    }
     
-    fn  calculate_unit(config: &SmartCalcConfig, number: f64, source_type: Rc<DynamicType>, target_type: Rc<DynamicType>, group: &BTreeMap<usize, Rc<DynamicType>>) -> Option<f64> {
+    fn  calculate_unit(config: &SmartCalcConfig, number: f64, source_type: Rc<DynamicType>, target_type: Rc<DynamicType>, group: &BTreeMap<usize, Rc<DynamicType>>) -> Result<f64, ErrorKind> {
         
         if source_type.index == target_type.index {
-            return Some(number);
+            return Ok(number);
+        }
+
+        /* Inverse units have no value for zero, "0 mpg" */
+        if let (Some(source_base), Some(target_base)) = (source_type.base, target_type.base) {
+            let base = source_base.to_base(config, number).ok_or(ErrorKind::DivisionByZero)?;
+            return target_base.to_unit(config, base).ok_or(ErrorKind::DivisionByZero);
         }
         
+        let incompatible = || ErrorKind::IncompatibleTypes { left: source_type.group_name.to_string(), right: target_type.group_name.to_string() };
         let mut number = number;
         let mut next_item = match group.get(&source_type.index) {
             Some(item) => item.clone(),
-            None => return None
+            None => return Err(incompatible())
         };
 
         let (mut search_index, is_upgrade) = match source_type.index > target_type.index {
@@ -53,19 +65,16 @@ impl DynamicTypeItem {
         };
 
         loop {
-            let code = match is_upgrade {
-                true => &next_item.upgrade_code[..],
-                false => &next_item.downgrade_code[..]
+            let formula = match is_upgrade {
+                true => next_item.upgrade.as_ref().ok_or_else(incompatible)?,
+                false => next_item.downgrade.as_ref().ok_or_else(incompatible)?
             };
             
-            number = match SmartCalc::basic_execute(code.replace("{value}", &number.to_string()), config) {
-                Ok(number) => number,
-                Err(_) => return None
-            };
+            number = formula.evaluate(config, number)?;
 
             next_item = match group.get(&search_index) {
                 Some(item) => item.clone(),
-                None => return None
+                None => return Err(incompatible())
             };
 
             search_index = match source_type.index > target_type.index {
@@ -79,64 +88,81 @@ impl DynamicTypeItem {
             
         }
         
-        Some(number)
+        Ok(number)
     }
     
     pub fn convert(config: &SmartCalcConfig, number: f64, source_type: Rc<DynamicType>, target_type: String) -> Option<(f64, Rc<DynamicType>)> {
-        let group = config.types.get(&source_type.group_name).unwrap();
-        let values: Vec<Rc<DynamicType>> = group.values().cloned().collect();
-        
-        /* In type calculation */
-        if let Some(target) = values.iter().find(|&s| s.names.contains(&target_type)) {
-            if source_type.index == target.index {
-                return Some((number, source_type.clone()));    
+        Self::try_convert(config, number, source_type, target_type).ok()
+    }
+
+    /* Absolute values of the groups with an offset, like temperatures, could not be below the zero of the base unit. "-300 °C" is below 0 K */
+    pub fn try_convert(config: &SmartCalcConfig, number: f64, source_type: Rc<DynamicType>, target_type: String) -> Result<(f64, Rc<DynamicType>), ErrorKind> {
+        let has_absolute_zero = config.types.get(&source_type.group_name)
+            .is_some_and(|group| group.values().any(|dynamic_type| matches!(dynamic_type.base, Some(UnitBase::Affine { offset, .. }) if offset != 0.0)));
+
+        if let (true, false, Some(base)) = (has_absolute_zero, source_type.difference, source_type.base) {
+            if base.to_base(config, number).is_some_and(|base| base < 0.0) {
+                return Err(ErrorKind::BelowAbsoluteZero);
             }
-            
-            let calculated_number = Self::calculate_unit(config, number, source_type.clone(), target.clone(), group)?;
-            return Some((calculated_number, target.clone()))
         }
-        
-        /* Calculation between types */
-        let type_conversion = match config.type_conversion.iter().find(|&s| s.source.name == source_type.group_name || s.target.name == source_type.group_name) {
-            Some(type_conversion) => type_conversion,
-            None => return None
-        };
-        
-        let (source_index, target_index) = match type_conversion.source.name == source_type.group_name {
-            true => (type_conversion.source.index, type_conversion.target.index),
-            false => (type_conversion.target.index, type_conversion.source.index)
-        };
-        
-        let target_dynamic_type = match group.get(&source_index) {
-            Some(target_type) => target_type,
-            None => return None
-        };
-        
-        let number = Self::calculate_unit(config, number, source_type.clone(), target_dynamic_type.clone(), group)?;
-        let code = match type_conversion.source.name == source_type.group_name {
-            true => &type_conversion.to_source_calculation[..],
-            false => &type_conversion.to_target_calculation[..]
-        };
 
-        let number = match SmartCalc::basic_execute(code.replace("{value}", &number.to_string()), config) {
-            Ok(number) => number,
-            Err(_) => return None
+        Self::convert_value(config, number, source_type, target_type)
+    }
+
+    /* Target unit could be in an other group, groups are connected with the shortest chain of type conversions */
+    fn convert_value(config: &SmartCalcConfig, number: f64, source_type: Rc<DynamicType>, target_type: String) -> Result<(f64, Rc<DynamicType>), ErrorKind> {
+        let target = match config.types.get(&source_type.group_name).and_then(|group| group.values().find(|&s| s.names.contains(&target_type))) {
+            Some(target) => target.clone(),
+            None => config.find_dynamic_type(&target_type).ok_or_else(|| ErrorKind::UnknownUnit(target_type.to_string()))?
         };
 
-        for (_, group) in config.types.iter() {
-            for (_, target_dynamic_type) in group.iter() {
-                
-                if target_dynamic_type.names.contains(&target_type) {
-                    let source_type = match group.get(&target_index) {
-                        Some(source_type) => source_type,
-                        None => return None
-                    };
-                    return Some((Self::calculate_unit(config, number, source_type.clone(), target_dynamic_type.clone(), group)?, target_dynamic_type.clone()));
-                }
-            }
+        let incompatible = || ErrorKind::IncompatibleTypes { left: source_type.group_name.to_string(), right: target.group_name.to_string() };
+        let mut number = number;
+        let mut current_type = source_type.clone();
+
+        for type_conversion in config.conversion_path(&source_type.group_name, &target.group_name)? {
+            let (from, to, formula) = match type_conversion.source.name == current_type.group_name {
+                true => (&type_conversion.source, &type_conversion.target, &type_conversion.to_source),
+                false => (&type_conversion.target, &type_conversion.source, &type_conversion.to_target)
+            };
+
+            let group = config.types.get(&current_type.group_name).ok_or_else(incompatible)?;
+            let bridge_type = group.get(&from.index).ok_or_else(incompatible)?;
+            number = Self::calculate_unit(config, number, current_type.clone(), bridge_type.clone(), group)?;
+            number = formula.evaluate(config, number)?;
+            current_type = config.types.get(&to.name).and_then(|group| group.get(&to.index)).ok_or_else(incompatible)?.clone();
         }
-        
-        None
+
+        let group = config.types.get(&target.group_name).ok_or_else(incompatible)?;
+        let number = Self::calculate_unit(config, number, current_type, target.clone(), group)?;
+        Ok((number, target))
+    }
+
+    /* Converts an amount of change, offsets are not applied. "Δ10 °C" is "Δ18 °F" */
+    pub fn convert_difference(config: &SmartCalcConfig, number: f64, source_type: Rc<DynamicType>, target_type: String) -> Result<(f64, Rc<DynamicType>), ErrorKind> {
+        let (zero, _) = Self::convert_value(config, 0.0, source_type.clone(), target_type.to_string())?;
+        let (value, target) = Self::convert_value(config, number, source_type, target_type)?;
+        Ok((value - zero, target))
+    }
+
+    /* Absolute values of affine units like "20 °C" are not added to each other, right side is used as a difference.
+       Subtracting two absolute values gives a difference. Inverse units are calculated in the left unit */
+    fn calculate_nonlinear(config: &SmartCalcConfig, left: &DynamicTypeItem, right: &DynamicTypeItem, operation_type: OperationType) -> Option<Rc<dyn DataItem>> {
+        let group = config.types.get(&left.1.group_name)?;
+        let has_inverse = group.values().any(|dynamic_type| matches!(dynamic_type.base, Some(UnitBase::Inverse { .. })));
+        let absolute = |item: &DynamicTypeItem, target: &Rc<DynamicType>| Self::convert(config, item.0, item.1.clone(), target.names[0].to_string()).map(|(number, _)| number);
+        let difference = |item: &DynamicTypeItem, target: &Rc<DynamicType>| Self::convert_difference(config, item.0, item.1.clone(), target.names[0].to_string()).ok().map(|(number, _)| number);
+
+        let (number, dynamic_type, is_difference) = match (operation_type, left.1.difference, right.1.difference) {
+            _ if has_inverse => (do_calculation(config, left.0, absolute(right, &left.1)?, operation_type), &left.1, false),
+            (OperationType::Add, false, _) | (OperationType::Sub, false, true) => (do_calculation(config, left.0, difference(right, &left.1)?, operation_type), &left.1, false),
+            (OperationType::Add, true, false) => (do_calculation(config, difference(left, &right.1)?, right.0, operation_type), &right.1, false),
+            (OperationType::Sub, false, false) => (do_calculation(config, left.0, absolute(right, &left.1)?, operation_type), &left.1, true),
+            (OperationType::Add | OperationType::Sub, true, true) => (do_calculation(config, left.0, difference(right, &left.1)?, operation_type), &left.1, true),
+            _ => return None
+        };
+
+        Some(Rc::new(DynamicTypeItem(number, dynamic_type.with_difference(is_difference))))
     }
 }
 
@@ -153,14 +179,29 @@ impl DataItem for DynamicTypeItem {
     fn as_any(&self) -> &dyn Any { self }
     
     fn calculate(&self, config: &SmartCalcConfig, on_left: bool, other: &dyn DataItem, operation_type: OperationType) -> Option<Rc<dyn DataItem>> {
-        let (other_number, is_same_type)  = match other.type_name() {
-            "NUMBER" => (other.get_underlying_number(), false),
-            "DYNAMIC_TYPE" => {
-                let other_dynamic_type: &DynamicTypeItem = other.as_any().downcast_ref::<DynamicTypeItem>().unwrap();
-                let (new_number, _) = DynamicTypeItem::convert(config, other_dynamic_type.get_number(), other_dynamic_type.get_type(), self.1.names[0].clone()).unwrap();
-                (new_number, true)
+        let other_number = match other.type_name() {
+            "NUMBER" => other.get_underlying_number(),
+            "PERCENT" => percent_of(config, self.0, other.get_underlying_number()),
+            "DYNAMIC_TYPE" if matches!(operation_type, OperationType::Add | OperationType::Sub) && !self.is_linear_group(config) => {
+                let other = other.as_any().downcast_ref::<DynamicTypeItem>()?;
+                if other.1.group_name != self.1.group_name {
+                    return None;
+                }
+
+                return match on_left {
+                    true => Self::calculate_nonlinear(config, self, other, operation_type),
+                    false => Self::calculate_nonlinear(config, other, self, operation_type)
+                };
+            },
+            "DYNAMIC_TYPE" | "MONEY" | "DURATION" | "COMPOUND_TYPE" => {
+                /* Units are multiplied into compound units, "100 km / 2 hours" is "50 km/h" */
+                let other_unit = as_compound(config, other)?;
+                let this_unit = (self.0, CompoundUnit::new(UnitKind::Dynamic(self.1.clone()), 1));
+                return match on_left {
+                    true => calculate_units(config, this_unit, other_unit, operation_type),
+                    false => calculate_units(config, other_unit, this_unit, operation_type)
+                };
             },
-            "PERCENT" => (do_divition(self.0, 100.0) * other.get_underlying_number(), true),
             _ => return None
         };
 
@@ -169,20 +210,14 @@ impl DataItem for DynamicTypeItem {
         } else { 
             (other_number, self.0) 
         };
-        
-        let result = match operation_type {
-            OperationType::Add => left + right,
-            OperationType::Div => {
-                match is_same_type {
-                    true => return Some(Rc::new(NumberItem(do_divition(left, right), NumberType::Decimal))),
-                    false => do_divition(left, right)
-                }
-            },
-            OperationType::Mul => left * right,
-            OperationType::Sub => left - right
-        };
-        
-        Some(Rc::new(DynamicTypeItem(result, self.1.clone())))
+
+        match (operation_type, on_left, other.type_name()) {
+            (OperationType::Pow, true, "NUMBER") => power_unit(config, self.0, &CompoundUnit::new(UnitKind::Dynamic(self.1.clone()), 1), other_number),
+            (OperationType::Pow, _, _) => None,
+            (OperationType::Div, false, "NUMBER") => Some(from_compound(config, do_calculation(config, left, right, operation_type), CompoundUnit::new(UnitKind::Dynamic(self.1.clone()), -1))),
+            (OperationType::Div | OperationType::IntDiv, _, "PERCENT") => Some(Rc::new(NumberItem(do_calculation(config, left, right, operation_type).into(), NumberType::Decimal))),
+            _ => Some(Rc::new(DynamicTypeItem(do_calculation(config, left, right, operation_type), self.1.clone())))
+        }
     }
     
     fn get_number(&self, other: &dyn DataItem) -> f64 {
@@ -196,13 +231,25 @@ impl DataItem for DynamicTypeItem {
     fn get_underlying_number(&self) -> f64 { self.0 }
     fn type_name(&self) -> &'static str { "DYNAMIC_TYPE" }
     fn type_id(&self) -> TypeId { TypeId::of::<DynamicTypeItem>() }
-    fn print(&self, config: &SmartCalcConfig, _: &Session) -> String {
+    fn print(&self, config: &SmartCalcConfig, session: &Session) -> String {
         let decimal_digit = self.1.decimal_digits.map_or(2, |x| x);
         let remove_fract_if_zero = self.1.remove_fract_if_zero.map_or(true, |x| x);
         let use_fract_rounding = self.1.use_fract_rounding.map_or(true, |x| x);
 
-        let formated_number = format_number(self.0, config.thousand_separator.to_string(), config.decimal_seperator.to_string(), decimal_digit, remove_fract_if_zero, use_fract_rounding);
-        self.1.format.replace("{value}", &formated_number)
+        let number = match use_fract_rounding {
+            true => round_number(config, self.0, decimal_digit),
+            false => self.0
+        };
+
+        let formated_number = format_number(number, config.thousand_separator.to_string(), config.decimal_seperator.to_string(), decimal_digit, remove_fract_if_zero, use_fract_rounding);
+        let format = config.format.get(&session.get_language())
+            .and_then(|format| format.dynamic_type.iter().find(|item| item.group == self.1.group_name && item.index == self.1.index))
+            .map_or(&self.1.format, |item| &item.format);
+
+        match self.1.difference {
+            true => format!("Δ{}", format.replace("{value}", &formated_number)),
+            false => format.replace("{value}", &formated_number)
+        }
     }
     fn unary(&self, unary: UnaryType) -> Rc<dyn DataItem> {
         match unary {
@@ -215,6 +262,7 @@ impl DataItem for DynamicTypeItem {
 #[cfg(test)]
 #[test]
 fn format_result_test() {
+    use alloc::vec::Vec;
     use crate::config::DynamicType;
     use crate::config::SmartCalcConfig;
     let config = SmartCalcConfig::default();
@@ -0,0 +1,158 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use alloc::boxed::Box;
+use core::iter::Peekable;
+use core::str::Chars;
+use alloc::string::String;
+use crate::config::SmartCalcConfig;
+use crate::error::ErrorKind;
+use crate::tools::do_calculation;
+use super::OperationType;
+
+/* Conversion code of a dynamic type like "{value} * 25.4", parsed once while the configuration is loaded */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Formula {
+    Value,
+    Number(f64),
+    Minus(Box<Formula>),
+    Operation(Box<Formula>, OperationType, Box<Formula>)
+}
+
+impl Formula {
+    /* Numbers are written with "." decimal point. Supports "+ - * / ^" and parentheses */
+    pub fn parse(code: &str) -> Option<Self> {
+        let mut chars = code.chars().peekable();
+        let formula = parse_expression(&mut chars)?;
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            Some(_) => None,
+            None => Some(formula)
+        }
+    }
+
+    pub fn evaluate(&self, config: &SmartCalcConfig, value: f64) -> Result<f64, ErrorKind> {
+        let result = match self {
+            Formula::Value => value,
+            Formula::Number(number) => *number,
+            Formula::Minus(formula) => -formula.evaluate(config, value)?,
+            Formula::Operation(left, operation_type, right) => {
+                let right = right.evaluate(config, value)?;
+                if matches!(operation_type, OperationType::Div | OperationType::IntDiv | OperationType::Mod) && right == 0.0 {
+                    return Err(ErrorKind::DivisionByZero);
+                }
+                do_calculation(config, left.evaluate(config, value)?, right, *operation_type)
+            }
+        };
+
+        match result.is_finite() {
+            true => Ok(result),
+            false => Err(ErrorKind::Overflow)
+        }
+    }
+
+    /* Scale and offset of the formula, "{value} * 9 / 5 + 32" is (1.8, 32). None if the value is not used linearly */
+    pub fn affine(&self) -> Option<(f64, f64)> {
+        match self {
+            Formula::Value => Some((1.0, 0.0)),
+            Formula::Number(number) => Some((0.0, *number)),
+            Formula::Minus(formula) => formula.affine().map(|(scale, offset)| (-scale, -offset)),
+            Formula::Operation(left, operation_type, right) => {
+                let (left_scale, left_offset) = left.affine()?;
+                let (right_scale, right_offset) = right.affine()?;
+                match operation_type {
+                    OperationType::Add => Some((left_scale + right_scale, left_offset + right_offset)),
+                    OperationType::Sub => Some((left_scale - right_scale, left_offset - right_offset)),
+                    OperationType::Mul if left_scale == 0.0 => Some((left_offset * right_scale, left_offset * right_offset)),
+                    OperationType::Mul if right_scale == 0.0 => Some((left_scale * right_offset, left_offset * right_offset)),
+                    OperationType::Div if right_scale == 0.0 && right_offset != 0.0 => Some((left_scale / right_offset, left_offset / right_offset)),
+                    OperationType::Pow if left_scale == 0.0 && right_scale == 0.0 => Some((0.0, left_offset.powf(right_offset))),
+                    _ => None
+                }
+            }
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+}
+
+fn parse_operator(chars: &mut Peekable<Chars>, operators: &[(char, OperationType)]) -> Option<OperationType> {
+    skip_whitespace(chars);
+    let operation_type = operators.iter().find(|(operator, _)| chars.peek() == Some(operator)).map(|(_, operation_type)| *operation_type)?;
+    chars.next();
+    Some(operation_type)
+}
+
+fn parse_expression(chars: &mut Peekable<Chars>) -> Option<Formula> {
+    let mut formula = parse_term(chars)?;
+    while let Some(operation_type) = parse_operator(chars, &[('+', OperationType::Add), ('-', OperationType::Sub)]) {
+        formula = Formula::Operation(Box::new(formula), operation_type, Box::new(parse_term(chars)?));
+    }
+    Some(formula)
+}
+
+fn parse_term(chars: &mut Peekable<Chars>) -> Option<Formula> {
+    let mut formula = parse_power(chars)?;
+    while let Some(operation_type) = parse_operator(chars, &[('*', OperationType::Mul), ('/', OperationType::Div)]) {
+        formula = Formula::Operation(Box::new(formula), operation_type, Box::new(parse_power(chars)?));
+    }
+    Some(formula)
+}
+
+fn parse_power(chars: &mut Peekable<Chars>) -> Option<Formula> {
+    let formula = parse_unary(chars)?;
+    match parse_operator(chars, &[('^', OperationType::Pow)]) {
+        Some(operation_type) => Some(Formula::Operation(Box::new(formula), operation_type, Box::new(parse_power(chars)?))),
+        None => Some(formula)
+    }
+}
+
+fn parse_unary(chars: &mut Peekable<Chars>) -> Option<Formula> {
+    skip_whitespace(chars);
+    match chars.peek()? {
+        '-' => {
+            chars.next();
+            Some(Formula::Minus(Box::new(parse_unary(chars)?)))
+        },
+        '(' => {
+            chars.next();
+            let formula = parse_expression(chars)?;
+            skip_whitespace(chars);
+            chars.next_if_eq(&')')?;
+            Some(formula)
+        },
+        '{' => {
+            let name: String = chars.by_ref().take_while(|ch| *ch != '}').collect();
+            (name == "{value").then_some(Formula::Value)
+        },
+        _ => {
+            let mut number = String::new();
+            while let Some(ch) = chars.next_if(|ch| ch.is_ascii_digit() || *ch == '.') {
+                number.push(ch);
+            }
+            number.parse::<f64>().ok().map(Formula::Number)
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn formula_test() {
+    let config = SmartCalcConfig::default();
+    assert_eq!(Formula::parse("{value} * 25.4").and_then(|formula| formula.evaluate(&config, 2.0).ok()), Some(50.8));
+    assert_eq!(Formula::parse("({value} - 32) * 5 / 9").and_then(|formula| formula.evaluate(&config, 212.0).ok()), Some(100.0));
+    assert_eq!(Formula::parse("-{value} + 2 ^ 3").and_then(|formula| formula.evaluate(&config, 1.0).ok()), Some(7.0));
+    assert_eq!(Formula::parse("100 / {value}").map(|formula| formula.evaluate(&config, 0.0)), Some(Err(ErrorKind::DivisionByZero)));
+    assert_eq!(Formula::parse("{value} * 9 / 5 + 32").and_then(|formula| formula.affine()), Some((1.8, 32.0)));
+    assert_eq!(Formula::parse("{value}").and_then(|formula| formula.affine()), Some((1.0, 0.0)));
+    assert_eq!(Formula::parse("100 / {value}").and_then(|formula| formula.affine()), None);
+    assert_eq!(Formula::parse("{value} * {value}").and_then(|formula| formula.affine()), None);
+    assert_eq!(Formula::parse("{value} * "), None);
+    assert_eq!(Formula::parse("{amount} * 2"), None);
+    assert_eq!(Formula::parse("({value} * 2"), None);
+}
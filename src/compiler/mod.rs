@@ -11,12 +11,16 @@ use core::ops::Deref;
 use alloc::rc::Rc;
 use alloc::string::String;
 use alloc::string::ToString;
-use alloc::format;
+use alloc::vec::Vec;
 
 use crate::session::Session;
+use crate::error::{ErrorKind, SmartCalcError, Span};
 use crate::config::SmartCalcConfig;
 use crate::types::*;
 use crate::variable::VariableInfo;
+use self::money::{add_currency_conversion, MoneyItem};
+use self::compound_type::{as_compound, calculate_units, from_compound, CompoundTypeItem, CompoundUnit, UnitKind, UnitTerm};
+use self::dynamic_type::DynamicTypeItem;
 
 pub mod number;
 pub mod percent;
@@ -26,14 +30,21 @@ pub mod duration;
 pub mod date;
 pub mod date_time;
 pub mod dynamic_type;
+pub mod compound_type;
+pub mod formula;
 
 #[derive(Clone)]
 #[derive(Copy)]
+#[derive(Debug)]
+#[derive(PartialEq)]
 pub enum OperationType {
     Add,
     Div,
     Mul,
-    Sub
+    Sub,
+    Pow,
+    Mod,
+    IntDiv
 }
 
 
@@ -57,21 +68,39 @@ pub trait DataItem: alloc::fmt::Debug {
     fn print(&self, config: &SmartCalcConfig, session: &Session) -> String;
 }
 
+/* Units are named with their group or compound unit in the errors, "metric-length and metric-weight are not compatible" */
+pub(crate) fn type_label(item: &dyn DataItem) -> String {
+    if let Some(dynamic_type) = item.as_any().downcast_ref::<DynamicTypeItem>() {
+        return dynamic_type.get_type().group_name.to_string();
+    }
+
+    match item.as_any().downcast_ref::<CompoundTypeItem>() {
+        Some(compound) => compound.get_unit().format(),
+        None => item.type_name().to_string()
+    }
+}
+
+pub(crate) fn incompatible_types(left: &dyn DataItem, right: &dyn DataItem) -> ErrorKind {
+    ErrorKind::IncompatibleTypes { left: type_label(left), right: type_label(right) }
+}
+
 pub struct Interpreter;
 
 impl Interpreter {
-    pub fn execute(config: &SmartCalcConfig, ast: Rc<SmartCalcAstType>, session: &Session) -> Result<Rc<SmartCalcAstType>, String> {
+    pub fn execute(config: &SmartCalcConfig, ast: Rc<SmartCalcAstType>, session: &Session) -> Result<Rc<SmartCalcAstType>, SmartCalcError> {
         Interpreter::execute_ast(config, session, ast)
     }
 
-    fn execute_ast(config: &SmartCalcConfig, session: &Session, ast: Rc<SmartCalcAstType>) -> Result<Rc<SmartCalcAstType>, String> {
+    fn execute_ast(config: &SmartCalcConfig, session: &Session, ast: Rc<SmartCalcAstType>) -> Result<Rc<SmartCalcAstType>, SmartCalcError> {
         match ast.deref() {
-            SmartCalcAstType::Binary { left, operator, right } => Interpreter::executer_binary(config, session, left.clone(), *operator, right.clone()),
+            SmartCalcAstType::Binary { left, operator, right, span } => Interpreter::executer_binary(config, session, left.clone(), *operator, right.clone(), *span),
             SmartCalcAstType::Assignment { variable, expression } => Interpreter::executer_assignment(config, session, variable.clone(), expression.clone()),
             SmartCalcAstType::Variable(variable)               => Ok(Interpreter::executer_variable(variable.clone())),
             SmartCalcAstType::Item(_)                          => Ok(ast),
             SmartCalcAstType::Month(_)                         => Ok(ast),
             SmartCalcAstType::PrefixUnary(ch, ast)             => Interpreter::executer_unary(config, session, *ch, ast.clone()),
+            SmartCalcAstType::Call { name, arguments, span }   => Interpreter::executer_call(config, session, name, arguments, *span),
+            SmartCalcAstType::Conversion { expression, unit, span } => Interpreter::executer_conversion(config, session, expression.clone(), unit, *span),
             SmartCalcAstType::None                             => Ok(Rc::new(SmartCalcAstType::None)),
             _ => {
                 log::debug!("Operation not implemented {:?}", ast);
@@ -84,57 +113,131 @@ impl Interpreter {
         variable.data.borrow().clone()
     }
 
-    fn executer_assignment(config: &SmartCalcConfig, session: &Session, variable: Rc<VariableInfo>, expression: Rc<SmartCalcAstType>) -> Result<Rc<SmartCalcAstType>, String> {
+    fn executer_assignment(config: &SmartCalcConfig, session: &Session, variable: Rc<VariableInfo>, expression: Rc<SmartCalcAstType>) -> Result<Rc<SmartCalcAstType>, SmartCalcError> {
         let computed  = Interpreter::execute_ast(config, session, expression)?;
         *variable.data.borrow_mut() = computed.clone();
+        session.set_line_assignment(variable, computed.clone());
         Ok(computed)
     }
     
-    fn calculate_item(config: &SmartCalcConfig, operator: char, left: Rc<SmartCalcAstType>, right: Rc<SmartCalcAstType>) -> Result<Rc<SmartCalcAstType>, String> {
-        let left = match left.deref() {
-            SmartCalcAstType::Item(left) => left.clone(),
-            _ => return Err("Unknown calculation".to_string())
+    fn calculate_item(config: &SmartCalcConfig, operator: char, left_ast: Rc<SmartCalcAstType>, right_ast: Rc<SmartCalcAstType>, span: Span) -> Result<Rc<SmartCalcAstType>, SmartCalcError> {
+        let (left, right) = match (left_ast.deref(), right_ast.deref()) {
+            (SmartCalcAstType::Item(left), SmartCalcAstType::Item(right)) => (left.clone(), right.clone()),
+            _ => return Err(SmartCalcError::new(ErrorKind::IncompatibleTypes { left: left_ast.type_name(), right: right_ast.type_name() }, span))
         };
         
-        let right = match right.deref() {
-            SmartCalcAstType::Item(right) => right.clone(),
-            _ => return Err("Unknown calculation".to_string())
+        let operation_type = match operator {
+            '+' => OperationType::Add,
+            '-' => OperationType::Sub,
+            '*' => OperationType::Mul,
+            '/' => OperationType::Div,
+            '^' => OperationType::Pow,
+            '%' => OperationType::Mod,
+            '\\' => OperationType::IntDiv,
+            _ => return Err(SmartCalcError::new(ErrorKind::UnknownOperator(operator), span))
         };
-        
-        let result = match operator {
-            '+' => left.calculate(config, true, right.deref(), OperationType::Add),
-            '-' => left.calculate(config, true, right.deref(), OperationType::Sub),
-            '*' => left.calculate(config, true, right.deref(), OperationType::Mul),
-            '/' => left.calculate(config, true, right.deref(), OperationType::Div),
-            _ => return Err(format!("Unknown operator. ({})", operator))
+
+        if matches!(operation_type, OperationType::Div | OperationType::IntDiv | OperationType::Mod) && right.get_underlying_number() == 0.0 {
+            return Err(SmartCalcError::new(ErrorKind::DivisionByZero, span));
+        }
+
+        /* "2 * 3 m" is calculated by right item, it knows how to handle number. "10 / $2" and "2 - 3 m" are not valid */
+        let result = match (left.calculate(config, true, right.deref(), operation_type), operation_type) {
+            (Some(item), _) => Some(item),
+            (None, OperationType::Add | OperationType::Mul) => right.calculate(config, false, left.deref(), operation_type),
+
+            /* Number does not have a unit, "10 / 4 m" is "2,5 m⁻¹" */
+            (None, OperationType::Div) if left.type_name() == "NUMBER" => as_compound(config, right.deref())
+                .and_then(|right_unit| calculate_units(config, (left.get_underlying_number(), CompoundUnit::default()), right_unit, operation_type)),
+            (None, _) => None
         };
         
+        /* "10 ^ 400" and "(-8) ^ 0,5" could not be represented */
         match result {
+            Some(item) if item.get_underlying_number().is_nan() => Err(SmartCalcError::new(ErrorKind::UndefinedOperation(operator), span)),
+            Some(item) if item.get_underlying_number().is_infinite() => Err(SmartCalcError::new(ErrorKind::Overflow, span)),
             Some(item) => Ok(Rc::new(SmartCalcAstType::Item(item.clone()))),
-            None => Err("Unknown calculation".to_string())
+            None => Err(SmartCalcError::new(incompatible_types(left.deref(), right.deref()), span))
         }
     }
 
-    fn executer_binary(config: &SmartCalcConfig, session: &Session, left: Rc<SmartCalcAstType>, operator: char, right: Rc<SmartCalcAstType>) -> Result<Rc<SmartCalcAstType>, String> {
+    fn executer_binary(config: &SmartCalcConfig, session: &Session, left: Rc<SmartCalcAstType>, operator: char, right: Rc<SmartCalcAstType>, span: Span) -> Result<Rc<SmartCalcAstType>, SmartCalcError> {
         let computed_left  = Interpreter::execute_ast(config, session, left)?;
         let computed_right = Interpreter::execute_ast(config, session, right)?;
 
+        if let (SmartCalcAstType::Item(left), SmartCalcAstType::Item(right)) = (computed_left.deref(), computed_right.deref()) {
+            add_currency_conversion(config, session, [left.deref(), right.deref()].iter().copied());
+        }
+
         match (computed_left.deref(), computed_right.deref()) {
-            (SmartCalcAstType::Item(_), _)           | (_, SmartCalcAstType::Item(_))           => Interpreter::calculate_item(config, operator, computed_left.clone(), computed_right.clone()),
-            _ => Err("Uknown calculation result".to_string())
+            (SmartCalcAstType::Item(_), _)           | (_, SmartCalcAstType::Item(_))           => Interpreter::calculate_item(config, operator, computed_left.clone(), computed_right.clone(), span),
+            _ => Err(SmartCalcError::new(ErrorKind::IncompatibleTypes { left: computed_left.type_name(), right: computed_right.type_name() }, span))
         }
     }
 
-    fn executer_unary(config: &SmartCalcConfig, session: &Session, operator: char, ast: Rc<SmartCalcAstType>) -> Result<Rc<SmartCalcAstType>, String> {
+    fn executer_call(config: &SmartCalcConfig, session: &Session, name: &str, arguments: &[Rc<SmartCalcAstType>], span: Span) -> Result<Rc<SmartCalcAstType>, SmartCalcError> {
+        let function = match config.functions.get(name) {
+            Some(function) => function,
+            None => return Err(SmartCalcError::new(ErrorKind::UnknownFunction(name.to_string()), span))
+        };
+
+        let mut items = Vec::with_capacity(arguments.len());
+        for argument in arguments.iter() {
+            match Interpreter::execute_ast(config, session, argument.clone())?.deref() {
+                SmartCalcAstType::Item(item) => items.push(item.clone()),
+                _ => return Err(SmartCalcError::new(ErrorKind::InvalidArgument { function: name.to_string() }, span))
+            };
+        }
+
+        add_currency_conversion(config, session, items.iter().map(|item| item.deref()));
+        match function.call(config, &items) {
+            Ok(item) => Ok(Rc::new(SmartCalcAstType::Item(item))),
+            Err(kind) => Err(SmartCalcError::new(kind, span))
+        }
+    }
+
+    /* Single units keep their own conversion rules, temperatures are converted with the offset */
+    fn executer_conversion(config: &SmartCalcConfig, session: &Session, expression: Rc<SmartCalcAstType>, unit: &CompoundUnit, span: Span) -> Result<Rc<SmartCalcAstType>, SmartCalcError> {
+        let computed = Interpreter::execute_ast(config, session, expression)?;
+        let item = match computed.deref() {
+            SmartCalcAstType::Item(item) => item.clone(),
+            _ => return Err(SmartCalcError::new(ErrorKind::IncompatibleTypes { left: computed.type_name(), right: unit.format() }, span))
+        };
+
+        add_currency_conversion(config, session, core::iter::once(item.deref()));
+        let incompatible = || SmartCalcError::new(ErrorKind::IncompatibleTypes { left: type_label(item.deref()), right: unit.format() }, span);
+
+        if let ([UnitTerm { kind: UnitKind::Dynamic(target), exponent: 1 }], Some(dynamic_type)) = (unit.terms.as_slice(), item.as_any().downcast_ref::<DynamicTypeItem>()) {
+            let (number, target) = DynamicTypeItem::try_convert(config, dynamic_type.get_number(), dynamic_type.get_type(), target.names[0].to_string()).map_err(|kind| SmartCalcError::new(kind, span))?;
+            return Ok(Rc::new(SmartCalcAstType::Item(Rc::new(DynamicTypeItem(number, target)))));
+        }
+
+        /* Money keeps its decimal value */
+        if let ([UnitTerm { kind: UnitKind::Currency(currency), exponent: 1 }], Some(money)) = (unit.terms.as_slice(), item.as_any().downcast_ref::<MoneyItem>()) {
+            let money = money.convert_to(config, currency.clone()).ok_or_else(incompatible)?;
+            return Ok(Rc::new(SmartCalcAstType::Item(Rc::new(money))));
+        }
+
+        let (number, source) = as_compound(config, item.deref()).ok_or_else(incompatible)?;
+        let number = source.convert(config, number, unit).ok_or_else(incompatible)?;
+        let converted: Rc<dyn DataItem> = match unit.terms.as_slice() {
+            [UnitTerm { kind: UnitKind::Dynamic(target), exponent: 1 }] => Rc::new(DynamicTypeItem(number, target.clone())),
+            _ => from_compound(config, number, unit.clone())
+        };
+
+        Ok(Rc::new(SmartCalcAstType::Item(converted)))
+    }
+
+    fn executer_unary(config: &SmartCalcConfig, session: &Session, operator: char, ast: Rc<SmartCalcAstType>) -> Result<Rc<SmartCalcAstType>, SmartCalcError> {
         let computed = Interpreter::execute_ast(config, session, ast)?;
 
         let result = match operator {
             '+' => return Ok(computed),
             '-' => match computed.deref() {
                 SmartCalcAstType::Item(item) => SmartCalcAstType::Item(item.unary(UnaryType::Minus)),
-                _ => return Err("Syntax error".to_string())
+                _ => return Err(SmartCalcError::new(ErrorKind::InvalidUnaryOperand, Span::default()))
             },
-            _ => return Err("Syntax error".to_string())
+            _ => return Err(SmartCalcError::new(ErrorKind::UnknownOperator(operator), Span::default()))
         };
 
         Ok(Rc::new(result))
@@ -7,21 +7,34 @@
 use core::any::{Any, TypeId};
 use alloc::format;
 use alloc::rc::Rc;
-use alloc::string::ToString;
+use alloc::collections::BTreeSet;
 use alloc::string::String;
 use core::ops::Deref;
 use crate::session::Session;
 use crate::config::SmartCalcConfig;
-use crate::types::{CurrencyInfo, TokenType, NumberType};
+use crate::types::{CurrencyInfo, TokenType, NumberType, NumberValue};
 
 use super::number::NumberItem;
+use super::compound_type::{as_compound, calculate_units, CompoundUnit, UnitKind};
 use super::{DataItem, OperationType, UnaryType};
-use crate::formatter::format_number;
-use crate::tools::do_divition;
+use crate::formatter::format_value;
+use crate::tools::{calculate_number, percent_of_number};
+
+/* Currency rates are used in the line if money items have different currencies */
+pub(crate) fn add_currency_conversion<'a>(config: &SmartCalcConfig, session: &Session, items: impl Iterator<Item = &'a dyn DataItem>) {
+    let currencies = items
+        .filter_map(|item| item.as_any().downcast_ref::<MoneyItem>())
+        .map(|money| money.get_currency())
+        .collect::<BTreeSet<_>>();
+
+    if currencies.len() > 1 {
+        session.add_rate_date(config.currency_rate_date(currencies.iter()));
+    }
+}
 
 #[derive(Debug)]
 
-pub struct MoneyItem(pub f64, pub Rc<CurrencyInfo>);
+pub struct MoneyItem(pub NumberValue, pub Rc<CurrencyInfo>);
 
 impl MoneyItem {
     pub fn get_currency(&self) -> Rc<CurrencyInfo> {
@@ -29,19 +42,22 @@ impl MoneyItem {
     }
     
     pub fn get_price(&self) -> f64 {
+        self.0.to_f64()
+    }
+
+    pub fn get_value(&self) -> NumberValue {
         self.0
     }
     
-    fn convert_currency(&self, config: &SmartCalcConfig, left: &MoneyItem) -> f64 {
-        let as_usd = match config.currency_rate.get(&left.get_currency()) {
-            Some(l_rate) => do_divition(left.get_price(), *l_rate),
-            _ => 0.0
-        };
-    
-        match config.currency_rate.get(&self.get_currency()) {
-            Some(r_rate) => as_usd * r_rate,
-            _ => 0.0
+    /* Missing currency rate gives none */
+    pub fn convert_to(&self, config: &SmartCalcConfig, currency: Rc<CurrencyInfo>) -> Option<MoneyItem> {
+        if self.get_currency() == currency {
+            return Some(MoneyItem(self.get_value(), currency));
         }
+
+        let as_usd = calculate_number(config, self.get_value(), (*config.currency_rate.get(&self.get_currency())?).into(), OperationType::Div);
+        let price = calculate_number(config, as_usd, (*config.currency_rate.get(&currency)?).into(), OperationType::Mul);
+        Some(MoneyItem(price, currency))
     }
 }
 
@@ -50,20 +66,36 @@ impl DataItem for MoneyItem {
         TokenType::Money(self.0, self.1.clone())
     }
     fn is_same(&self, other: &dyn Any) -> bool {
-        match other.downcast_ref::<(f64, Rc<CurrencyInfo>)>() {
-            Some((l_value, l_symbol)) => (l_value - self.0).abs() < f64::EPSILON && l_symbol.deref() == self.1.deref(),
+        match other.downcast_ref::<(NumberValue, Rc<CurrencyInfo>)>() {
+            Some((l_value, l_symbol)) => (*l_value == self.0 || (l_value.to_f64() - self.0.to_f64()).abs() < f64::EPSILON) && l_symbol.deref() == self.1.deref(),
             None => false
         }
     }
     fn as_any(&self) -> &dyn Any { self }
     
     fn calculate(&self, config: &SmartCalcConfig, on_left: bool, other: &dyn DataItem, operation_type: OperationType) -> Option<Rc<dyn DataItem>> {
+        /* Raising money to a power does not produce a money */
+        if let OperationType::Pow = operation_type {
+            return None;
+        }
+
+        /* Prices per unit are compound units, "$10 / 2 kg" is "$5,00/kg" */
+        let is_rate = matches!((other.type_name(), operation_type, on_left), ("DURATION", OperationType::Div, true));
+        if is_rate || matches!(other.type_name(), "DYNAMIC_TYPE" | "COMPOUND_TYPE") {
+            let other_unit = as_compound(config, other)?;
+            let this_unit = (self.get_price(), CompoundUnit::new(UnitKind::Currency(self.1.clone()), 1));
+            return match on_left {
+                true => calculate_units(config, this_unit, other_unit, operation_type),
+                false => calculate_units(config, other_unit, this_unit, operation_type)
+            };
+        }
+
         /* If both item is money and current money is on left side, skip calculation */
         let (other_amount, target_curreny, is_other_money)  = match other.type_name() {
-            "NUMBER" => (other.get_underlying_number(), self.1.clone(), false),
-            "MONEY" => (self.convert_currency(config, other.as_any().downcast_ref::<MoneyItem>().unwrap()), self.1.clone(), true),
-            "PERCENT" => (other.get_number(self), self.1.clone(), false),
-            "DURATION" => (other.get_number(self), self.1.clone(), false),
+            "NUMBER" => (other.as_any().downcast_ref::<NumberItem>()?.get_value(), self.1.clone(), false),
+            "MONEY" => (other.as_any().downcast_ref::<MoneyItem>()?.convert_to(config, self.get_currency()).map_or(0.0.into(), |money| money.get_value()), self.1.clone(), true),
+            "PERCENT" => (percent_of_number(config, self.0, other.get_underlying_number()), self.1.clone(), false),
+            "DURATION" => (other.get_number(self).into(), self.1.clone(), false),
             _ => return None
         };
         
@@ -73,35 +105,27 @@ impl DataItem for MoneyItem {
             (other_amount, self.0 ) 
         };
         
-        let result = match operation_type {
-            OperationType::Add => left + right,
-            OperationType::Div => {
-                let div_result = do_divition(left, right);
-                match is_other_money {
-                    true => return Some(Rc::new(NumberItem(div_result, NumberType::Decimal))),
-                    false => div_result
-                }
-            },
-            OperationType::Mul => left * right,
-            OperationType::Sub => left - right
-        };
+        let result = calculate_number(config, left, right, operation_type);
+        if let (OperationType::Div | OperationType::IntDiv, true) = (operation_type, is_other_money) {
+            return Some(Rc::new(NumberItem(result, NumberType::Decimal)));
+        }
         Some(Rc::new(MoneyItem(result, target_curreny)))
     }
     
     fn get_number(&self, other: &dyn DataItem) -> f64 {
        if self.type_name() == other.type_name() {
-           return self.0 
+           return self.get_price()
        }
        
-       other.get_underlying_number() * self.0
+       other.get_underlying_number() * self.get_price()
     }
     
-    fn get_underlying_number(&self) -> f64 { self.0 }
+    fn get_underlying_number(&self) -> f64 { self.get_price() }
     fn type_name(&self) -> &'static str { "MONEY" }
     fn type_id(&self) -> TypeId { TypeId::of::<MoneyItem>() }
     fn print(&self, config: &SmartCalcConfig, _: &Session) -> String {
         let currency = self.get_currency();
-        let formated_price = format_number(self.get_price(), config.thousand_separator.to_string(), config.decimal_seperator.to_string(), currency.decimal_digits, false, true);
+        let formated_price = format_value(config, self.get_value(), currency.decimal_digits, false);
         match (currency.symbol_on_left, currency.space_between_amount_and_symbol) {
             (true, true) => format!("{} {}", currency.symbol, formated_price),
             (true, false) => format!("{}{}", currency.symbol, formated_price),
@@ -111,7 +135,7 @@ impl DataItem for MoneyItem {
     }
     fn unary(&self, unary: UnaryType) -> Rc<dyn DataItem> {
         match unary {
-            UnaryType::Minus => Rc::new(Self(-1.0 * self.0, self.1.clone())),
+            UnaryType::Minus => Rc::new(Self(-self.0, self.1.clone())),
             UnaryType::Plus => Rc::new(Self(self.0, self.1.clone()))
         }
     }
@@ -122,6 +146,7 @@ impl DataItem for MoneyItem {
 #[test]
 fn format_result_test() {
     use crate::compiler::money::MoneyItem;
+    use alloc::string::ToString;
     use crate::config::SmartCalcConfig;
     let config = SmartCalcConfig::default();
     let session = Session::default();
@@ -131,31 +156,31 @@ fn format_result_test() {
     let uzs = config.get_currency("uzs".to_string()).unwrap();
     let uyu = config.get_currency("uyu".to_string()).unwrap();
 
-    assert_eq!(MoneyItem(0.0, usd.clone()).print(&config, &session), "$0,00".to_string());
-    assert_eq!(MoneyItem(0.05555, usd.clone()).print(&config, &session), "$0,06".to_string());
-    assert_eq!(MoneyItem(123.05555, usd.clone()).print(&config, &session), "$123,06".to_string());
-    assert_eq!(MoneyItem(1234.05555, usd.clone()).print(&config, &session), "$1.234,06".to_string());
-    assert_eq!(MoneyItem(123456.05555, usd.clone()).print(&config, &session), "$123.456,06".to_string());
-    assert_eq!(MoneyItem(123456.0, usd.clone()).print(&config, &session), "$123.456,00".to_string());
-
-    assert_eq!(MoneyItem(0.0, tl.clone()).print(&config, &session), "₺0,00".to_string());
-    assert_eq!(MoneyItem(0.05555, tl.clone()).print(&config, &session), "₺0,06".to_string());
-    assert_eq!(MoneyItem(123.05555, tl.clone()).print(&config, &session), "₺123,06".to_string());
-    assert_eq!(MoneyItem(1234.05555, tl.clone()).print(&config, &session), "₺1.234,06".to_string());
-    assert_eq!(MoneyItem(123456.05555, tl.clone()).print(&config, &session), "₺123.456,06".to_string());
-    assert_eq!(MoneyItem(123456.0, tl.clone()).print(&config, &session), "₺123.456,00".to_string());
-
-    assert_eq!(MoneyItem(0.0, uzs.clone()).print(&config, &session), "0,00 сўм".to_string());
-    assert_eq!(MoneyItem(0.05555, uzs.clone()).print(&config, &session), "0,06 сўм".to_string());
-    assert_eq!(MoneyItem(123.05555, uzs.clone()).print(&config, &session), "123,06 сўм".to_string());
-    assert_eq!(MoneyItem(1234.05555, uzs.clone()).print(&config, &session), "1.234,06 сўм".to_string());
-    assert_eq!(MoneyItem(123456.05555, uzs.clone()).print(&config, &session), "123.456,06 сўм".to_string());
-    assert_eq!(MoneyItem(123456.0, uzs.clone()).print(&config, &session), "123.456,00 сўм".to_string());
-
-    assert_eq!(MoneyItem(0.0, uyu.clone()).print(&config, &session), "$U 0,00".to_string());
-    assert_eq!(MoneyItem(0.05555, uyu.clone()).print(&config, &session), "$U 0,06".to_string());
-    assert_eq!(MoneyItem(123.05555, uyu.clone()).print(&config, &session), "$U 123,06".to_string());
-    assert_eq!(MoneyItem(1234.05555, uyu.clone()).print(&config, &session), "$U 1.234,06".to_string());
-    assert_eq!(MoneyItem(123456.05555, uyu.clone()).print(&config, &session), "$U 123.456,06".to_string());
-    assert_eq!(MoneyItem(123456.0, uyu.clone()).print(&config, &session), "$U 123.456,00".to_string());
+    assert_eq!(MoneyItem(0.0.into(), usd.clone()).print(&config, &session), "$0,00".to_string());
+    assert_eq!(MoneyItem(0.05555.into(), usd.clone()).print(&config, &session), "$0,06".to_string());
+    assert_eq!(MoneyItem(123.05555.into(), usd.clone()).print(&config, &session), "$123,06".to_string());
+    assert_eq!(MoneyItem(1234.05555.into(), usd.clone()).print(&config, &session), "$1.234,06".to_string());
+    assert_eq!(MoneyItem(123456.05555.into(), usd.clone()).print(&config, &session), "$123.456,06".to_string());
+    assert_eq!(MoneyItem(123456.0.into(), usd.clone()).print(&config, &session), "$123.456,00".to_string());
+
+    assert_eq!(MoneyItem(0.0.into(), tl.clone()).print(&config, &session), "₺0,00".to_string());
+    assert_eq!(MoneyItem(0.05555.into(), tl.clone()).print(&config, &session), "₺0,06".to_string());
+    assert_eq!(MoneyItem(123.05555.into(), tl.clone()).print(&config, &session), "₺123,06".to_string());
+    assert_eq!(MoneyItem(1234.05555.into(), tl.clone()).print(&config, &session), "₺1.234,06".to_string());
+    assert_eq!(MoneyItem(123456.05555.into(), tl.clone()).print(&config, &session), "₺123.456,06".to_string());
+    assert_eq!(MoneyItem(123456.0.into(), tl.clone()).print(&config, &session), "₺123.456,00".to_string());
+
+    assert_eq!(MoneyItem(0.0.into(), uzs.clone()).print(&config, &session), "0,00 сўм".to_string());
+    assert_eq!(MoneyItem(0.05555.into(), uzs.clone()).print(&config, &session), "0,06 сўм".to_string());
+    assert_eq!(MoneyItem(123.05555.into(), uzs.clone()).print(&config, &session), "123,06 сўм".to_string());
+    assert_eq!(MoneyItem(1234.05555.into(), uzs.clone()).print(&config, &session), "1.234,06 сўм".to_string());
+    assert_eq!(MoneyItem(123456.05555.into(), uzs.clone()).print(&config, &session), "123.456,06 сўм".to_string());
+    assert_eq!(MoneyItem(123456.0.into(), uzs.clone()).print(&config, &session), "123.456,00 сўм".to_string());
+
+    assert_eq!(MoneyItem(0.0.into(), uyu.clone()).print(&config, &session), "$U 0,00".to_string());
+    assert_eq!(MoneyItem(0.05555.into(), uyu.clone()).print(&config, &session), "$U 0,06".to_string());
+    assert_eq!(MoneyItem(123.05555.into(), uyu.clone()).print(&config, &session), "$U 123,06".to_string());
+    assert_eq!(MoneyItem(1234.05555.into(), uyu.clone()).print(&config, &session), "$U 1.234,06".to_string());
+    assert_eq!(MoneyItem(123456.05555.into(), uyu.clone()).print(&config, &session), "$U 123.456,06".to_string());
+    assert_eq!(MoneyItem(123456.0.into(), uyu.clone()).print(&config, &session), "$U 123.456,00".to_string());
 }
\ No newline at end of file
@@ -13,7 +13,7 @@ use crate::types::TokenType;
 use super::{DataItem, OperationType, UnaryType};
 use crate::formatter::format_number;
 use alloc::format;
-use crate::tools::do_divition;
+use crate::tools::{do_divition, do_calculation, round_number};
 
 
 #[derive(Debug)]
@@ -30,7 +30,7 @@ impl DataItem for PercentItem {
         }
     }
     fn as_any(&self) -> &dyn Any { self }
-    fn calculate(&self, _: &SmartCalcConfig, on_left: bool, other: &dyn DataItem, operation_type: OperationType) -> Option<Rc<dyn DataItem>> {
+    fn calculate(&self, config: &SmartCalcConfig, on_left: bool, other: &dyn DataItem, operation_type: OperationType) -> Option<Rc<dyn DataItem>> {
         if TypeId::of::<Self>() != other.type_id() {
             return None;
         }
@@ -42,12 +42,7 @@ impl DataItem for PercentItem {
             (number, self.0 ) 
         };
         
-        let result = match operation_type {
-            OperationType::Add => left + right,
-            OperationType::Div => left / right,
-            OperationType::Mul => left * right,
-            OperationType::Sub => left - right
-        };
+        let result = do_calculation(config, left, right, operation_type);
         Some(Rc::new(PercentItem(result)))
     }
     
@@ -62,7 +57,7 @@ impl DataItem for PercentItem {
     fn get_underlying_number(&self) -> f64 { self.0 }
     fn type_name(&self) -> &'static str { "PERCENT" }
     fn type_id(&self) -> TypeId { TypeId::of::<PercentItem>() }
-    fn print(&self, config: &SmartCalcConfig, _: &Session) -> String { format!("%{:}", format_number(self.0, config.thousand_separator.to_string(), config.decimal_seperator.to_string(), 2, true, true)) }
+    fn print(&self, config: &SmartCalcConfig, _: &Session) -> String { format!("%{:}", format_number(round_number(config, self.0, 2), config.thousand_separator.to_string(), config.decimal_seperator.to_string(), 2, true, true)) }
     fn unary(&self, unary: UnaryType) -> Rc<dyn DataItem> {
         match unary {
             UnaryType::Minus => Rc::new(Self(-1.0 * self.0)),
@@ -6,10 +6,8 @@
 
 use core::any::{Any, TypeId};
 use alloc::rc::Rc;
-use alloc::string::ToString;
 use alloc::string::String;
-use chrono::{Duration, Timelike, NaiveDateTime, FixedOffset};
-use chrono::TimeZone;
+use chrono::{Duration, Timelike, NaiveDateTime};
 use crate::session::Session;
 use crate::config::SmartCalcConfig;
 use crate::types::{TokenType, TimeOffset};
@@ -79,9 +77,7 @@ impl DataItem for TimeItem {
     fn type_name(&self) -> &'static str { "TIME" }
     fn type_id(&self) -> TypeId { TypeId::of::<TimeItem>() }
     fn print(&self, _: &SmartCalcConfig, _: &Session) -> String {
-        let tz_offset = FixedOffset::east(self.1.offset * 60);
-        let datetime = tz_offset.from_utc_datetime(&self.0);
-        alloc::format!("{} {}", datetime.format("%H:%M:%S").to_string(), self.1.name)
+        alloc::format!("{} {}", self.1.to_local(&self.0).format("%H:%M:%S"), self.1.name_at(&self.0))
     }
     fn unary(&self, _: UnaryType) -> Rc<dyn DataItem> {
         Rc::new(Self(self.0, self.1.clone()))
@@ -93,6 +89,7 @@ impl DataItem for TimeItem {
 #[test]
 fn time_test() {
     use core::ops::Deref;
+    use alloc::string::ToString;
     use crate::compiler::time::TimeItem;
     use crate::config::SmartCalcConfig;
     let config = SmartCalcConfig::default();
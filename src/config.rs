@@ -4,28 +4,49 @@
  * Licensed under the GNU General Public License v2.0.
  */
 
+use chrono_tz::Tz;
 use core::borrow::Borrow;
+use core::ops::Deref;
+use core::str::FromStr;
 use alloc::format;
 use alloc::rc::Rc;
 use alloc::string::String;
 use alloc::string::ToString;
 use alloc::vec::Vec;
 use alloc::collections::btree_map::BTreeMap;
+use alloc::collections::VecDeque;
 use regex::Regex;
-use serde_json::from_str;
+use alloc::vec;
+use serde::de::DeserializeOwned;
+use serde_json::{from_str, Map, Value};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use crate::currency::{CurrencyRateHistory, CurrencyRateProvider, CurrencyRatesInfo};
+use crate::error::{ConfigError, ConfigErrorKind, CurrencyRateError, ErrorKind};
 use crate::session::Session;
 use crate::tokinizer::RuleItemList;
 use crate::tokinizer::RuleType;
 use crate::types::CurrencyInfo;
 use crate::types::TimeOffset;
+use crate::types::NumericMode;
+use crate::tools::find_zone;
+use crate::types::{NumberType, TokenType};
 use crate::tokinizer::Tokinizer;
 use crate::tokinizer::TokenInfo;
 use crate::tokinizer::RULE_FUNCTIONS;
+use crate::tokinizer::read_currency;
 use crate::constants::*;
+use crate::function::{FunctionType, MATH_FUNCTIONS};
+use crate::compiler::OperationType;
+use crate::compiler::formula::Formula;
+use crate::compiler::compound_type::CompoundUnit;
+use crate::tools::do_calculation;
 
 pub type LanguageData<T> = BTreeMap<String, T>;
 pub type CurrencyData<T> = BTreeMap<Rc<CurrencyInfo>, T>;
 
+/* Rate of a day could be used for the following days without rate, like weekends and holidays */
+const MAX_RATE_HISTORY_GAP: i64 = 7;
+
 #[derive(Default)]
 #[derive(Clone)]
 #[derive(Debug)]
@@ -38,9 +59,54 @@ pub struct DynamicType {
     pub upgrade_code: String,
     pub downgrade_code: String,
     pub names:Vec<String>,
+
+    /* Written in compound units like "kW·h", first name is used when it is not set */
+    pub symbol: Option<String>,
     pub decimal_digits: Option<u8>,
     pub use_fract_rounding: Option<bool>,
-    pub remove_fract_if_zero: Option<bool>
+    pub remove_fract_if_zero: Option<bool>,
+
+    /* Compiled upgrade_code and downgrade_code */
+    pub upgrade: Option<Formula>,
+    pub downgrade: Option<Formula>,
+
+    /* Conversion with the base unit of the group, used instead of the calculation codes */
+    pub base: Option<UnitBase>,
+
+    /* Amount of change, like "Δ10 °C", instead of an absolute value */
+    pub difference: bool
+}
+
+/* Value in the base unit of the group. Affine is "value * scale + offset", inverse is "scale / value" */
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum UnitBase {
+    Affine { scale: f64, offset: f64 },
+    Inverse { scale: f64 }
+}
+
+impl UnitBase {
+    pub fn to_base(self, config: &SmartCalcConfig, value: f64) -> Option<f64> {
+        match self {
+            UnitBase::Affine { scale, offset } => Some(do_calculation(config, do_calculation(config, value, scale, OperationType::Mul), offset, OperationType::Add)),
+            UnitBase::Inverse { .. } if value == 0.0 => None,
+            UnitBase::Inverse { scale } => Some(do_calculation(config, scale, value, OperationType::Div))
+        }
+    }
+
+    pub fn to_unit(self, config: &SmartCalcConfig, base: f64) -> Option<f64> {
+        match self {
+            UnitBase::Affine { scale, offset } => Some(do_calculation(config, do_calculation(config, base, offset, OperationType::Sub), scale, OperationType::Div)),
+            UnitBase::Inverse { .. } if base == 0.0 => None,
+            UnitBase::Inverse { scale } => Some(do_calculation(config, scale, base, OperationType::Div))
+        }
+    }
+
+    /* Zero of the unit is the zero of the base unit */
+    pub fn is_linear(self) -> bool {
+        matches!(self, UnitBase::Affine { offset, .. } if offset == 0.0)
+    }
 }
 
 impl DynamicType {
@@ -50,12 +116,67 @@ impl DynamicType {
             index,
             format,
             parse,
+            upgrade: Formula::parse(&upgrade_code),
+            downgrade: Formula::parse(&downgrade_code),
             upgrade_code,
             downgrade_code,
             names,
+            symbol: None,
             decimal_digits,
             use_fract_rounding,
-            remove_fract_if_zero
+            remove_fract_if_zero,
+            base: None,
+            difference: false
+        }
+    }
+
+    pub fn is_linear(&self) -> bool {
+        self.base.is_none_or(UnitBase::is_linear)
+    }
+
+    pub fn symbol(&self) -> &str {
+        self.symbol.as_deref().unwrap_or(&self.names[0])
+    }
+
+    fn with_base(&self, base: UnitBase) -> Self {
+        DynamicType { base: Some(base), ..self.clone() }
+    }
+
+    /* Same unit as an absolute value or as a difference */
+    pub fn with_difference(self: &Rc<Self>, difference: bool) -> Rc<Self> {
+        match self.difference == difference {
+            true => self.clone(),
+            false => Rc::new(DynamicType { difference, ..self.deref().clone() })
+        }
+    }
+}
+
+/* Conversion between two dynamic type groups with compiled calculation codes */
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct TypeConversion {
+    pub source: JsonTypeConversionItem,
+    pub target: JsonTypeConversionItem,
+    pub to_source: Formula,
+    pub to_target: Formula
+}
+
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct NamedConstant {
+    pub tokens: Vec<Rc<TokenInfo>>,
+    pub token_type: TokenType,
+
+    /* One letter names like "e" are not used next to numbers, "1e3" and "5 c" are not constants */
+    pub standalone: bool
+}
+
+impl NamedConstant {
+    pub fn new(tokens: Vec<Rc<TokenInfo>>, token_type: TokenType, standalone: bool) -> Self {
+        NamedConstant {
+            tokens,
+            token_type,
+            standalone
         }
     }
 }
@@ -67,19 +188,30 @@ pub struct SmartCalcConfig {
     pub(crate) currency_alias: LanguageData<Rc<CurrencyInfo>>,
     pub(crate) timezones: BTreeMap<String, i32>,
     pub(crate) currency_rate: CurrencyData<f64>,
+    pub(crate) currency_rate_date: CurrencyData<NaiveDateTime>,
+    pub(crate) currency_rates_info: CurrencyRatesInfo,
+    pub(crate) currency_rate_history: BTreeMap<NaiveDate, CurrencyData<f64>>,
+    pub(crate) asset_units: CurrencyData<BTreeMap<String, f64>>,
     pub(crate) token_parse_regex: LanguageData<Vec<Regex>>,
     pub(crate) word_group: LanguageData<BTreeMap<String, Vec<String>>>,
     pub(crate) constant_pair: LanguageData<BTreeMap<String, ConstantType>>,
+    pub(crate) named_constants: LanguageData<Vec<NamedConstant>>,
+    pub(crate) locations: LanguageData<BTreeMap<String, Tz>>,
     pub(crate) language_alias_regex: LanguageData<Vec<(Regex, String)>>,
     pub(crate) alias_regex: Vec<(Regex, String)>,
     pub(crate) rule: LanguageData<RuleItemList>,
     pub(crate) types: BTreeMap<String, BTreeMap<usize, Rc<DynamicType>>>,
-    pub(crate) type_conversion: Vec<JsonTypeConversion>,
+    pub(crate) type_conversion: Vec<TypeConversion>,
+    pub(crate) conversion_graph: BTreeMap<String, Vec<usize>>,
+    pub(crate) type_units: BTreeMap<String, CompoundUnit>,
+    pub(crate) functions: BTreeMap<String, FunctionType>,
     pub(crate) month_regex: LanguageData<MonthItemList>,
     pub(crate) decimal_seperator: String,
     pub(crate) thousand_separator: String,
     pub(crate) timezone: String,
-    pub(crate) timezone_offset: i32
+    pub(crate) timezone_offset: i32,
+    pub(crate) timezone_zone: Option<Tz>,
+    pub(crate) numeric_mode: NumericMode
 }
 
 impl Default for SmartCalcConfig {
@@ -92,7 +224,8 @@ impl SmartCalcConfig {
     pub fn get_time_offset(&self) -> TimeOffset {
         TimeOffset {
             name: self.timezone.to_string(),
-            offset: self.timezone_offset
+            offset: self.timezone_offset,
+            zone: self.timezone_zone
         }
     }
 
@@ -102,36 +235,128 @@ impl SmartCalcConfig {
             .cloned()
     }
 
+    /* Rates written in the configuration file, they are used until the rates are updated */
+    pub(crate) fn configured_currency_rates(&self) -> CurrencyData<f64> {
+        let mut rates = CurrencyData::new();
+        for (code, asset) in self.json_data.assets.iter() {
+            if let Some(currency) = self.get_currency(code.to_lowercase()).filter(|currency| currency.code == asset.currency.code) {
+                rates.insert(currency, asset.rate);
+            }
+        }
+
+        for (key, value) in self.json_data.currency_rates.iter() {
+            match self.get_currency(key) {
+                Some(currency) => { rates.insert(currency, *value); },
+                None => log::warn!("'{}' currency not found at rate", key)
+            };
+        }
+        rates
+    }
+
     pub fn load_from_json(json_data: &str) -> Self {
+        match from_str(json_data) {
+            Ok(data) => SmartCalcConfig::from_json_constant(data),
+            Err(error) => panic!("JSON parse error: {}", error)
+        }
+    }
+
+    /// Load a complete configuration, problems are returned instead of panic.
+    pub fn try_load_from_json(json_data: &str) -> Result<Self, Vec<ConfigError>> {
+        SmartCalcConfig::from_json_value(parse_json(json_data)?)
+    }
+
+    /// Load the default configuration and merge the partial JSON documents over it, in order.
+    ///
+    /// Objects are merged key by key and `null` removes the key. Arrays are extended, dynamic types
+    /// are matched with `name` and their items with `index`, type conversions with `source` and `target`.
+    pub fn load_with_overlays<T: Borrow<str>>(overlays: &[T]) -> Result<Self, Vec<ConfigError>> {
+        let mut document = parse_json(JSON_DATA)?;
+        let mut errors = Vec::new();
+
+        for overlay in overlays.iter() {
+            merge_json(&mut document, parse_json(overlay.borrow())?, "", &mut errors);
+        }
+
+        match errors.is_empty() {
+            true => SmartCalcConfig::from_json_value(document),
+            false => Err(errors)
+        }
+    }
+
+    fn from_json_value(document: Value) -> Result<Self, Vec<ConfigError>> {
+        let json_data: JsonConstant = match serde_json::from_value(document.clone()) {
+            Ok(json_data) => json_data,
+            Err(error) => return Err(vec![schema_error(&document, error)])
+        };
+
+        let errors = validate_json(&json_data);
+        match errors.is_empty() {
+            true => Ok(SmartCalcConfig::from_json_constant(json_data)),
+            false => Err(errors)
+        }
+    }
+
+    fn from_json_constant(json_data: JsonConstant) -> Self {
         let mut config = SmartCalcConfig {
-            json_data: match from_str(json_data) {
-                Ok(data) => data,
-                Err(error) => panic!("JSON parse error: {}", error)
-            },
+            json_data,
             format: LanguageData::new(),
             currency: LanguageData::new(),
             currency_alias: LanguageData::new(),
             timezones: BTreeMap::new(),
             currency_rate: CurrencyData::new(),
+            currency_rate_date: CurrencyData::new(),
+            currency_rates_info: CurrencyRatesInfo::default(),
+            currency_rate_history: BTreeMap::new(),
+            asset_units: CurrencyData::new(),
             token_parse_regex: LanguageData::new(),
             word_group: LanguageData::new(),
             constant_pair: LanguageData::new(),
+            named_constants: LanguageData::new(),
+            locations: LanguageData::new(),
             language_alias_regex: LanguageData::new(),
             rule: LanguageData::new(),
             types: BTreeMap::new(),
             type_conversion: Vec::new(),
+            conversion_graph: BTreeMap::new(),
+            type_units: BTreeMap::new(),
+            functions: BTreeMap::new(),
             month_regex: LanguageData::new(),
             alias_regex: Vec::new(),
             decimal_seperator: ",".to_string(),
             thousand_separator: ".".to_string(),
             timezone: "UTC".to_string(),
-            timezone_offset: 0
+            timezone_offset: 0,
+            timezone_zone: None,
+            numeric_mode: NumericMode::default()
         };
         
         for (name, currency) in config.json_data.currencies.iter() {
             config.currency.insert(name.to_lowercase(), currency.clone());
         }
 
+        for (code, asset) in config.json_data.assets.iter() {
+            if config.currency.contains_key(&code.to_lowercase()) {
+                log::warn!("'{}' asset is already defined as currency", code);
+                continue;
+            }
+
+            let currency = Rc::new(asset.currency.clone());
+            config.currency.insert(code.to_lowercase(), currency.clone());
+
+            for name in asset.names.iter() {
+                config.currency_alias.insert(name.to_lowercase(), currency.clone());
+            }
+
+            if !asset.units.is_empty() {
+                let units = asset.units.iter().map(|(unit, amount)| (unit.to_lowercase(), *amount)).collect();
+                config.asset_units.insert(currency, units);
+            }
+        }
+
+        for (name, function) in MATH_FUNCTIONS.iter() {
+            config.functions.insert(name.to_string(), FunctionType::Internal(*function));
+        }
+
         for (timezone, offset) in config.json_data.timezones.iter() {
             config.timezones.insert(timezone.clone(), *offset);
         }
@@ -156,12 +381,7 @@ impl SmartCalcConfig {
             };
         }
 
-        for (key, value) in config.json_data.currency_rates.iter() {
-            match config.get_currency(key) {
-                Some(currency) => { config.currency_rate.insert(currency.clone(), *value); },
-                None => log::warn!("'{}' currency not found at rate", key)
-            };
-        }
+        config.currency_rate = config.configured_currency_rates();
 
         for (language, language_constant) in config.json_data.languages.iter() {
             let mut language_aliases = Vec::new();
@@ -240,6 +460,18 @@ impl SmartCalcConfig {
             config.word_group.insert(language.to_string(), word_groups);
         }
 
+        for (language, language_constant) in config.json_data.languages.iter() {
+            let mut locations = BTreeMap::new();
+            for (name, zone) in language_constant.locations.iter() {
+                match Tz::from_str(zone) {
+                    Ok(zone) => { locations.insert(name.to_lowercase(), zone); },
+                    Err(_) => log::warn!("'{}' timezone not found at location", zone)
+                };
+            }
+
+            config.locations.insert(language.to_string(), locations);
+        }
+
         for (language, language_constant) in config.json_data.languages.iter() {
             let mut constants = BTreeMap::new();
             for (alias_name, constant_type) in language_constant.constant_pair.iter() {
@@ -287,25 +519,39 @@ impl SmartCalcConfig {
             
             for type_item in dynamic_type.items.iter() {
                 
-                if type_item.upgrade_code.is_none() || type_item.downgrade_code.is_none() {
+                if type_item.scale.is_none() && (type_item.upgrade_code.is_none() || type_item.downgrade_code.is_none()) {
                     log::warn!("Dynamic type {}:{} has missing calculation code. Please check upgrade_code and downgrade_code fields", dynamic_type.name, type_item.index);
                     continue;
                 }
                 
                 let upgrade_code = type_item.upgrade_code.as_ref().map_or(String::new(), |item| item.to_string());
                 let downgrade_code = type_item.downgrade_code.as_ref().map_or(String::new(), |item| item.to_string());
+                let (upgrade, downgrade) = (Formula::parse(&upgrade_code), Formula::parse(&downgrade_code));
+
+                if type_item.scale.is_none() && (upgrade.is_none() || downgrade.is_none()) {
+                    log::warn!("Dynamic type {}:{} has invalid calculation code. Please check upgrade_code and downgrade_code fields", dynamic_type.name, type_item.index);
+                    continue;
+                }
 
                 let mut token_info = DynamicType {
                     group_name: dynamic_type.name.to_string(),
                     index: type_item.index,
                     format: type_item.format.to_string(),
                     parse: Vec::new(),
+                    upgrade,
+                    downgrade,
                     upgrade_code,
                     downgrade_code,
                     names: type_item.names.clone(),
+                    symbol: type_item.symbol.clone(),
                     decimal_digits: type_item.decimal_digits,
                     use_fract_rounding: type_item.use_fract_rounding,
-                    remove_fract_if_zero: type_item.remove_fract_if_zero
+                    remove_fract_if_zero: type_item.remove_fract_if_zero,
+                    base: type_item.scale.map(|scale| match type_item.inverse.unwrap_or_default() {
+                        true => UnitBase::Inverse { scale },
+                        false => UnitBase::Affine { scale, offset: type_item.offset.unwrap_or_default() }
+                    }),
+                    difference: false
                 };
 
                 for type_parse_item in type_item.parse.iter() {
@@ -319,9 +565,20 @@ impl SmartCalcConfig {
                 
                 dynamic_type_holder.insert(token_info.index, Rc::new(token_info));
             }
-            
+
+            set_group_base(&mut dynamic_type_holder);
             config.types.insert(dynamic_type.name.to_string(), dynamic_type_holder);
         }
+
+        /* Base unit of the group written with the other units, like "m²" for area */
+        for dynamic_type in config.json_data.types.iter() {
+            if let Some(unit) = &dynamic_type.unit {
+                match CompoundUnit::parse_text(&config, unit) {
+                    Some(compound_unit) => { config.type_units.insert(dynamic_type.name.to_string(), compound_unit); },
+                    None => log::warn!("{} unit of {} type is not valid", unit, dynamic_type.name)
+                };
+            }
+        }
         
         for type_conversion in config.json_data.type_conversion.iter() {
             let source = config.types.get(&type_conversion.source.name);
@@ -348,11 +605,542 @@ impl SmartCalcConfig {
                 log::warn!("{} type not defined", type_conversion.target.name);
             }
             
-            if source_found && target_found {
-                config.type_conversion.push(type_conversion.clone());
+            let to_source = Formula::parse(&type_conversion.to_source_calculation);
+            let to_target = Formula::parse(&type_conversion.to_target_calculation);
+            if to_source.is_none() || to_target.is_none() {
+                log::warn!("{} to {} conversion has invalid calculation code", type_conversion.source.name, type_conversion.target.name);
+            }
+
+            if let (true, true, Some(to_source), Some(to_target)) = (source_found, target_found, to_source, to_target) {
+                for group_name in [&type_conversion.source.name, &type_conversion.target.name] {
+                    config.conversion_graph.entry(group_name.to_string()).or_default().push(config.type_conversion.len());
+                }
+
+                config.type_conversion.push(TypeConversion {
+                    source: type_conversion.source.clone(),
+                    target: type_conversion.target.clone(),
+                    to_source,
+                    to_target
+                });
+            }
+        }
+
+        for (language, language_constant) in config.json_data.languages.iter() {
+            let mut named_constants = Vec::new();
+            for (name, constant) in language_constant.named_constants.iter() {
+                let token_type = match &constant.unit {
+                    Some(unit) => match config.find_dynamic_type(unit) {
+                        Some(dynamic_type) => TokenType::DynamicType(constant.value, dynamic_type),
+                        None => {
+                            log::warn!("'{}' unit not found at named constant", unit);
+                            continue;
+                        }
+                    },
+                    None => TokenType::Number(constant.value.into(), NumberType::Decimal)
+                };
+
+                let mut session = Session::new();
+                session.set_language(language.to_string());
+                session.set_text(name.to_string());
+                named_constants.push(NamedConstant::new(Tokinizer::token_infos(&config, &session), token_type, name.chars().count() == 1 && name.is_ascii()));
             }
+
+            /* Longest names are checked first, "speed of light" has priority over "light" */
+            named_constants.sort_by_key(|named_constant| core::cmp::Reverse(named_constant.tokens.len()));
+            config.named_constants.insert(language.to_string(), named_constants);
         }
 
         config
     }
-}
\ No newline at end of file
+
+    /// Check the configuration and return the problems with their location in the JSON document.
+    ///
+    /// Problematic items are skipped while loading, this can be used to find them.
+    pub fn validate(&self) -> Vec<ConfigError> {
+        validate_json(&self.json_data)
+    }
+
+    /* Rates are relative to the provider's base currency after update, rates of the other currencies are converted with the current cross rate */
+    pub(crate) fn update_currency_rates(&mut self, provider: &dyn CurrencyRateProvider) -> Result<usize, CurrencyRateError> {
+        let base_code = provider.base_currency();
+        let base = read_currency(self, &base_code).ok_or_else(|| CurrencyRateError::UnknownCurrency(base_code.to_string()))?;
+
+        let rates = provider.rates();
+        if rates.is_empty() {
+            return Err(CurrencyRateError::NoRates);
+        }
+
+        if let Some((currency, _)) = rates.iter().find(|(_, rate)| !rate.is_finite() || **rate <= 0.0) {
+            return Err(CurrencyRateError::InvalidRate(currency.to_string()));
+        }
+
+        match self.currency_rate.get(&base).cloned() {
+            Some(base_rate) => self.currency_rate.values_mut().for_each(|rate| *rate /= base_rate),
+            None => self.currency_rate.clear()
+        };
+
+        let as_of = provider.as_of();
+        let mut updated = 0;
+        for (code, rate) in core::iter::once((base_code.to_string(), 1.0)).chain(rates) {
+            let currency = match read_currency(self, &code) {
+                Some(currency) => currency,
+                None => {
+                    log::warn!("'{}' currency not found at rate provider", code);
+                    continue;
+                }
+            };
+
+            match as_of {
+                Some(as_of) => self.currency_rate_date.insert(currency.clone(), as_of),
+                None => self.currency_rate_date.remove(&currency)
+            };
+
+            if currency != base {
+                updated += 1;
+            }
+            self.currency_rate.insert(currency, rate);
+        }
+
+        self.currency_rates_info = CurrencyRatesInfo {
+            base: base.code.to_string(),
+            as_of,
+            max_age: provider.max_age()
+        };
+        Ok(updated)
+    }
+
+    pub(crate) fn set_currency_rate_history(&mut self, history: &CurrencyRateHistory) -> Result<usize, CurrencyRateError> {
+        let base = read_currency(self, &history.base).ok_or_else(|| CurrencyRateError::UnknownCurrency(history.base.to_string()))?;
+        let mut days = BTreeMap::new();
+
+        for (date, rates) in history.days.iter() {
+            let mut day_rates = CurrencyData::new();
+            day_rates.insert(base.clone(), 1.0);
+
+            for (code, rate) in rates.iter() {
+                match read_currency(self, code) {
+                    Some(currency) => { day_rates.insert(currency, *rate); },
+                    None => log::warn!("'{}' currency not found at rate history", code)
+                };
+            }
+            days.insert(*date, day_rates);
+        }
+
+        self.currency_rate_history = days;
+        Ok(self.currency_rate_history.len())
+    }
+
+    /* Rates of the latest day on or before the date are used, there is no rate on weekends and holidays */
+    pub(crate) fn historical_rates(&self, date: NaiveDate, from: &Rc<CurrencyInfo>, to: &Rc<CurrencyInfo>) -> Result<(NaiveDate, f64, f64), Rc<CurrencyInfo>> {
+        let days = self.currency_rate_history.range(date - Duration::days(MAX_RATE_HISTORY_GAP)..=date).rev();
+        for (day, rates) in days.clone() {
+            if let (Some(from_rate), Some(to_rate)) = (rates.get(from), rates.get(to)) {
+                return Ok((*day, *from_rate, *to_rate));
+            }
+        }
+
+        let mut days = days;
+        match days.any(|(_, rates)| rates.contains_key(from)) {
+            true => Err(to.clone()),
+            false => Err(from.clone())
+        }
+    }
+
+    /* Oldest date of the rates, None if one of them is not known */
+    pub(crate) fn currency_rate_date<'a>(&self, currencies: impl Iterator<Item = &'a Rc<CurrencyInfo>>) -> Option<NaiveDateTime> {
+        let dates = currencies
+            .map(|currency| self.currency_rate_date.get(currency).cloned())
+            .collect::<Option<Vec<_>>>()?;
+        dates.into_iter().min()
+    }
+
+    /// IANA zone of the city or country name in the language, IANA zone and city names are used if it is not in the gazetteer.
+    pub(crate) fn find_location(&self, language: &str, name: &str) -> Option<Tz> {
+        self.locations
+            .get(language)
+            .and_then(|locations| locations.get(&name.trim().to_lowercase()))
+            .cloned()
+            .or_else(|| find_zone(name))
+    }
+
+    /* Names are lower case, symbols like "K" are case sensitive */
+    pub(crate) fn find_dynamic_type(&self, name: &str) -> Option<Rc<DynamicType>> {
+        let lowercase_name = name.to_lowercase();
+        self.types
+            .values()
+            .flat_map(|items| items.values())
+            .find(|dynamic_type| dynamic_type.names.contains(&lowercase_name) || dynamic_type.symbol.as_deref() == Some(name))
+            .cloned()
+    }
+
+    /* Shortest chain of type conversions between two groups, there must be only one */
+    pub(crate) fn conversion_path(&self, source: &str, target: &str) -> Result<Vec<&TypeConversion>, ErrorKind> {
+        let mut distances = BTreeMap::from([(source, (0, 1))]);
+        let mut previous: BTreeMap<&str, (&str, usize)> = BTreeMap::new();
+        let mut queue = VecDeque::from([source]);
+
+        while let Some(group_name) = queue.pop_front() {
+            let (distance, paths) = distances[group_name];
+            for index in self.conversion_graph.get(group_name).into_iter().flatten() {
+                let type_conversion = &self.type_conversion[*index];
+                let next = match type_conversion.source.name == group_name {
+                    true => &type_conversion.target.name[..],
+                    false => &type_conversion.source.name[..]
+                };
+
+                match distances.get_mut(next) {
+                    Some((next_distance, next_paths)) if *next_distance == distance + 1 => *next_paths += paths,
+                    Some(_) => (),
+                    None => {
+                        distances.insert(next, (distance + 1, paths));
+                        previous.insert(next, (group_name, *index));
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        match distances.get(target) {
+            Some((_, 1)) => (),
+            Some(_) => return Err(ErrorKind::AmbiguousConversion { left: source.to_string(), right: target.to_string() }),
+            None => return Err(ErrorKind::IncompatibleTypes { left: source.to_string(), right: target.to_string() })
+        };
+
+        let mut path = Vec::new();
+        let mut group_name = target;
+        while let Some((previous_group, index)) = previous.get(group_name) {
+            path.insert(0, &self.type_conversion[*index]);
+            group_name = previous_group;
+        }
+        Ok(path)
+    }
+}
+
+fn parse_json(json_data: &str) -> Result<Value, Vec<ConfigError>> {
+    from_str(json_data).map_err(|error| vec![ConfigError::new("", ConfigErrorKind::Syntax(error.to_string()))])
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object"
+    }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    match path.is_empty() {
+        true => key.to_string(),
+        false => format!("{}.{}", path, key)
+    }
+}
+
+fn merge_json(base: &mut Value, overlay: Value, path: &str, errors: &mut Vec<ConfigError>) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                let key_path = join_path(path, &key);
+                match (base.get_mut(&key), value) {
+                    (_, Value::Null) => { base.remove(&key); },
+                    (Some(base_value), value) if path.is_empty() && key == "types" => merge_keyed_array(base_value, value, &key_path, &["name"], errors),
+                    (Some(base_value), value) if path.is_empty() && key == "type_conversion" => merge_keyed_array(base_value, value, &key_path, &["source", "target"], errors),
+                    (Some(base_value), value) => merge_json(base_value, value, &key_path, errors),
+                    (None, value) => { base.insert(key, value); }
+                };
+            }
+        },
+        (Value::Array(base), Value::Array(overlay)) => {
+            for item in overlay {
+                if !base.contains(&item) {
+                    base.push(item);
+                }
+            }
+        },
+        (base, overlay) if base.is_null() || json_type(base) == json_type(&overlay) => *base = overlay,
+        (base, overlay) => errors.push(ConfigError::new(path, ConfigErrorKind::TypeMismatch { expected: json_type(base), found: json_type(&overlay) }))
+    }
+}
+
+/* Items with the same key fields are merged, dynamic type items are matched with their index */
+fn merge_keyed_array(base: &mut Value, overlay: Value, path: &str, keys: &[&str], errors: &mut Vec<ConfigError>) {
+    let (base, overlay) = match (base, overlay) {
+        (Value::Array(base), Value::Array(overlay)) => (base, overlay),
+        (base, overlay) => return errors.push(ConfigError::new(path, ConfigErrorKind::TypeMismatch { expected: json_type(base), found: json_type(&overlay) }))
+    };
+
+    let same_item = |left: &Map<String, Value>, right: &Map<String, Value>| keys.iter().all(|key| left.get(*key) == right.get(*key));
+
+    for (index, item) in overlay.into_iter().enumerate() {
+        let item_path = format!("{}[{}]", path, index);
+        let item = match item {
+            Value::Object(item) if keys.iter().all(|key| item.contains_key(*key)) => item,
+            Value::Object(_) => {
+                errors.push(ConfigError::new(item_path, ConfigErrorKind::InvalidValue(format!("'{}' fields are required", keys.join("', '")))));
+                continue;
+            },
+            item => {
+                errors.push(ConfigError::new(item_path, ConfigErrorKind::TypeMismatch { expected: "object", found: json_type(&item) }));
+                continue;
+            }
+        };
+
+        let position = base.iter().position(|base_item| matches!(base_item, Value::Object(base_item) if same_item(base_item, &item)));
+        match (position, keys) {
+            (Some(position), ["name"]) => {
+                let mut item = item;
+                let items = item.remove("items");
+                merge_json(&mut base[position], Value::Object(item), &item_path, errors);
+
+                if let (Some(Value::Object(base_item)), Some(items)) = (base.get_mut(position), items) {
+                    match base_item.get_mut("items") {
+                        Some(base_items) => merge_keyed_array(base_items, items, &join_path(&item_path, "items"), &["index"], errors),
+                        None => { base_item.insert("items".to_string(), items); }
+                    };
+                }
+            },
+            (Some(position), _) => base[position] = Value::Object(item),
+            (None, _) => base.push(Value::Object(item))
+        }
+    }
+}
+
+fn section_error<T: DeserializeOwned>(value: &Value, path: String) -> Option<ConfigError> {
+    serde_json::from_value::<T>(value.clone()).err().map(|error| ConfigError::new(path, ConfigErrorKind::InvalidValue(error.to_string())))
+}
+
+/* Serde errors do not have the location in the document, sections are checked one by one to find it */
+fn schema_error(document: &Value, error: serde_json::Error) -> ConfigError {
+    let languages = document.get("languages").and_then(|languages| languages.as_object()).into_iter().flatten()
+        .find_map(|(language, value)| section_error::<JsonLanguageConstant>(value, format!("languages.{}", language)));
+    let currencies = || document.get("currencies").and_then(|currencies| currencies.as_object()).into_iter().flatten()
+        .find_map(|(currency, value)| section_error::<CurrencyInfo>(value, format!("currencies.{}", currency)));
+    let assets = || document.get("assets").and_then(|assets| assets.as_object()).into_iter().flatten()
+        .find_map(|(asset, value)| section_error::<JsonAsset>(value, format!("assets.{}", asset)));
+    let types = || document.get("types").and_then(|types| types.as_array()).into_iter().flatten().enumerate()
+        .find_map(|(index, value)| section_error::<JsonDynamicType>(value, format!("types[{}]", index)));
+    let type_conversions = || document.get("type_conversion").and_then(|types| types.as_array()).into_iter().flatten().enumerate()
+        .find_map(|(index, value)| section_error::<JsonTypeConversion>(value, format!("type_conversion[{}]", index)));
+
+    languages
+        .or_else(currencies)
+        .or_else(assets)
+        .or_else(types)
+        .or_else(type_conversions)
+        .unwrap_or_else(|| ConfigError::new("", ConfigErrorKind::InvalidValue(error.to_string())))
+}
+
+fn check_regex(pattern: &str, path: String, errors: &mut Vec<ConfigError>) {
+    if let Err(error) = Regex::new(pattern) {
+        errors.push(ConfigError::new(path, ConfigErrorKind::InvalidRegex(error.to_string())));
+    }
+}
+
+fn validate_json(json_data: &JsonConstant) -> Vec<ConfigError> {
+    let mut errors = Vec::new();
+
+    for (parse_type, patterns) in json_data.parse.iter() {
+        for (index, pattern) in patterns.iter().enumerate() {
+            check_regex(pattern, format!("parse.{}[{}]", parse_type, index), &mut errors);
+        }
+    }
+
+    for alias in json_data.alias.keys() {
+        check_regex(&format!(r"\b{}\b", alias), format!("alias.{}", alias), &mut errors);
+    }
+
+    for (language, language_constant) in json_data.languages.iter() {
+        for alias in language_constant.alias.keys() {
+            check_regex(&format!(r"\b{}\b", alias), format!("languages.{}.alias.{}", language, alias), &mut errors);
+        }
+
+        for (field, months) in [("long_months", &language_constant.long_months), ("short_months", &language_constant.short_months)] {
+            for (name, month) in months.iter() {
+                if !(1..=12).contains(month) {
+                    errors.push(ConfigError::new(format!("languages.{}.{}.{}", language, field, name), ConfigErrorKind::InvalidValue(format!("{} is not a month number", month))));
+                }
+            }
+
+            for month in 1..=12 {
+                if !months.values().any(|number| *number == month) {
+                    errors.push(ConfigError::new(format!("languages.{}.{}", language, field), ConfigErrorKind::MissingMonth(month)));
+                }
+            }
+        }
+
+        for (name, constant_type) in language_constant.constant_pair.iter() {
+            if ConstantType::from_u8(*constant_type).is_none() {
+                errors.push(ConfigError::new(format!("languages.{}.constant_pair.{}", language, name), ConfigErrorKind::InvalidValue(format!("{} is not a constant type", constant_type))));
+            }
+        }
+
+        for rule_name in language_constant.rules.keys() {
+            if !RULE_FUNCTIONS.contains_key(rule_name) {
+                errors.push(ConfigError::new(format!("languages.{}.rules.{}", language, rule_name), ConfigErrorKind::UnknownFunction(rule_name.to_string())));
+            }
+        }
+    }
+
+    let mut type_names = BTreeMap::new();
+    let mut unit_names = BTreeMap::new();
+    for (type_index, dynamic_type) in json_data.types.iter().enumerate() {
+        let path = format!("types[{}]", type_index);
+        if type_names.insert(dynamic_type.name.to_string(), path.to_string()).is_some() {
+            errors.push(ConfigError::new(join_path(&path, "name"), ConfigErrorKind::DuplicateName(dynamic_type.name.to_string())));
+        }
+
+        for (item_index, item) in dynamic_type.items.iter().enumerate() {
+            let path = format!("{}.items[{}]", path, item_index);
+            match item.scale {
+                Some(scale) => {
+                    if scale == 0.0 || !scale.is_finite() {
+                        errors.push(ConfigError::new(join_path(&path, "scale"), ConfigErrorKind::InvalidValue(format!("{} is not a valid scale", scale))));
+                    }
+
+                    if item.inverse.unwrap_or_default() && item.offset.is_some() {
+                        errors.push(ConfigError::new(join_path(&path, "offset"), ConfigErrorKind::InvalidValue("inverse units can not have offset".to_string())));
+                    }
+                },
+                None => {
+                    for (field, code) in [("upgrade_code", &item.upgrade_code), ("downgrade_code", &item.downgrade_code)] {
+                        match code {
+                            Some(code) => errors.extend(check_formula(join_path(&path, field), code)),
+                            None => errors.push(ConfigError::new(&path, ConfigErrorKind::MissingField(field.to_string())))
+                        }
+                    }
+                }
+            }
+
+            for name in item.names.iter() {
+                if unit_names.insert(name.to_lowercase(), path.to_string()).is_some() {
+                    errors.push(ConfigError::new(join_path(&path, "names"), ConfigErrorKind::DuplicateName(name.to_string())));
+                }
+            }
+        }
+
+        /* Scaled units are converted through the base unit, they can not be mixed with calculation codes */
+        let scaled_items = dynamic_type.items.iter().filter(|item| item.scale.is_some()).count();
+        if scaled_items != 0 && scaled_items != dynamic_type.items.len() {
+            errors.push(ConfigError::new(&path, ConfigErrorKind::InvalidValue("'scale' is required for all items when one of them has it".to_string())));
+        }
+    }
+
+    for (index, type_conversion) in json_data.type_conversion.iter().enumerate() {
+        for (field, code) in [("to_source_calculation", &type_conversion.to_source_calculation), ("to_target_calculation", &type_conversion.to_target_calculation)] {
+            errors.extend(check_formula(format!("type_conversion[{}].{}", index, field), code));
+        }
+    }
+
+    errors.extend(check_references(json_data));
+    errors
+}
+
+/* Units with linear calculation codes are converted through the first unit of the group, "1 mile to mm" is calculated without going through every unit */
+fn set_group_base(group: &mut BTreeMap<usize, Rc<DynamicType>>) {
+    if group.values().any(|dynamic_type| dynamic_type.base.is_some()) {
+        return;
+    }
+
+    let mut bases = Vec::new();
+    let mut previous: Option<(usize, f64, f64)> = None;
+    for (index, dynamic_type) in group.iter() {
+        let (scale, offset) = match previous {
+            None => (1.0, 0.0),
+            Some((previous_index, scale, offset)) => match dynamic_type.downgrade.as_ref().and_then(Formula::affine) {
+                Some((code_scale, code_offset)) if *index == previous_index + 1 && code_scale != 0.0 => (scale * code_scale, scale * code_offset + offset),
+                _ => return
+            }
+        };
+
+        bases.push((*index, UnitBase::Affine { scale, offset }));
+        previous = Some((*index, scale, offset));
+    }
+
+    for (index, base) in bases {
+        if let Some(dynamic_type) = group.get_mut(&index) {
+            *dynamic_type = Rc::new(dynamic_type.with_base(base));
+        }
+    }
+}
+
+fn check_formula(path: String, code: &str) -> Option<ConfigError> {
+    match Formula::parse(code) {
+        Some(_) => None,
+        None => Some(ConfigError::new(path, ConfigErrorKind::InvalidValue(format!("'{}' is not a valid formula", code))))
+    }
+}
+
+/* Names used in the configuration must be defined in the configuration */
+fn check_references(json_data: &JsonConstant) -> Vec<ConfigError> {
+    let mut errors = Vec::new();
+    let currency_exists = |code: &str| json_data.currencies.keys().chain(json_data.assets.keys()).any(|currency| currency.to_lowercase() == code.to_lowercase());
+    let type_exists = |name: &str, index: usize| json_data.types.iter().any(|dynamic_type| dynamic_type.name == name && dynamic_type.items.iter().any(|item| item.index == index));
+
+    for (alias, code) in json_data.currency_alias.iter() {
+        if !currency_exists(code) {
+            errors.push(ConfigError::new(format!("currency_alias.{}", alias), ConfigErrorKind::UnknownCurrency(code.to_string())));
+        }
+    }
+
+    for code in json_data.currency_rates.keys() {
+        if !currency_exists(code) {
+            errors.push(ConfigError::new(format!("currency_rates.{}", code), ConfigErrorKind::UnknownCurrency(code.to_string())));
+        }
+    }
+
+    for (code, asset) in json_data.assets.iter() {
+        if json_data.currencies.keys().any(|currency| currency.to_lowercase() == code.to_lowercase()) {
+            errors.push(ConfigError::new(format!("assets.{}", code), ConfigErrorKind::DuplicateName(code.to_string())));
+        }
+
+        if !asset.rate.is_finite() || asset.rate <= 0.0 {
+            errors.push(ConfigError::new(format!("assets.{}.rate", code), ConfigErrorKind::InvalidValue(format!("{} is not a positive rate", asset.rate))));
+        }
+
+        for (unit, amount) in asset.units.iter() {
+            if !amount.is_finite() || *amount <= 0.0 {
+                errors.push(ConfigError::new(format!("assets.{}.units.{}", code, unit), ConfigErrorKind::InvalidValue(format!("{} is not a positive amount", amount))));
+            }
+        }
+    }
+
+    for (index, type_conversion) in json_data.type_conversion.iter().enumerate() {
+        for (field, item) in [("source", &type_conversion.source), ("target", &type_conversion.target)] {
+            if !type_exists(&item.name, item.index) {
+                errors.push(ConfigError::new(format!("type_conversion[{}].{}", index, field), ConfigErrorKind::UnknownDynamicType(format!("{}:{}", item.name, item.index))));
+            }
+        }
+    }
+
+    for (index, dynamic_type) in json_data.types.iter().enumerate() {
+        if let Some(unit) = &dynamic_type.unit {
+            for name in unit.split(['/', '·']).map(|name| name.trim().trim_end_matches(['²', '³']).to_lowercase()) {
+                let name_exists = json_data.types.iter().flat_map(|dynamic_type| dynamic_type.items.iter()).any(|item| item.names.contains(&name));
+                if !name_exists && !currency_exists(&name) {
+                    errors.push(ConfigError::new(format!("types[{}].unit", index), ConfigErrorKind::UnknownDynamicType(name)));
+                }
+            }
+        }
+    }
+
+    for (language, language_constant) in json_data.languages.iter() {
+        for (name, zone) in language_constant.locations.iter() {
+            if Tz::from_str(zone).is_err() {
+                errors.push(ConfigError::new(format!("languages.{}.locations.{}", language, name), ConfigErrorKind::InvalidValue(format!("{} is not an IANA timezone", zone))));
+            }
+        }
+
+        for (name, constant) in language_constant.named_constants.iter() {
+            if let Some(unit) = &constant.unit {
+                let unit_exists = json_data.types.iter().flat_map(|dynamic_type| dynamic_type.items.iter()).any(|item| item.names.contains(&unit.to_lowercase()));
+                if !unit_exists {
+                    errors.push(ConfigError::new(format!("languages.{}.named_constants.{}.unit", language, name), ConfigErrorKind::UnknownDynamicType(unit.to_string())));
+                }
+            }
+        }
+    }
+
+    errors
+}
@@ -32,6 +32,13 @@ pub struct DurationFormat {
     pub duration_type: DurationFormatType,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DynamicTypeFormat {
+    pub group: String,
+    pub index: usize,
+    pub format: String,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct MonthInfo {
     pub short: String,
@@ -44,6 +51,10 @@ pub struct JsonFormat {
     pub duration: Vec<DurationFormat>,
     pub date: BTreeMap<String, String>,
 
+    /* Dynamic types are written with the "format" of the type when the language has no format for it */
+    #[serde(default)]
+    pub dynamic_type: Vec<DynamicTypeFormat>,
+
     #[serde(skip)]
     pub language: String,
 }
@@ -61,6 +72,10 @@ pub enum ConstantType {
     Tomorrow = 9,
     Yesterday = 10,
     Now = 11,
+    Previous = 12,
+    Sum = 13,
+    Average = 14,
+    Line = 15,
 }
 
 #[derive(Clone, Debug, Serialize_repr, Deserialize_repr)]
@@ -90,6 +105,10 @@ impl ConstantType {
             9 => Some(ConstantType::Tomorrow),
             10 => Some(ConstantType::Yesterday),
             11 => Some(ConstantType::Now),
+            12 => Some(ConstantType::Previous),
+            13 => Some(ConstantType::Sum),
+            14 => Some(ConstantType::Average),
+            15 => Some(ConstantType::Line),
             _ => None,
         }
     }
@@ -107,6 +126,14 @@ pub struct LanguageRule {
     pub samples: Vec<Sample>,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JsonNamedConstant {
+    pub value: f64,
+
+    #[serde(default)]
+    pub unit: Option<String>
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct JsonLanguageConstant {
     pub number_notation: BTreeMap<String, NumberNotationType>,
@@ -114,18 +141,50 @@ pub struct JsonLanguageConstant {
     pub short_months: BTreeMap<String, u8>,
     pub word_group: BTreeMap<String, Vec<String>>,
     pub constant_pair: BTreeMap<String, u8>,
+
+    #[serde(default)]
+    pub named_constants: BTreeMap<String, JsonNamedConstant>,
+
+    #[serde(default)]
+    pub messages: BTreeMap<String, String>,
+
+    /* City and country names with their IANA zones, like "japan": "Asia/Tokyo" */
+    #[serde(default)]
+    pub locations: BTreeMap<String, String>,
     pub rules: BTreeMap<String, LanguageRule>,
     pub alias: BTreeMap<String, String>,
     pub format: JsonFormat,
 }
 
+/* Cryptocurrencies and commodities, they are used like currencies */
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Serialize, Deserialize)]
+pub struct JsonAsset {
+    #[serde(flatten)]
+    pub currency: CurrencyInfo,
+
+    /* Amount of the asset for one US dollar */
+    pub rate: f64,
+
+    #[serde(default)]
+    pub names: Vec<String>,
+
+    /* Amount of the asset in the unit, like troy ounces in a gram of gold */
+    #[serde(default)]
+    pub units: BTreeMap<String, f64>
+}
+
 #[derive(Default)]
 #[derive(Clone)]
 #[derive(Debug)]
 #[derive(Serialize, Deserialize)]
 pub struct JsonDynamicType {
     pub name: String,
-    pub items: Vec<JsonDynamicTypeItem>
+    pub items: Vec<JsonDynamicTypeItem>,
+
+    #[serde(default)]
+    pub unit: Option<String>
 }
 
 #[derive(Default)]
@@ -158,6 +217,9 @@ pub struct JsonDynamicTypeItem {
     pub parse: Vec<String>,
     pub names: Vec<String>,
 
+    #[serde(default)]
+    pub symbol: Option<String>,
+
     #[serde(default)]
     pub upgrade_code: Option<String>,
     
@@ -171,7 +233,16 @@ pub struct JsonDynamicTypeItem {
     pub use_fract_rounding: Option<bool>,
     
     #[serde(default)]
-    pub remove_fract_if_zero: Option<bool>
+    pub remove_fract_if_zero: Option<bool>,
+
+    #[serde(default)]
+    pub scale: Option<f64>,
+
+    #[serde(default)]
+    pub offset: Option<f64>,
+
+    #[serde(default)]
+    pub inverse: Option<bool>
 }
 
 #[derive(Serialize, Deserialize, Default, Clone)]
@@ -182,6 +253,9 @@ pub struct JsonConstant {
     pub currency_alias: BTreeMap<String, String>,
     pub currency_rates: BTreeMap<String, f64>,
     pub currencies: BTreeMap<String, Rc<CurrencyInfo>>,
+
+    #[serde(default)]
+    pub assets: BTreeMap<String, JsonAsset>,
     pub languages: BTreeMap<String, JsonLanguageConstant>,
     pub type_group: BTreeMap<String, Vec<String>>,
     pub timezones: BTreeMap<String, i32>,
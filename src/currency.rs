@@ -0,0 +1,370 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use regex::Regex;
+use serde_json::Value;
+
+use crate::error::CurrencyRateError;
+
+/// Source of the currency rates, used by `SmartCalc::update_currency_rates` to update all rates at once.
+pub trait CurrencyRateProvider {
+    /// Code of the currency which the rates are relative to, like `EUR`.
+    fn base_currency(&self) -> String;
+
+    /// Amount of each currency for one unit of the base currency, by currency code.
+    fn rates(&self) -> BTreeMap<String, f64>;
+
+    /// Date of the rates in UTC, if it is known.
+    fn as_of(&self) -> Option<NaiveDateTime>;
+
+    /// Rates older than this are stale.
+    fn max_age(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Currency rates loaded from a rate file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurrencyRates {
+    pub base: String,
+    pub as_of: Option<NaiveDateTime>,
+    pub max_age: Option<Duration>,
+    pub rates: BTreeMap<String, f64>
+}
+
+/// Base currency and date of the rates currently used in conversions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurrencyRatesInfo {
+    pub base: String,
+    pub as_of: Option<NaiveDateTime>,
+    pub max_age: Option<Duration>
+}
+
+impl Default for CurrencyRatesInfo {
+    fn default() -> Self {
+        CurrencyRatesInfo {
+            base: "USD".to_string(),
+            as_of: None,
+            max_age: None
+        }
+    }
+}
+
+impl CurrencyRatesInfo {
+    /// Rates are stale if they are older than `max_age`, or their date is not known while `max_age` is set.
+    pub fn is_stale(&self, now: NaiveDateTime) -> bool {
+        match (self.max_age, self.as_of) {
+            (Some(max_age), Some(as_of)) => now - as_of > max_age,
+            (Some(_), None) => true,
+            (None, _) => false
+        }
+    }
+}
+
+impl CurrencyRateProvider for CurrencyRates {
+    fn base_currency(&self) -> String {
+        self.base.to_string()
+    }
+
+    fn rates(&self) -> BTreeMap<String, f64> {
+        self.rates.clone()
+    }
+
+    fn as_of(&self) -> Option<NaiveDateTime> {
+        self.as_of
+    }
+
+    fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+}
+
+impl CurrencyRates {
+    pub fn new<T: ToString>(base: T, as_of: Option<NaiveDateTime>, rates: BTreeMap<String, f64>) -> Self {
+        CurrencyRates { base: base.to_string(), as_of, max_age: None, rates }
+    }
+
+    /// Load the rates with the format detected from the content, XML, JSON or CSV.
+    pub fn parse(data: &str) -> Result<Self, CurrencyRateError> {
+        match data.trim_start().chars().next() {
+            Some('<') => CurrencyRates::from_ecb_xml(data),
+            Some('{') => CurrencyRates::from_json(data),
+            _ => CurrencyRates::from_csv(data)
+        }
+    }
+
+    /// Load the euro foreign exchange reference rates of European Central Bank, like `eurofxref-daily.xml`.
+    ///
+    /// If the file has more than one day, the latest day is used.
+    pub fn from_ecb_xml(data: &str) -> Result<Self, CurrencyRateError> {
+        let (date, rates) = parse_ecb_days(data)?
+            .into_iter()
+            .max_by_key(|(date, _)| *date)
+            .ok_or(CurrencyRateError::NoRates)?;
+
+        CurrencyRates::new("EUR", Some(date.and_time(NaiveTime::MIN)), rates).checked()
+    }
+
+    /// Load the rates from a JSON map like `{"eur": 0.92}`, rates are relative to US dollar.
+    ///
+    /// Rate services format is also supported, `{"base": "EUR", "date": "2024-05-17", "rates": {"USD": 1.08}}`. Date could be given as unix `timestamp` too.
+    pub fn from_json(data: &str) -> Result<Self, CurrencyRateError> {
+        let document: Value = serde_json::from_str(data).map_err(|error| CurrencyRateError::Syntax(error.to_string()))?;
+        let object = document.as_object().ok_or_else(|| CurrencyRateError::Syntax("object expected".to_string()))?;
+
+        let (base, as_of, rates) = match object.get("rates") {
+            Some(rates) => {
+                let base = match object.get("base") {
+                    Some(Value::String(base)) => base.to_string(),
+                    Some(_) => return Err(CurrencyRateError::Syntax("'base' must be a currency code".to_string())),
+                    None => "USD".to_string()
+                };
+
+                let as_of = match (object.get("timestamp"), object.get("date")) {
+                    (Some(timestamp), _) => match timestamp.as_i64().and_then(|timestamp| DateTime::from_timestamp(timestamp, 0)) {
+                        Some(date_time) => Some(date_time.naive_utc()),
+                        None => return Err(CurrencyRateError::InvalidDate(timestamp.to_string()))
+                    },
+                    (None, Some(Value::String(date))) => Some(parse_date(date)?),
+                    (None, Some(date)) => return Err(CurrencyRateError::InvalidDate(date.to_string())),
+                    (None, None) => None
+                };
+
+                (base, as_of, rates.as_object().ok_or_else(|| CurrencyRateError::Syntax("'rates' must be an object".to_string()))?)
+            },
+            None => ("USD".to_string(), None, object)
+        };
+
+        let mut currency_rates = BTreeMap::new();
+        for (currency, rate) in rates.iter() {
+            match rate.as_f64() {
+                Some(rate) => currency_rates.insert(currency.to_lowercase(), rate),
+                None => return Err(CurrencyRateError::InvalidRate(currency.to_string()))
+            };
+        }
+
+        CurrencyRates::new(base, as_of, currency_rates).checked()
+    }
+
+    /// Load the rates from CSV.
+    ///
+    /// European Central Bank format with a `Date` column and a column for each currency is relative to euro, like `eurofxref.csv`.
+    /// Otherwise each line has a currency code and its rate relative to US dollar, like `eur,0.92`.
+    pub fn from_csv(data: &str) -> Result<Self, CurrencyRateError> {
+        let rows = csv_rows(data);
+        if is_ecb_csv(&rows) {
+            let (date, rates) = parse_ecb_csv_days(&rows)?
+                .into_iter()
+                .max_by_key(|(date, _)| *date)
+                .ok_or(CurrencyRateError::NoRates)?;
+
+            return CurrencyRates::new("EUR", Some(date.and_time(NaiveTime::MIN)), rates).checked();
+        }
+
+        let mut rates = BTreeMap::new();
+        for (index, cells) in rows.iter().enumerate() {
+            match cells.as_slice() {
+                /* Column names */
+                [_, rate] if index == 0 && rate.parse::<f64>().is_err() => continue,
+                [currency, rate] => rates.insert(currency.to_lowercase(), parse_rate(currency, rate)?),
+                _ => return Err(CurrencyRateError::Syntax(format!("'{}' must have currency and rate", cells.join(","))))
+            };
+        }
+
+        CurrencyRates::new("USD", None, rates).checked()
+    }
+
+    fn checked(self) -> Result<Self, CurrencyRateError> {
+        if self.rates.is_empty() {
+            return Err(CurrencyRateError::NoRates);
+        }
+
+        match self.rates.iter().find(|(_, rate)| !rate.is_finite() || **rate <= 0.0) {
+            Some((currency, _)) => Err(CurrencyRateError::InvalidRate(currency.to_string())),
+            None => Ok(self)
+        }
+    }
+}
+
+/// Daily currency rates, used in conversions with a date like `100 usd to eur on 1 mar 2021`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurrencyRateHistory {
+    pub base: String,
+    pub days: BTreeMap<NaiveDate, BTreeMap<String, f64>>
+}
+
+impl CurrencyRateHistory {
+    pub fn new<T: ToString>(base: T, days: BTreeMap<NaiveDate, BTreeMap<String, f64>>) -> Self {
+        CurrencyRateHistory { base: base.to_string(), days }
+    }
+
+    /// Load the history with the format detected from the content, XML, JSON or CSV.
+    pub fn parse(data: &str) -> Result<Self, CurrencyRateError> {
+        match data.trim_start().chars().next() {
+            Some('<') => CurrencyRateHistory::from_ecb_xml(data),
+            Some('{') => CurrencyRateHistory::from_json(data),
+            _ => CurrencyRateHistory::from_csv(data)
+        }
+    }
+
+    /// Load the historical reference rates of European Central Bank, like `eurofxref-hist.xml`.
+    pub fn from_ecb_xml(data: &str) -> Result<Self, CurrencyRateError> {
+        CurrencyRateHistory::new("EUR", parse_ecb_days(data)?.into_iter().collect()).checked()
+    }
+
+    /// Load the history from a JSON document like `{"base": "EUR", "rates": {"2021-03-01": {"USD": 1.2}}}`, base is US dollar if it is not given.
+    pub fn from_json(data: &str) -> Result<Self, CurrencyRateError> {
+        let document: Value = serde_json::from_str(data).map_err(|error| CurrencyRateError::Syntax(error.to_string()))?;
+        let base = match document.get("base") {
+            Some(Value::String(base)) => base.to_string(),
+            Some(_) => return Err(CurrencyRateError::Syntax("'base' must be a currency code".to_string())),
+            None => "USD".to_string()
+        };
+
+        let dates = document.get("rates").and_then(|rates| rates.as_object()).ok_or_else(|| CurrencyRateError::Syntax("'rates' must be an object".to_string()))?;
+        let mut days = BTreeMap::new();
+        for (date, rates) in dates.iter() {
+            let rates = rates.as_object().ok_or_else(|| CurrencyRateError::Syntax(format!("rates of '{}' must be an object", date)))?;
+            let mut day = BTreeMap::new();
+            for (currency, rate) in rates.iter() {
+                day.insert(currency.to_lowercase(), rate.as_f64().ok_or_else(|| CurrencyRateError::InvalidRate(currency.to_string()))?);
+            }
+            days.insert(parse_date(date)?.date(), day);
+        }
+
+        CurrencyRateHistory::new(base, days).checked()
+    }
+
+    /// Load the history from CSV.
+    ///
+    /// European Central Bank format with a `Date` column and a column for each currency is relative to euro, like `eurofxref-hist.csv`.
+    /// Otherwise each line has a date, currency code and its rate relative to US dollar, like `2021-03-01,eur,0.83`.
+    pub fn from_csv(data: &str) -> Result<Self, CurrencyRateError> {
+        let rows = csv_rows(data);
+        if is_ecb_csv(&rows) {
+            return CurrencyRateHistory::new("EUR", parse_ecb_csv_days(&rows)?.into_iter().collect()).checked();
+        }
+
+        let mut days: BTreeMap<NaiveDate, BTreeMap<String, f64>> = BTreeMap::new();
+        for (index, cells) in rows.iter().enumerate() {
+            match cells.as_slice() {
+                /* Column names */
+                [_, _, rate] if index == 0 && rate.parse::<f64>().is_err() => continue,
+                [date, currency, rate] => days.entry(parse_date(date)?.date()).or_default().insert(currency.to_lowercase(), parse_rate(currency, rate)?),
+                _ => return Err(CurrencyRateError::Syntax(format!("'{}' must have date, currency and rate", cells.join(","))))
+            };
+        }
+
+        CurrencyRateHistory::new("USD", days).checked()
+    }
+
+    fn checked(self) -> Result<Self, CurrencyRateError> {
+        if self.days.values().all(|rates| rates.is_empty()) {
+            return Err(CurrencyRateError::NoRates);
+        }
+
+        match self.days.values().flatten().find(|(_, rate)| !rate.is_finite() || **rate <= 0.0) {
+            Some((currency, _)) => Err(CurrencyRateError::InvalidRate(currency.to_string())),
+            None => Ok(self)
+        }
+    }
+}
+
+fn parse_rate(currency: &str, rate: &str) -> Result<f64, CurrencyRateError> {
+    rate.parse::<f64>().map_err(|_| CurrencyRateError::InvalidRate(currency.to_string()))
+}
+
+fn parse_date(date: &str) -> Result<NaiveDateTime, CurrencyRateError> {
+    let date = date.trim().trim_end_matches('Z');
+    for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S"] {
+        if let Ok(date_time) = NaiveDateTime::parse_from_str(date, format) {
+            return Ok(date_time);
+        }
+    }
+
+    for format in ["%Y-%m-%d", "%d %B %Y"] {
+        if let Ok(date) = NaiveDate::parse_from_str(date, format) {
+            return Ok(date.and_time(NaiveTime::MIN));
+        }
+    }
+
+    Err(CurrencyRateError::InvalidDate(date.to_string()))
+}
+
+type RateDay = (NaiveDate, BTreeMap<String, f64>);
+
+/* Cells of the lines, empty lines and comments are skipped */
+fn csv_rows(data: &str) -> Vec<Vec<&str>> {
+    data.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut cells = line.split(',').map(|cell| cell.trim()).collect::<Vec<_>>();
+            while cells.last() == Some(&"") {
+                cells.pop();
+            }
+            cells
+        })
+        .collect()
+}
+
+/* ECB header is the date column and currency codes, "date,currency,rate" is the header of the rows with one rate */
+fn is_ecb_csv(rows: &[Vec<&str>]) -> bool {
+    match rows.first().map(|header| header.as_slice()) {
+        Some([date, currency, ..]) => date.eq_ignore_ascii_case("date") && !currency.eq_ignore_ascii_case("currency"),
+        Some([date]) => date.eq_ignore_ascii_case("date"),
+        _ => false
+    }
+}
+
+/* First row has the currency codes, following rows have the date and the rates of the day */
+fn parse_ecb_csv_days(rows: &[Vec<&str>]) -> Result<Vec<RateDay>, CurrencyRateError> {
+    let header = rows.first().ok_or(CurrencyRateError::NoRates)?;
+    let mut days = Vec::new();
+
+    for row in rows.iter().skip(1) {
+        let date = parse_date(row.first().ok_or(CurrencyRateError::NoRates)?)?.date();
+        let mut rates = BTreeMap::new();
+
+        for (currency, rate) in header.iter().zip(row.iter()).skip(1) {
+            /* Currencies without rate in that day are written as N/A */
+            if rate.eq_ignore_ascii_case("n/a") {
+                continue;
+            }
+
+            rates.insert(currency.to_lowercase(), parse_rate(currency, rate)?);
+        }
+        days.push((date, rates));
+    }
+
+    Ok(days)
+}
+
+/* Each day is a Cube element with time attribute, rates of the day are in it */
+fn parse_ecb_days(data: &str) -> Result<Vec<RateDay>, CurrencyRateError> {
+    let cube_regex = Regex::new(r#"<Cube\s+(?:time\s*=\s*['"]([^'"]*)['"]|currency\s*=\s*['"]([^'"]*)['"]\s+rate\s*=\s*['"]([^'"]*)['"])"#).unwrap();
+    let mut days: Vec<RateDay> = Vec::new();
+
+    for capture in cube_regex.captures_iter(data) {
+        match (capture.get(1), capture.get(2), capture.get(3), days.last_mut()) {
+            (Some(time), _, _, _) => {
+                let date = NaiveDate::parse_from_str(time.as_str(), "%Y-%m-%d").map_err(|_| CurrencyRateError::InvalidDate(time.as_str().to_string()))?;
+                days.push((date, BTreeMap::new()));
+            },
+            (None, Some(currency), Some(rate), Some((_, rates))) => {
+                rates.insert(currency.as_str().to_lowercase(), parse_rate(currency.as_str(), rate.as_str())?);
+            },
+            _ => return Err(CurrencyRateError::Syntax("rate is not in a day".to_string()))
+        }
+    }
+
+    Ok(days)
+}
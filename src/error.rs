@@ -0,0 +1,295 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use core::fmt;
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+
+use serde_derive::Serialize;
+
+use crate::config::SmartCalcConfig;
+
+/// Location of the erroneous text in the line.
+///
+/// `start` and `end` are byte positions, `char_start` and `char_end` are character positions like `UiToken`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub char_start: usize,
+    pub char_end: usize
+}
+
+impl Span {
+    pub fn new(text: &str, start: usize, end: usize) -> Self {
+        Span {
+            start,
+            end,
+            char_start: char_position(text, start),
+            char_end: char_position(text, end)
+        }
+    }
+
+    pub fn merge(&self, other: &Span) -> Self {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+            char_start: self.char_start.min(other.char_start),
+            char_end: self.char_end.max(other.char_end)
+        }
+    }
+}
+
+fn char_position(text: &str, position: usize) -> usize {
+    match text.get(..position) {
+        Some(part) => part.chars().count(),
+        None => text.chars().count()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum ErrorKind {
+    UnexpectedEnd,
+    UnexpectedToken,
+    InvalidExpression,
+    UnclosedParenthesis,
+    InvalidFunctionArgument,
+    InvalidUnaryOperand,
+    UnknownOperator(char),
+    UnknownFunction(String),
+    ArgumentCount { function: String, min: usize, max: Option<usize> },
+    InvalidArgument { function: String },
+    UndefinedResult { function: String },
+    IncompatibleTypes { left: String, right: String },
+    AmbiguousConversion { left: String, right: String },
+    DivisionByZero,
+    Overflow,
+    BelowAbsoluteZero,
+    UndefinedOperation(char),
+    UnknownUnit(String),
+    UnknownCurrency(String),
+    MissingRate(String),
+    MissingHistoricalRate { currency: String, date: String },
+    RuleMismatch(String),
+    Custom(String)
+}
+
+impl ErrorKind {
+    /* Rule mismatch only means that the rule could not be applied to matched tokens */
+    pub(crate) fn is_reportable(&self) -> bool {
+        !matches!(self, ErrorKind::RuleMismatch(_))
+    }
+
+    /// Key of the message in the language's message catalogue.
+    pub fn message_key(&self) -> Option<&'static str> {
+        match self {
+            ErrorKind::UnexpectedEnd => Some("unexpected_end"),
+            ErrorKind::UnexpectedToken => Some("unexpected_token"),
+            ErrorKind::InvalidExpression => Some("invalid_expression"),
+            ErrorKind::UnclosedParenthesis => Some("unclosed_parenthesis"),
+            ErrorKind::InvalidFunctionArgument => Some("invalid_function_argument"),
+            ErrorKind::InvalidUnaryOperand => Some("invalid_unary_operand"),
+            ErrorKind::UnknownOperator(_) => Some("unknown_operator"),
+            ErrorKind::UnknownFunction(_) => Some("unknown_function"),
+            ErrorKind::ArgumentCount { min, max: Some(max), .. } if min == max => Some("argument_count"),
+            ErrorKind::ArgumentCount { max: Some(_), .. } => Some("argument_count_range"),
+            ErrorKind::ArgumentCount { max: None, .. } => Some("argument_count_at_least"),
+            ErrorKind::InvalidArgument { .. } => Some("invalid_argument"),
+            ErrorKind::UndefinedResult { .. } => Some("undefined_result"),
+            ErrorKind::IncompatibleTypes { .. } => Some("incompatible_types"),
+            ErrorKind::AmbiguousConversion { .. } => Some("ambiguous_conversion"),
+            ErrorKind::DivisionByZero => Some("division_by_zero"),
+            ErrorKind::Overflow => Some("overflow"),
+            ErrorKind::BelowAbsoluteZero => Some("below_absolute_zero"),
+            ErrorKind::UndefinedOperation(_) => Some("undefined_operation"),
+            ErrorKind::UnknownUnit(_) => Some("unknown_unit"),
+            ErrorKind::UnknownCurrency(_) => Some("unknown_currency"),
+            ErrorKind::MissingRate(_) => Some("missing_rate"),
+            ErrorKind::MissingHistoricalRate { .. } => Some("missing_historical_rate"),
+            ErrorKind::RuleMismatch(_) | ErrorKind::Custom(_) => None
+        }
+    }
+
+    fn message_arguments(&self, messages: &BTreeMap<String, String>) -> Vec<(&'static str, String)> {
+        let type_label = |type_name: &str| match messages.get(&format!("type_{}", type_name.to_lowercase())) {
+            Some(label) => label.to_string(),
+            None => type_name.to_string()
+        };
+
+        match self {
+            ErrorKind::UnknownOperator(operator) |
+            ErrorKind::UndefinedOperation(operator) => vec![("operator", operator.to_string())],
+            ErrorKind::UnknownFunction(function) |
+            ErrorKind::InvalidArgument { function } |
+            ErrorKind::UndefinedResult { function } => vec![("function", function.to_string())],
+            ErrorKind::ArgumentCount { function, min, max } => vec![("function", function.to_string()), ("min", min.to_string()), ("max", max.unwrap_or_default().to_string())],
+            ErrorKind::IncompatibleTypes { left, right } |
+            ErrorKind::AmbiguousConversion { left, right } => vec![("left", type_label(left)), ("right", type_label(right))],
+            ErrorKind::UnknownUnit(unit) => vec![("unit", unit.to_string())],
+            ErrorKind::UnknownCurrency(currency) |
+            ErrorKind::MissingRate(currency) => vec![("currency", currency.to_string())],
+            ErrorKind::MissingHistoricalRate { currency, date } => vec![("currency", currency.to_string()), ("date", date.to_string())],
+            _ => Vec::new()
+        }
+    }
+
+    /// Message in the given language, English message is used if the language does not have it.
+    pub fn localize(&self, config: &SmartCalcConfig, language: &str) -> String {
+        let messages = match config.json_data.languages.get(language) {
+            Some(language) => &language.messages,
+            None => return self.to_string()
+        };
+
+        let mut message = match self.message_key().and_then(|key| messages.get(key)) {
+            Some(message) => message.to_string(),
+            None => return self.to_string()
+        };
+
+        for (name, value) in self.message_arguments(messages) {
+            message = message.replace(&format!("{{{}}}", name), &value);
+        }
+        message
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::UnexpectedEnd => write!(f, "No more token"),
+            ErrorKind::UnexpectedToken => write!(f, "Unexpected token"),
+            ErrorKind::InvalidExpression => write!(f, "Invalid expression"),
+            ErrorKind::UnclosedParenthesis => write!(f, "Parentheses not closed"),
+            ErrorKind::InvalidFunctionArgument => write!(f, "Invalid function argument"),
+            ErrorKind::InvalidUnaryOperand => write!(f, "Unary works with number"),
+            ErrorKind::UnknownOperator(operator) => write!(f, "Unknown operator. ({})", operator),
+            ErrorKind::UnknownFunction(name) => write!(f, "Function not found. ({})", name),
+            ErrorKind::ArgumentCount { function, min, max: Some(max) } if min == max => write!(f, "{} function requires {} argument(s)", function, min),
+            ErrorKind::ArgumentCount { function, min, max: Some(max) } => write!(f, "{} function requires {} to {} argument(s)", function, min, max),
+            ErrorKind::ArgumentCount { function, min, max: None } => write!(f, "{} function requires at least {} argument(s)", function, min),
+            ErrorKind::InvalidArgument { function } => write!(f, "{} function argument is not valid", function),
+            ErrorKind::UndefinedResult { function } => write!(f, "{} function is not defined for the argument", function),
+            ErrorKind::IncompatibleTypes { left, right } => write!(f, "{} and {} are not compatible", left, right),
+            ErrorKind::AmbiguousConversion { left, right } => write!(f, "{} to {} conversion is ambiguous", left, right),
+            ErrorKind::DivisionByZero => write!(f, "Division by zero"),
+            ErrorKind::Overflow => write!(f, "Result is too big"),
+            ErrorKind::BelowAbsoluteZero => write!(f, "Value is below absolute zero"),
+            ErrorKind::UndefinedOperation(operator) => write!(f, "{} operation is not defined for the values", operator),
+            ErrorKind::UnknownUnit(unit) => write!(f, "Unknown unit. ({})", unit),
+            ErrorKind::UnknownCurrency(currency) => write!(f, "Unknown currency. ({})", currency),
+            ErrorKind::MissingRate(currency) => write!(f, "Currency rate not found. ({})", currency),
+            ErrorKind::MissingHistoricalRate { currency, date } => write!(f, "Currency rate of {} not found. ({})", date, currency),
+            ErrorKind::RuleMismatch(message) => write!(f, "{}", message),
+            ErrorKind::Custom(message) => write!(f, "{}", message)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SmartCalcError {
+    pub kind: ErrorKind,
+    pub span: Span,
+
+    /// Message in the session language.
+    pub message: String
+}
+
+impl SmartCalcError {
+    pub fn new(kind: ErrorKind, span: Span) -> Self {
+        let message = kind.to_string();
+        SmartCalcError { kind, span, message }
+    }
+
+    pub(crate) fn localize(mut self, config: &SmartCalcConfig, language: &str) -> Self {
+        self.message = self.kind.localize(config, language);
+        self
+    }
+}
+
+impl fmt::Display for SmartCalcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigErrorKind {
+    Syntax(String),
+    TypeMismatch { expected: &'static str, found: &'static str },
+    InvalidValue(String),
+    UnknownCurrency(String),
+    UnknownDynamicType(String),
+    InvalidRegex(String),
+    UnknownFunction(String),
+    MissingField(String),
+    MissingMonth(u8),
+    DuplicateName(String)
+}
+
+impl fmt::Display for ConfigErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigErrorKind::Syntax(message) => write!(f, "JSON parse error, {}", message),
+            ConfigErrorKind::TypeMismatch { expected, found } => write!(f, "{} expected but {} found", expected, found),
+            ConfigErrorKind::InvalidValue(message) => write!(f, "Invalid value, {}", message),
+            ConfigErrorKind::UnknownCurrency(currency) => write!(f, "Unknown currency. ({})", currency),
+            ConfigErrorKind::UnknownDynamicType(name) => write!(f, "Unknown dynamic type. ({})", name),
+            ConfigErrorKind::InvalidRegex(message) => write!(f, "Invalid regex, {}", message),
+            ConfigErrorKind::UnknownFunction(name) => write!(f, "Rule function not found. ({})", name),
+            ConfigErrorKind::MissingField(name) => write!(f, "'{}' field is missing", name),
+            ConfigErrorKind::MissingMonth(month) => write!(f, "Month {} is not defined", month),
+            ConfigErrorKind::DuplicateName(name) => write!(f, "'{}' name is already used", name)
+        }
+    }
+}
+
+/// Problem found in the JSON configuration.
+///
+/// `path` is the location of the problem in the document, like `languages.en.alias` or `types[2].items[0]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub path: String,
+    pub kind: ConfigErrorKind
+}
+
+impl ConfigError {
+    pub fn new<T: ToString>(path: T, kind: ConfigErrorKind) -> Self {
+        ConfigError { path: path.to_string(), kind }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path.is_empty() {
+            true => write!(f, "{}", self.kind),
+            false => write!(f, "{}: {}", self.path, self.kind)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CurrencyRateError {
+    Syntax(String),
+    InvalidRate(String),
+    InvalidDate(String),
+    NoRates,
+    UnknownCurrency(String)
+}
+
+impl fmt::Display for CurrencyRateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CurrencyRateError::Syntax(message) => write!(f, "Rate file could not be parsed, {}", message),
+            CurrencyRateError::InvalidRate(currency) => write!(f, "Invalid currency rate. ({})", currency),
+            CurrencyRateError::InvalidDate(date) => write!(f, "Invalid rate date. ({})", date),
+            CurrencyRateError::NoRates => write!(f, "No currency rate found"),
+            CurrencyRateError::UnknownCurrency(currency) => write!(f, "Unknown currency. ({})", currency)
+        }
+    }
+}
@@ -0,0 +1,442 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+/*
+ C interface of the library. Handles are created with `*_new` functions and must be released with the matching `*_free` function.
+ Strings returned from a result are owned by the result and valid until `smartcalc_result_free` is called.
+*/
+use core::ffi::{c_char, CStr};
+use core::ptr;
+use std::panic::AssertUnwindSafe;
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::smartcalc::ExecuteResult;
+use crate::token::ui_token::UiTokenType;
+use crate::{CurrencyRateHistory, CurrencyRates, Session, SmartCalc};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmartCalcLineStatus {
+    Empty,
+    Success,
+    Error
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmartCalcUiTokenType {
+    Text,
+    Number,
+    Symbol1,
+    Symbol2,
+    DateTime,
+    Operator,
+    Comment,
+    VariableDefination,
+    VariableUse,
+    Month,
+    Constant
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SmartCalcUiToken {
+    pub start: usize,
+    pub end: usize,
+    pub ui_type: SmartCalcUiTokenType
+}
+
+/// Error of the line, `message` is in the session language.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SmartCalcLineError {
+    pub message: *const c_char,
+    pub start: usize,
+    pub end: usize,
+    pub char_start: usize,
+    pub char_end: usize
+}
+
+struct FfiLine {
+    output: Option<CString>,
+    rate_date: Option<CString>,
+    error: Option<(CString, crate::Span)>,
+    ui_tokens: Vec<SmartCalcUiToken>
+}
+
+/// Execution result of all lines.
+pub struct SmartCalcResult {
+    lines: Vec<Option<FfiLine>>
+}
+
+impl From<&UiTokenType> for SmartCalcUiTokenType {
+    fn from(ui_type: &UiTokenType) -> Self {
+        match ui_type {
+            UiTokenType::Text => SmartCalcUiTokenType::Text,
+            UiTokenType::Number => SmartCalcUiTokenType::Number,
+            UiTokenType::Symbol1 => SmartCalcUiTokenType::Symbol1,
+            UiTokenType::Symbol2 => SmartCalcUiTokenType::Symbol2,
+            UiTokenType::DateTime => SmartCalcUiTokenType::DateTime,
+            UiTokenType::Operator => SmartCalcUiTokenType::Operator,
+            UiTokenType::Comment => SmartCalcUiTokenType::Comment,
+            UiTokenType::VariableDefination => SmartCalcUiTokenType::VariableDefination,
+            UiTokenType::VariableUse => SmartCalcUiTokenType::VariableUse,
+            UiTokenType::Month => SmartCalcUiTokenType::Month,
+            UiTokenType::Constant => SmartCalcUiTokenType::Constant
+        }
+    }
+}
+
+/* Interior null bytes could not be represented in C strings */
+fn to_c_string(text: String) -> CString {
+    CString::new(text.replace('\0', "")).unwrap_or_default()
+}
+
+impl From<ExecuteResult> for SmartCalcResult {
+    fn from(result: ExecuteResult) -> Self {
+        let lines = result.lines.into_iter().map(|line| line.map(|line| {
+            let (output, rate_date, error) = match line.result {
+                Ok(result) => (Some(to_c_string(result.output)), result.rate_date.map(|date| to_c_string(date.format("%Y-%m-%dT%H:%M:%SZ").to_string())), None),
+                Err(error) => (None, None, Some((to_c_string(error.message), error.span)))
+            };
+
+            FfiLine {
+                output,
+                rate_date,
+                error,
+                ui_tokens: line.ui_tokens.iter().map(|token| SmartCalcUiToken { start: token.start, end: token.end, ui_type: SmartCalcUiTokenType::from(&token.ui_type) }).collect()
+            }
+        })).collect();
+
+        SmartCalcResult { lines }
+    }
+}
+
+/* Panics must not unwind into the C caller, the fallback is returned instead. Only works when panics unwind, the "release-ffi" profile is used instead of "release" which aborts */
+fn guard<T>(fallback: T, function: impl FnOnce() -> T) -> T {
+    std::panic::catch_unwind(AssertUnwindSafe(function)).unwrap_or(fallback)
+}
+
+unsafe fn read_str<'a>(text: *const c_char) -> Option<&'a str> {
+    match text.is_null() {
+        true => None,
+        false => CStr::from_ptr(text).to_str().ok()
+    }
+}
+
+unsafe fn get_line<'a>(result: *const SmartCalcResult, index: usize) -> Option<&'a FfiLine> {
+    result.as_ref().and_then(|result| result.lines.get(index)).and_then(|line| line.as_ref())
+}
+
+/// Create a calculator with the default configuration.
+#[no_mangle]
+pub extern "C" fn smartcalc_new() -> *mut SmartCalc {
+    guard(ptr::null_mut(), || {
+        Box::into_raw(Box::default())
+    })
+}
+
+/// Create a calculator from JSON configuration. Returns null if the text is not valid UTF-8 or not a valid configuration.
+///
+/// # Safety
+/// `json_data` must be a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_load_from_json(json_data: *const c_char) -> *mut SmartCalc {
+    guard(ptr::null_mut(), || {
+        match read_str(json_data) {
+            Some(json_data) => match SmartCalc::try_load_from_json(json_data) {
+                Ok(smartcalc) => Box::into_raw(Box::new(smartcalc)),
+                Err(_) => ptr::null_mut()
+            },
+            None => ptr::null_mut()
+        }
+    })
+}
+
+/// # Safety
+/// `smartcalc` must be created with `smartcalc_new` or `smartcalc_load_from_json`, or null.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_free(smartcalc: *mut SmartCalc) {
+    guard((), || {
+        if !smartcalc.is_null() {
+            drop(Box::from_raw(smartcalc));
+        }
+    })
+}
+
+/// # Safety
+/// `smartcalc` must be a valid handle and `currency` a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_update_currency(smartcalc: *mut SmartCalc, currency: *const c_char, rate: f64) -> bool {
+    guard(false, || {
+        match (smartcalc.as_mut(), read_str(currency)) {
+            (Some(smartcalc), Some(currency)) => smartcalc.update_currency(currency, rate),
+            _ => false
+        }
+    })
+}
+
+/// Update the currency rates from ECB XML, CSV or JSON content, returns false if the content could not be loaded.
+///
+/// # Safety
+/// `smartcalc` must be a valid handle and `data` a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_update_currency_rates(smartcalc: *mut SmartCalc, data: *const c_char) -> bool {
+    guard(false, || {
+        match (smartcalc.as_mut(), read_str(data)) {
+            (Some(smartcalc), Some(data)) => CurrencyRates::parse(data).and_then(|rates| smartcalc.update_currency_rates(&rates)).is_ok(),
+            _ => false
+        }
+    })
+}
+
+/// Set the dated currency rates used in historical conversions from ECB XML, CSV or JSON content, returns false if the content could not be loaded.
+///
+/// # Safety
+/// `smartcalc` must be a valid handle and `data` a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_set_currency_rate_history(smartcalc: *mut SmartCalc, data: *const c_char) -> bool {
+    guard(false, || {
+        match (smartcalc.as_mut(), read_str(data)) {
+            (Some(smartcalc), Some(data)) => CurrencyRateHistory::parse(data).and_then(|history| smartcalc.set_currency_rate_history(&history)).is_ok(),
+            _ => false
+        }
+    })
+}
+
+/// # Safety
+/// `smartcalc` must be a valid handle and `timezone` a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_set_timezone(smartcalc: *mut SmartCalc, timezone: *const c_char) -> bool {
+    guard(false, || {
+        match (smartcalc.as_mut(), read_str(timezone)) {
+            (Some(smartcalc), Some(timezone)) => smartcalc.set_timezone(timezone.to_string()).is_ok(),
+            _ => false
+        }
+    })
+}
+
+/// # Safety
+/// `smartcalc` must be a valid handle and `separator` a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_set_decimal_separator(smartcalc: *mut SmartCalc, separator: *const c_char) -> bool {
+    guard(false, || {
+        match (smartcalc.as_mut(), read_str(separator)) {
+            (Some(smartcalc), Some(separator)) => {
+                smartcalc.set_decimal_seperator(separator.to_string());
+                true
+            },
+            _ => false
+        }
+    })
+}
+
+/// # Safety
+/// `smartcalc` must be a valid handle and `separator` a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_set_thousand_separator(smartcalc: *mut SmartCalc, separator: *const c_char) -> bool {
+    guard(false, || {
+        match (smartcalc.as_mut(), read_str(separator)) {
+            (Some(smartcalc), Some(separator)) => {
+                smartcalc.set_thousand_separator(separator.to_string());
+                true
+            },
+            _ => false
+        }
+    })
+}
+
+/// Execute the text, lines are separated with new line. Returns null if the arguments are not valid.
+///
+/// # Safety
+/// `smartcalc` must be a valid handle, `language` and `text` null terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_execute(smartcalc: *const SmartCalc, language: *const c_char, text: *const c_char) -> *mut SmartCalcResult {
+    guard(ptr::null_mut(), || {
+        match (smartcalc.as_ref(), read_str(language), read_str(text)) {
+            (Some(smartcalc), Some(language), Some(text)) => Box::into_raw(Box::new(SmartCalcResult::from(smartcalc.execute(language, text)))),
+            _ => ptr::null_mut()
+        }
+    })
+}
+
+/// Create an empty session.
+#[no_mangle]
+pub extern "C" fn smartcalc_session_new() -> *mut Session {
+    guard(ptr::null_mut(), || {
+        Box::into_raw(Box::new(Session::new()))
+    })
+}
+
+/// # Safety
+/// `session` must be created with `smartcalc_session_new`, or null.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_session_free(session: *mut Session) {
+    guard((), || {
+        if !session.is_null() {
+            drop(Box::from_raw(session));
+        }
+    })
+}
+
+/// # Safety
+/// `session` must be a valid handle and `text` a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_session_set_text(session: *mut Session, text: *const c_char) -> bool {
+    guard(false, || {
+        match (session.as_mut(), read_str(text)) {
+            (Some(session), Some(text)) => {
+                session.set_text(text.to_string());
+                true
+            },
+            _ => false
+        }
+    })
+}
+
+/// # Safety
+/// `session` must be a valid handle and `language` a null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_session_set_language(session: *mut Session, language: *const c_char) -> bool {
+    guard(false, || {
+        match (session.as_mut(), read_str(language)) {
+            (Some(session), Some(language)) => {
+                session.set_language(language.to_string());
+                true
+            },
+            _ => false
+        }
+    })
+}
+
+/// Execute all lines of the session. Returns null if the arguments are not valid.
+///
+/// # Safety
+/// `smartcalc` and `session` must be valid handles.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_execute_session(smartcalc: *const SmartCalc, session: *const Session) -> *mut SmartCalcResult {
+    guard(ptr::null_mut(), || {
+        match (smartcalc.as_ref(), session.as_ref()) {
+            (Some(smartcalc), Some(session)) => Box::into_raw(Box::new(SmartCalcResult::from(smartcalc.execute_session(session)))),
+            _ => ptr::null_mut()
+        }
+    })
+}
+
+/// # Safety
+/// `result` must be returned from an execute function, or null.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_result_free(result: *mut SmartCalcResult) {
+    guard((), || {
+        if !result.is_null() {
+            drop(Box::from_raw(result));
+        }
+    })
+}
+
+/// # Safety
+/// `result` must be a valid result, or null.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_result_line_count(result: *const SmartCalcResult) -> usize {
+    guard(0, || {
+        result.as_ref().map(|result| result.lines.len()).unwrap_or_default()
+    })
+}
+
+/// Empty lines and the lines out of range are `Empty`.
+///
+/// # Safety
+/// `result` must be a valid result, or null.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_result_line_status(result: *const SmartCalcResult, index: usize) -> SmartCalcLineStatus {
+    guard(SmartCalcLineStatus::Empty, || {
+        match get_line(result, index) {
+            Some(FfiLine { output: Some(_), .. }) => SmartCalcLineStatus::Success,
+            Some(FfiLine { error: Some(_), .. }) => SmartCalcLineStatus::Error,
+            _ => SmartCalcLineStatus::Empty
+        }
+    })
+}
+
+/// Formatted output of the line, null if the line does not have a result.
+///
+/// # Safety
+/// `result` must be a valid result, or null.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_result_line_output(result: *const SmartCalcResult, index: usize) -> *const c_char {
+    guard(ptr::null(), || {
+        match get_line(result, index).and_then(|line| line.output.as_ref()) {
+            Some(output) => output.as_ptr(),
+            None => ptr::null()
+        }
+    })
+}
+
+/// Date of the oldest currency rate used in the line like `2024-05-17T00:00:00Z`, null if the line does not have a currency conversion or the date is not known.
+///
+/// # Safety
+/// `result` must be a valid result, or null.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_result_line_rate_date(result: *const SmartCalcResult, index: usize) -> *const c_char {
+    guard(ptr::null(), || {
+        match get_line(result, index).and_then(|line| line.rate_date.as_ref()) {
+            Some(rate_date) => rate_date.as_ptr(),
+            None => ptr::null()
+        }
+    })
+}
+
+/// Fill the error of the line, returns false if the line does not have an error.
+///
+/// # Safety
+/// `result` must be a valid result, or null. `error` must point to writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_result_line_error(result: *const SmartCalcResult, index: usize, error: *mut SmartCalcLineError) -> bool {
+    guard(false, || {
+        match (get_line(result, index).and_then(|line| line.error.as_ref()), error.as_mut()) {
+            (Some((message, span)), Some(error)) => {
+                *error = SmartCalcLineError {
+                    message: message.as_ptr(),
+                    start: span.start,
+                    end: span.end,
+                    char_start: span.char_start,
+                    char_end: span.char_end
+                };
+                true
+            },
+            _ => false
+        }
+    })
+}
+
+/// # Safety
+/// `result` must be a valid result, or null.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_result_ui_token_count(result: *const SmartCalcResult, index: usize) -> usize {
+    guard(0, || {
+        get_line(result, index).map(|line| line.ui_tokens.len()).unwrap_or_default()
+    })
+}
+
+/// Fill the ui token of the line, returns false if the token does not exist.
+///
+/// # Safety
+/// `result` must be a valid result, or null. `token` must point to writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn smartcalc_result_ui_token(result: *const SmartCalcResult, index: usize, token_index: usize, token: *mut SmartCalcUiToken) -> bool {
+    guard(false, || {
+        match (get_line(result, index).and_then(|line| line.ui_tokens.get(token_index)), token.as_mut()) {
+            (Some(ui_token), Some(token)) => {
+                *token = *ui_token;
+                true
+            },
+            _ => false
+        }
+    })
+}
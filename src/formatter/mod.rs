@@ -8,11 +8,12 @@ use alloc::{string::String};
 use alloc::format;
 use alloc::string::ToString;
 use crate::session::Session;
-use crate::tools::do_divition;
+use crate::tools::{do_divition, round_number};
+use rust_decimal::{Decimal, RoundingStrategy};
 use core::ops::Deref;
 
 use crate::config::SmartCalcConfig;
-use crate::types::{SmartCalcAstType};
+use crate::types::{NumberValue, SmartCalcAstType};
 use crate::constants::MonthInfo;
 
 pub const MINUTE: i64 = 60;
@@ -81,6 +82,42 @@ pub fn format_number(number: f64, thousands_separator: String, decimal_separator
     trunc_formated
 }
 
+pub fn format_decimal(number: Decimal, thousands_separator: String, decimal_separator: String, decimal_digits: u8, remove_fract_if_zero: bool, use_fract_rounding: bool) -> String {
+    let formated_number = match use_fract_rounding {
+        true => format!("{:.width$}", number.round_dp_with_strategy(decimal_digits.into(), RoundingStrategy::MidpointAwayFromZero).abs(), width = decimal_digits.into()),
+        false => format!("{}", number.normalize().abs())
+    };
+
+    let (trunc_part, fract_part) = formated_number.split_once('.').unwrap_or((&formated_number, ""));
+    let mut trunc_formated = String::new();
+
+    if number.is_sign_negative() && trunc_part.chars().chain(fract_part.chars()).any(|digit| digit != '0') {
+        trunc_formated.push('-');
+    }
+
+    for (index, digit) in trunc_part.chars().enumerate() {
+        if index != 0 && (trunc_part.len() - index) % 3 == 0 {
+            trunc_formated.push_str(&thousands_separator);
+        }
+        trunc_formated.push(digit);
+    }
+
+    if !fract_part.is_empty() && (fract_part.chars().any(|digit| digit != '0') || !remove_fract_if_zero) {
+        trunc_formated.push_str(&decimal_separator);
+        trunc_formated.push_str(fract_part);
+    }
+
+    trunc_formated
+}
+
+/* Decimal values are rounded while formatting, f64 values are rounded in decimal mode */
+pub fn format_value(config: &SmartCalcConfig, number: NumberValue, decimal_digits: u8, remove_fract_if_zero: bool) -> String {
+    match number {
+        NumberValue::Float(number) => format_number(round_number(config, number, decimal_digits), config.thousand_separator.to_string(), config.decimal_seperator.to_string(), decimal_digits, remove_fract_if_zero, true),
+        NumberValue::Decimal(number) => format_decimal(number, config.thousand_separator.to_string(), config.decimal_seperator.to_string(), decimal_digits, remove_fract_if_zero, true)
+    }
+}
+
 pub fn get_month_info(config: &SmartCalcConfig, language: &'_ str, month: u8) -> Option<MonthInfo> {
     match config.month_regex.get(language) {
         Some(month_list) => month_list.get((month - 1) as usize).map(|(_, month)| month.clone()),
@@ -133,6 +170,21 @@ fn format_number_test() {
     assert_eq!(format_number(-123456.1, ",".to_string(), ".".to_string(), 2, false, false), "-123,456.1".to_string());
 }
 
+#[cfg(test)]
+#[test]
+fn format_decimal_test() {
+    use core::str::FromStr;
+    let decimal = |number| Decimal::from_str(number).unwrap();
+
+    assert_eq!(format_decimal(decimal("123"), ",".to_string(), ".".to_string(), 2, false, true), "123.00".to_string());
+    assert_eq!(format_decimal(decimal("123"), ",".to_string(), ".".to_string(), 2, true, true), "123".to_string());
+    assert_eq!(format_decimal(decimal("1234.005"), ",".to_string(), ".".to_string(), 2, false, true), "1,234.01".to_string());
+    assert_eq!(format_decimal(decimal("123456.123456789"), ",".to_string(), ".".to_string(), 2, false, false), "123,456.123456789".to_string());
+    assert_eq!(format_decimal(decimal("-123456.1"), ",".to_string(), ".".to_string(), 2, false, true), "-123,456.10".to_string());
+    assert_eq!(format_decimal(decimal("-0.001"), ",".to_string(), ".".to_string(), 2, true, true), "0".to_string());
+    assert_eq!(format_decimal(decimal("12345678901234567.89"), ".".to_string(), ",".to_string(), 2, false, true), "12.345.678.901.234.567,89".to_string());
+}
+
 #[cfg(test)]
 #[test]
 fn format_result_test() {
@@ -146,9 +198,9 @@ fn format_result_test() {
 
     let mut session = Session::default();
     session.set_language("en".to_string());
-    assert_eq!(NumberItem(123456.123456789, NumberType::Decimal).print(&config, &session), "123.456,12".to_string());
-    assert_eq!(NumberItem(1.123456789, NumberType::Decimal).print(&config, &session), "1,12".to_string());
-    assert_eq!(NumberItem(2.0, NumberType::Hexadecimal).print(&config, &session), "0x2".to_string());
+    assert_eq!(NumberItem(123456.123456789.into(), NumberType::Decimal).print(&config, &session), "123.456,12".to_string());
+    assert_eq!(NumberItem(1.123456789.into(), NumberType::Decimal).print(&config, &session), "1,12".to_string());
+    assert_eq!(NumberItem(2.0.into(), NumberType::Hexadecimal).print(&config, &session), "0x2".to_string());
             
     assert_eq!(format_result(&config, &session, Rc::new(SmartCalcAstType::Item(Rc::new(TimeItem(chrono::Utc::today().and_hms(11, 30, 0).naive_utc(), config.get_time_offset()))))), "11:30:00 UTC".to_string());
     assert_eq!(format_result(&config, &session, Rc::new(SmartCalcAstType::Item(Rc::new(TimeItem(chrono::Utc::today().and_hms(0, 0, 0).naive_utc(), config.get_time_offset()))))), "00:00:00 UTC".to_string());
@@ -0,0 +1,134 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use core::ops::Deref;
+use alloc::rc::Rc;
+use alloc::string::ToString;
+
+use crate::compiler::{incompatible_types, DataItem, OperationType, UnaryType};
+use crate::compiler::number::NumberItem;
+use crate::config::SmartCalcConfig;
+use crate::error::ErrorKind;
+use crate::types::{NumberType, TokenType};
+
+use super::{check_argument_count, get_number_argument, get_number_value, replace_number};
+
+pub fn sqrt(_: &SmartCalcConfig, arguments: &[Rc<dyn DataItem>]) -> Result<Rc<dyn DataItem>, ErrorKind> {
+    check_argument_count("sqrt", arguments, 1, 1)?;
+    let number = get_number_argument("sqrt", arguments, 0)?;
+
+    if number < 0.0 {
+        return Err(ErrorKind::UndefinedResult { function: "sqrt".to_string() });
+    }
+    
+    Ok(Rc::new(NumberItem(number.sqrt().into(), NumberType::Decimal)))
+}
+
+pub fn abs(_: &SmartCalcConfig, arguments: &[Rc<dyn DataItem>]) -> Result<Rc<dyn DataItem>, ErrorKind> {
+    check_argument_count("abs", arguments, 1, 1)?;
+    
+    let item = arguments[0].clone();
+    match item.get_underlying_number() < 0.0 {
+        true => Ok(item.unary(UnaryType::Minus)),
+        false => Ok(item)
+    }
+}
+
+/* Decimal numbers used in rounding have 28 decimal digits at most */
+const MAX_DECIMAL_DIGITS: f64 = 28.0;
+
+pub fn round(_: &SmartCalcConfig, arguments: &[Rc<dyn DataItem>]) -> Result<Rc<dyn DataItem>, ErrorKind> {
+    check_argument_count("round", arguments, 1, 2)?;
+
+    let decimal_digits = match arguments.len() {
+        2 => get_number_argument("round", arguments, 1)?,
+        _ => 0.0
+    };
+
+    if !(0.0..=MAX_DECIMAL_DIGITS).contains(&decimal_digits) || decimal_digits.fract() != 0.0 {
+        return Err(ErrorKind::InvalidArgument { function: "round".to_string() });
+    }
+
+    let item = arguments[0].deref();
+    replace_number(item, get_number_value(item).round(decimal_digits as u8)).ok_or_else(|| ErrorKind::InvalidArgument { function: "round".to_string() })
+}
+
+pub fn floor(_: &SmartCalcConfig, arguments: &[Rc<dyn DataItem>]) -> Result<Rc<dyn DataItem>, ErrorKind> {
+    check_argument_count("floor", arguments, 1, 1)?;
+
+    let item = arguments[0].deref();
+    replace_number(item, get_number_value(item).floor()).ok_or_else(|| ErrorKind::InvalidArgument { function: "floor".to_string() })
+}
+
+pub fn ceil(_: &SmartCalcConfig, arguments: &[Rc<dyn DataItem>]) -> Result<Rc<dyn DataItem>, ErrorKind> {
+    check_argument_count("ceil", arguments, 1, 1)?;
+
+    let item = arguments[0].deref();
+    replace_number(item, get_number_value(item).ceil()).ok_or_else(|| ErrorKind::InvalidArgument { function: "ceil".to_string() })
+}
+
+fn compare(config: &SmartCalcConfig, left: &dyn DataItem, right: &dyn DataItem) -> Result<f64, ErrorKind> {
+    let comparable = match (left.as_token_type(), right.as_token_type()) {
+        (TokenType::DynamicType(_, left_type), TokenType::DynamicType(_, right_type)) => left_type.group_name == right_type.group_name,
+        _ => left.type_name() == right.type_name()
+    };
+
+    if !comparable {
+        return Err(incompatible_types(left, right));
+    }
+
+    match left.calculate(config, true, right, OperationType::Sub) {
+        Some(result) => Ok(result.get_underlying_number()),
+        None => Err(incompatible_types(left, right))
+    }
+}
+
+fn find_item(config: &SmartCalcConfig, name: &str, arguments: &[Rc<dyn DataItem>], replace: fn(f64) -> bool) -> Result<Rc<dyn DataItem>, ErrorKind> {
+    check_argument_count(name, arguments, 1, usize::MAX)?;
+
+    let mut found = arguments[0].clone();
+    for item in arguments.iter().skip(1) {
+        if replace(compare(config, item.deref(), found.deref())?) {
+            found = item.clone();
+        }
+    }
+
+    Ok(found)
+}
+
+pub fn min(config: &SmartCalcConfig, arguments: &[Rc<dyn DataItem>]) -> Result<Rc<dyn DataItem>, ErrorKind> {
+    find_item(config, "min", arguments, |difference| difference < 0.0)
+}
+
+pub fn max(config: &SmartCalcConfig, arguments: &[Rc<dyn DataItem>]) -> Result<Rc<dyn DataItem>, ErrorKind> {
+    find_item(config, "max", arguments, |difference| difference > 0.0)
+}
+
+pub fn log(_: &SmartCalcConfig, arguments: &[Rc<dyn DataItem>]) -> Result<Rc<dyn DataItem>, ErrorKind> {
+    check_argument_count("log", arguments, 1, 2)?;
+    let number = get_number_argument("log", arguments, 0)?;
+    let base = match arguments.len() {
+        2 => get_number_argument("log", arguments, 1)?,
+        _ => 10.0
+    };
+
+    if number <= 0.0 || base <= 0.0 || base == 1.0 {
+        return Err(ErrorKind::UndefinedResult { function: "log".to_string() });
+    }
+
+    Ok(Rc::new(NumberItem(number.log(base).into(), NumberType::Decimal)))
+}
+
+pub fn ln(_: &SmartCalcConfig, arguments: &[Rc<dyn DataItem>]) -> Result<Rc<dyn DataItem>, ErrorKind> {
+    check_argument_count("ln", arguments, 1, 1)?;
+    let number = get_number_argument("ln", arguments, 0)?;
+
+    if number <= 0.0 {
+        return Err(ErrorKind::UndefinedResult { function: "ln".to_string() });
+    }
+
+    Ok(Rc::new(NumberItem(number.ln().into(), NumberType::Decimal)))
+}
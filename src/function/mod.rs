@@ -0,0 +1,100 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+pub mod math;
+
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::collections::btree_map::BTreeMap;
+use lazy_static::*;
+
+use crate::compiler::DataItem;
+use crate::compiler::number::NumberItem;
+use crate::compiler::money::MoneyItem;
+use crate::compiler::percent::PercentItem;
+use crate::compiler::dynamic_type::DynamicTypeItem;
+use crate::compiler::compound_type::CompoundTypeItem;
+use crate::config::SmartCalcConfig;
+use crate::error::ErrorKind;
+use crate::smartcalc::FunctionTrait;
+use crate::types::{NumberValue, TokenType};
+
+use self::math::*;
+
+pub type FunctionFunc = fn(config: &SmartCalcConfig, arguments: &[Rc<dyn DataItem>]) -> Result<Rc<dyn DataItem>, ErrorKind>;
+
+#[allow(clippy::upper_case_acronyms)]
+pub enum FunctionType {
+    Internal(FunctionFunc),
+    API(Rc<dyn FunctionTrait>)
+}
+
+impl FunctionType {
+    pub fn call(&self, config: &SmartCalcConfig, arguments: &[Rc<dyn DataItem>]) -> Result<Rc<dyn DataItem>, ErrorKind> {
+        match self {
+            FunctionType::Internal(function) => function(config, arguments),
+            FunctionType::API(function) => function.call(config, arguments)
+        }
+    }
+}
+
+lazy_static! {
+    pub static ref MATH_FUNCTIONS: BTreeMap<String, FunctionFunc> = {
+        let mut m = BTreeMap::new();
+        m.insert("sqrt".to_string(),  sqrt as FunctionFunc);
+        m.insert("abs".to_string(),   abs as FunctionFunc);
+        m.insert("round".to_string(), round as FunctionFunc);
+        m.insert("floor".to_string(), floor as FunctionFunc);
+        m.insert("ceil".to_string(),  ceil as FunctionFunc);
+        m.insert("min".to_string(),   min as FunctionFunc);
+        m.insert("max".to_string(),   max as FunctionFunc);
+        m.insert("log".to_string(),   log as FunctionFunc);
+        m.insert("ln".to_string(),    ln as FunctionFunc);
+        m
+    };
+}
+
+pub fn check_argument_count(name: &str, arguments: &[Rc<dyn DataItem>], min: usize, max: usize) -> Result<(), ErrorKind> {
+    if arguments.len() < min || arguments.len() > max {
+        return Err(ErrorKind::ArgumentCount {
+            function: name.to_string(),
+            min,
+            max: match max {
+                usize::MAX => None,
+                _ => Some(max)
+            }
+        });
+    }
+    Ok(())
+}
+
+pub fn get_number_argument(name: &str, arguments: &[Rc<dyn DataItem>], index: usize) -> Result<f64, ErrorKind> {
+    match arguments.get(index) {
+        Some(item) if item.type_name() == "NUMBER" => Ok(item.get_underlying_number()),
+        _ => Err(ErrorKind::InvalidArgument { function: name.to_string() })
+    }
+}
+
+/* Numbers and money keep their decimal values */
+pub fn get_number_value(item: &dyn DataItem) -> NumberValue {
+    match item.as_token_type() {
+        TokenType::Number(number, _) | TokenType::Money(number, _) => number,
+        _ => item.get_underlying_number().into()
+    }
+}
+
+/* Creates same typed item with new number, "round($1.234)" stays money */
+pub fn replace_number(item: &dyn DataItem, number: NumberValue) -> Option<Rc<dyn DataItem>> {
+    match item.as_token_type() {
+        TokenType::Number(_, number_type)        => Some(Rc::new(NumberItem(number, number_type))),
+        TokenType::Money(_, currency)            => Some(Rc::new(MoneyItem(number, currency))),
+        TokenType::Percent(_)                    => Some(Rc::new(PercentItem(number.to_f64()))),
+        TokenType::DynamicType(_, dynamic_type)  => Some(Rc::new(DynamicTypeItem(number.to_f64(), dynamic_type))),
+        TokenType::CompoundType(_, unit)         => Some(Rc::new(CompoundTypeItem(number.to_f64(), unit))),
+        _ => None
+    }
+}
@@ -26,12 +26,26 @@ pub(crate) mod token;
 pub(crate) mod config;
 pub(crate) mod smartcalc;
 pub(crate) mod variable;
+pub(crate) mod function;
 pub(crate) mod session;
+pub(crate) mod error;
+pub(crate) mod result;
+pub(crate) mod currency;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "ffi")]
+extern crate std;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
 
 #[cfg(test)]
 mod tests;
 
 pub use smartcalc::SmartCalc;
+pub use smartcalc::{ExecuteResult, ExecuteLine, ExecuteLineResult, ReexecuteResult, SampleMismatch};
 pub use session::Session;
 pub use config::SmartCalcConfig;
 pub use types::SmartCalcAstType;
@@ -40,5 +54,11 @@ pub use compiler::DataItem;
 pub use token::ui_token::UiToken;
 pub use token::ui_token::UiTokenType;
 pub use smartcalc::RuleTrait;
+pub use smartcalc::FunctionTrait;
 pub use types::TokenType;
-pub use types::NumberType;
\ No newline at end of file
+pub use types::NumberType;
+pub use types::NumericMode;
+pub use types::NumberValue;
+pub use error::{SmartCalcError, ErrorKind, Span, ConfigError, ConfigErrorKind, CurrencyRateError};
+pub use currency::{CurrencyRateProvider, CurrencyRates, CurrencyRatesInfo, CurrencyRateHistory};
+pub use result::{JsonExecuteResult, LineResult, LineValue, ValueKind};
\ No newline at end of file
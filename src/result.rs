@@ -0,0 +1,143 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use core::ops::Deref;
+use alloc::vec::Vec;
+use alloc::string::{String, ToString};
+use serde_derive::Serialize;
+
+use crate::compiler::DataItem;
+use crate::compiler::duration::DurationItem;
+use crate::compiler::dynamic_type::DynamicTypeItem;
+use crate::compiler::compound_type::{CompoundTypeItem, UnitKind};
+use crate::compiler::money::MoneyItem;
+use crate::error::SmartCalcError;
+use crate::smartcalc::{ExecuteLine, ExecuteResult};
+use crate::token::ui_token::UiToken;
+use crate::types::SmartCalcAstType;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueKind {
+    Number,
+    Money,
+    Percent,
+    DynamicType,
+    CompoundType,
+    Date,
+    Time,
+    DateTime,
+    Duration,
+    Month,
+    None
+}
+
+/// Calculated value of the line.
+///
+/// `number` is the value in the given `unit` or `currency`, durations are in seconds. Dates and times do not have a number.
+/// `rate_date` is the date of the oldest currency rate used in the calculation, like `2024-05-17T00:00:00Z`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LineValue {
+    pub kind: ValueKind,
+    pub number: Option<f64>,
+    pub unit: Option<String>,
+    pub currency: Option<String>,
+    pub rate_date: Option<String>
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LineResult {
+    pub output: Option<String>,
+    pub value: Option<LineValue>,
+    pub error: Option<SmartCalcError>,
+    pub ui_tokens: Vec<UiToken>
+}
+
+/// Serializable form of `ExecuteResult`, empty lines are `null`.
+#[derive(Debug, Clone, Serialize, PartialEq, Default)]
+pub struct JsonExecuteResult {
+    pub status: bool,
+    pub lines: Vec<Option<LineResult>>
+}
+
+impl LineValue {
+    fn from_item(item: &dyn DataItem) -> Self {
+        let kind = match item.type_name() {
+            "NUMBER" => ValueKind::Number,
+            "MONEY" => ValueKind::Money,
+            "PERCENT" => ValueKind::Percent,
+            "DYNAMIC_TYPE" => ValueKind::DynamicType,
+            "COMPOUND_TYPE" => ValueKind::CompoundType,
+            "DATE" => ValueKind::Date,
+            "TIME" => ValueKind::Time,
+            "DATE_TIME" => ValueKind::DateTime,
+            "DURATION" => ValueKind::Duration,
+            _ => ValueKind::None
+        };
+
+        let number = match kind {
+            ValueKind::Number | ValueKind::Money | ValueKind::Percent | ValueKind::DynamicType | ValueKind::CompoundType => Some(item.get_underlying_number()),
+            ValueKind::Duration => item.as_any().downcast_ref::<DurationItem>().map(|duration| duration.get_duration().num_seconds() as f64),
+            _ => None
+        };
+
+        LineValue {
+            kind,
+            number,
+            unit: match item.as_any().downcast_ref::<CompoundTypeItem>() {
+                Some(compound) => Some(compound.get_unit().format()),
+                None => item.as_any().downcast_ref::<DynamicTypeItem>().and_then(|dynamic_type| dynamic_type.get_type().names.first().cloned())
+            },
+            currency: match item.as_any().downcast_ref::<CompoundTypeItem>() {
+                Some(compound) => compound.get_unit().terms.iter().find_map(|term| match &term.kind {
+                    UnitKind::Currency(currency) => Some(currency.code.to_string()),
+                    _ => None
+                }),
+                None => item.as_any().downcast_ref::<MoneyItem>().map(|money| money.get_currency().code.to_string())
+            },
+            rate_date: None
+        }
+    }
+
+    pub fn from_ast(ast: &SmartCalcAstType) -> Option<Self> {
+        match ast {
+            SmartCalcAstType::Item(item) => Some(LineValue::from_item(item.deref())),
+            SmartCalcAstType::Variable(variable) => LineValue::from_ast(variable.data.borrow().deref()),
+            SmartCalcAstType::Month(month) => Some(LineValue { kind: ValueKind::Month, number: Some(*month as f64), unit: None, currency: None, rate_date: None }),
+            _ => None
+        }
+    }
+}
+
+impl From<&ExecuteLine> for LineResult {
+    fn from(line: &ExecuteLine) -> Self {
+        let (output, value, error) = match &line.result {
+            Ok(result) => {
+                let rate_date = result.rate_date.map(|date| date.format("%Y-%m-%dT%H:%M:%SZ").to_string());
+                let value = LineValue::from_ast(result.ast.deref()).map(|value| LineValue { rate_date, ..value });
+                (Some(result.output.to_string()), value, None)
+            },
+            Err(error) => (None, None, Some(error.clone()))
+        };
+
+        LineResult { output, value, error, ui_tokens: line.ui_tokens.clone() }
+    }
+}
+
+impl From<&ExecuteResult> for JsonExecuteResult {
+    fn from(result: &ExecuteResult) -> Self {
+        JsonExecuteResult {
+            status: result.status,
+            lines: result.lines.iter().map(|line| line.as_ref().map(LineResult::from)).collect()
+        }
+    }
+}
+
+impl JsonExecuteResult {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
@@ -5,20 +5,47 @@
  */
 
 use core::cell::{Cell, RefCell};
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::string::{String, ToString};
 
 use alloc::{rc::Rc, vec::Vec};
 use regex::Regex;
 
+use chrono::NaiveDateTime;
+
+use crate::types::SmartCalcAstType;
 use crate::variable::VariableInfo;
 
+/* Execution information of the line, used by incremental re-execution */
+#[derive(Default, Clone)]
+pub(crate) struct LineState {
+    pub result: Option<Rc<SmartCalcAstType>>,
+    pub output: Option<Result<String, String>>,
+    pub assignment: Option<(Rc<VariableInfo>, Rc<SmartCalcAstType>)>,
+    pub used_variables: BTreeSet<String>,
+    pub use_line_reference: bool,
+    pub rate_dates: Vec<Option<NaiveDateTime>>
+}
+
+impl LineState {
+    /* Value of the line with full precision, "1,001" and "1,002" have the same output */
+    pub fn value(&self) -> Option<Result<String, String>> {
+        match self.result.as_deref() {
+            Some(SmartCalcAstType::Item(item)) => Some(Ok(item.as_token_type().to_string())),
+            _ => self.output.clone()
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct Session {
     text: String,
     text_parts: Vec<String>,
     language: String,
     position: Cell<usize>,
+    line_states: RefCell<Vec<LineState>>,
+    initial_variables: RefCell<Vec<(Rc<VariableInfo>, Rc<SmartCalcAstType>)>>,
+    updated_lines: RefCell<BTreeSet<usize>>,
 
     pub(crate) variables: RefCell<BTreeMap<String, Rc<VariableInfo>>>
 }
@@ -33,7 +60,10 @@ impl Session {
             text_parts: Vec::new(),
             language: String::new(),
             variables: RefCell::new(BTreeMap::new()),
-            position: Cell::default()
+            position: Cell::default(),
+            line_states: RefCell::new(Vec::new()),
+            initial_variables: RefCell::new(Vec::new()),
+            updated_lines: RefCell::new(BTreeSet::new())
         }
     }
 
@@ -45,6 +75,36 @@ impl Session {
             Ok(re) => re.split(&self.text).map(|item| item.to_string()).collect::<Vec<_>>(),
             _ => self.text.lines().map(|item| item.to_string()).collect::<Vec<_>>()
         };
+        self.line_states.borrow_mut().clear();
+        self.updated_lines.borrow_mut().clear();
+    }
+
+    /// Replace the text of a single line.
+    ///
+    /// Changed lines are calculated again with `SmartCalc::reexecute`. Returns `false` if the line does not exist.
+    pub fn update_line(&mut self, index: usize, text: String) -> bool {
+        if index >= self.text_parts.len() || text.contains('\n') {
+            return false;
+        }
+
+        self.text_parts[index] = text;
+        self.text = self.text_parts.join("\n");
+        self.updated_lines.borrow_mut().insert(index);
+        true
+    }
+
+    /// Add a line to the end of the text.
+    ///
+    /// New line is calculated with `SmartCalc::reexecute`, like the lines changed with `update_line`.
+    pub fn add_line(&mut self, text: String) -> bool {
+        if text.contains('\n') {
+            return false;
+        }
+
+        self.text_parts.push(text);
+        self.text = self.text_parts.join("\n");
+        self.updated_lines.borrow_mut().insert(self.text_parts.len() - 1);
+        true
     }
 
     /// Set the language used to interpret input.
@@ -75,6 +135,94 @@ impl Session {
         }
     }
     
+    pub(crate) fn current_line_index(&self) -> usize {
+        self.position.get()
+    }
+
+    pub(crate) fn set_current_line_index(&self, index: usize) {
+        self.position.set(index);
+    }
+
+    pub(crate) fn is_blank_line(&self, index: usize) -> bool {
+        match self.text_parts.get(index) {
+            Some(line) => line.trim().is_empty(),
+            None => true
+        }
+    }
+
+    pub(crate) fn start_line(&self) {
+        let index = self.position.get();
+        let mut line_states = self.line_states.borrow_mut();
+        if line_states.len() <= index {
+            line_states.resize(index + 1, LineState::default());
+        }
+        line_states[index] = LineState::default();
+    }
+
+    pub(crate) fn set_line_result(&self, result: Option<Rc<SmartCalcAstType>>, output: Option<Result<String, String>>) {
+        if let Some(state) = self.line_states.borrow_mut().get_mut(self.position.get()) {
+            state.result = result;
+            state.output = output;
+        }
+    }
+
+    pub(crate) fn set_line_assignment(&self, variable: Rc<VariableInfo>, value: Rc<SmartCalcAstType>) {
+        if let Some(state) = self.line_states.borrow_mut().get_mut(self.position.get()) {
+            state.assignment = Some((variable, value));
+        }
+    }
+
+    pub(crate) fn add_used_variable(&self, name: String) {
+        if let Some(state) = self.line_states.borrow_mut().get_mut(self.position.get()) {
+            state.used_variables.insert(name);
+        }
+    }
+
+    pub(crate) fn set_line_reference_used(&self) {
+        if let Some(state) = self.line_states.borrow_mut().get_mut(self.position.get()) {
+            state.use_line_reference = true;
+        }
+    }
+
+    /* Date of the currency rates used in the line, None if it is not known */
+    pub(crate) fn add_rate_date(&self, date: Option<NaiveDateTime>) {
+        if let Some(state) = self.line_states.borrow_mut().get_mut(self.position.get()) {
+            state.rate_dates.push(date);
+        }
+    }
+
+    pub(crate) fn get_line_result(&self, index: usize) -> Option<Rc<SmartCalcAstType>> {
+        self.line_states.borrow().get(index).and_then(|state| state.result.clone())
+    }
+
+    pub(crate) fn get_line_state(&self, index: usize) -> LineState {
+        self.line_states.borrow().get(index).cloned().unwrap_or_default()
+    }
+
+    pub(crate) fn take_updated_lines(&self) -> BTreeSet<usize> {
+        core::mem::take(&mut self.updated_lines.borrow_mut())
+    }
+
+    pub(crate) fn save_initial_variables(&self) {
+        *self.initial_variables.borrow_mut() = self.variables.borrow().values().map(|variable| (variable.clone(), variable.data.borrow().clone())).collect();
+    }
+
+    /* Variables are restored to the state before the line at index executed */
+    pub(crate) fn restore_variables(&self, index: usize) {
+        let mut variables = BTreeMap::new();
+        let assignments = self.line_states.borrow().iter().take(index).filter_map(|state| state.assignment.clone()).collect::<Vec<_>>();
+
+        for (variable, value) in self.initial_variables.borrow().iter().cloned().chain(assignments) {
+            variables.insert(variable.to_string(), (variable, value));
+        }
+
+        for (variable, value) in variables.values() {
+            *variable.data.borrow_mut() = value.clone();
+        }
+
+        *self.variables.borrow_mut() = variables.into_iter().map(|(name, (variable, _))| (name, variable)).collect();
+    }
+    
     pub(crate) fn add_variable(&self, variable_info: Rc<VariableInfo>) {
         self.variables.borrow_mut().insert(variable_info.to_string(), variable_info);
     }
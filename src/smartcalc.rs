@@ -6,25 +6,31 @@
 
 use core::borrow::Borrow;
 use core::ops::Deref;
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::vec::Vec;
 use alloc::rc::Rc;
 use alloc::string::{String, ToString};
 use anyhow::anyhow;
+use chrono::NaiveDateTime;
 use crate::Session;
 use crate::tokinizer::{read_currency, RuleType};
 
-use crate::compiler::Interpreter;
+use crate::compiler::{DataItem, Interpreter};
+use crate::function::FunctionType;
 use crate::logger::{LOGGER, initialize_logger};
 use crate::syntax::SyntaxParser;
 use crate::token::ui_token::UiToken;
 use crate::tokinizer::TokenInfo;
 use crate::tokinizer::Tokinizer;
-use crate::tools::parse_timezone;
-use crate::types::TokenType;
+use crate::tools::{find_zone, parse_timezone};
+use crate::types::{TimeOffset, TokenType};
 use crate::types::SmartCalcAstType;
+use crate::types::NumericMode;
 use crate::formatter::format_result;
 use crate::config::{SmartCalcConfig, DynamicType};
+use crate::error::{ConfigError, CurrencyRateError, ErrorKind, SmartCalcError};
+use crate::currency::{CurrencyRateProvider, CurrencyRatesInfo, CurrencyRateHistory};
+use crate::result::JsonExecuteResult;
 
 pub type ExecutionLine = Option<ExecuteLine>;
 
@@ -33,6 +39,11 @@ pub trait RuleTrait {
     fn call(&self, smartcalc: &SmartCalcConfig, fields: &BTreeMap<String, TokenType>) -> Option<TokenType>;
 }
 
+pub trait FunctionTrait {
+    fn name(&self) -> String;
+    fn call(&self, smartcalc: &SmartCalcConfig, arguments: &[Rc<dyn DataItem>]) -> Result<Rc<dyn DataItem>, ErrorKind>;
+}
+
 #[derive(Debug)]
 #[derive(Default)]
 pub struct ExecuteResult {
@@ -40,32 +51,55 @@ pub struct ExecuteResult {
     pub lines: Vec<ExecutionLine>
 }
 
+#[derive(Debug)]
+#[derive(Default)]
+pub struct ReexecuteResult {
+    pub status: bool,
+    pub changed_lines: Vec<(usize, ExecutionLine)>
+}
+
+/// Result of the calculated line.
+///
+/// `rate_date` is the date of the oldest currency rate used in the line, it is `None` if there is no currency conversion or the date of the rate is not known.
 #[derive(Debug, Clone)]
 pub struct ExecuteLineResult {
     pub output: String,
-    pub ast: Rc<SmartCalcAstType>
+    pub ast: Rc<SmartCalcAstType>,
+    pub rate_date: Option<NaiveDateTime>
 }
 
 impl ExecuteLineResult {
     pub fn new(output: String, ast: Rc<SmartCalcAstType>) -> Self {
-        ExecuteLineResult { output, ast }
+        ExecuteLineResult { output, ast, rate_date: None }
     }
 }
 
 #[derive(Debug)]
 pub struct ExecuteLine {
-    pub result: Result<ExecuteLineResult, String>,
+    pub result: Result<ExecuteLineResult, SmartCalcError>,
     pub raw_tokens: Vec<Rc<TokenType>>,
     pub ui_tokens: Vec<UiToken>,
     pub calculated_tokens: Vec<Rc<TokenInfo>>
 }
 
 impl ExecuteLine {
-    pub fn new(result: Result<ExecuteLineResult, String>, ui_tokens: Vec<UiToken>, raw_tokens: Vec<Rc<TokenType>>, calculated_tokens: Vec<Rc<TokenInfo>>) -> Self {
+    pub fn new(result: Result<ExecuteLineResult, SmartCalcError>, ui_tokens: Vec<UiToken>, raw_tokens: Vec<Rc<TokenType>>, calculated_tokens: Vec<Rc<TokenInfo>>) -> Self {
         ExecuteLine { result, ui_tokens, raw_tokens, calculated_tokens }
     }
 }
 
+/// Sample of a language rule whose result is different from the expected result.
+///
+/// `actual` is the output of the query, or the error message if the query failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleMismatch {
+    pub language: String,
+    pub rule: String,
+    pub query: String,
+    pub expected: String,
+    pub actual: Option<String>
+}
+
 pub struct SmartCalc {
     config: SmartCalcConfig
 }
@@ -124,7 +158,21 @@ impl SmartCalc {
         self.config.thousand_separator = thousand_separator;
     }
     
+    pub fn set_numeric_mode(&mut self, numeric_mode: NumericMode) {
+        self.config.numeric_mode = numeric_mode;
+    }
+    
+    /// Set the timezone with an IANA zone name like "Europe/Istanbul" or an abbreviation like "EST".
     pub fn set_timezone(&mut self, timezone: String) -> Result<(), String> {
+        /* Upper case names like "CET" are abbreviations, zone names have lower case letters */
+        if let Some(zone) = find_zone(&timezone).filter(|_| timezone.chars().any(char::is_lowercase)) {
+            let time_offset = TimeOffset::from_zone(zone);
+            self.config.timezone = time_offset.name;
+            self.config.timezone_offset = time_offset.offset;
+            self.config.timezone_zone = Some(zone);
+            return Ok(());
+        }
+
         let timezone = match self.config.token_parse_regex.get("timezone") {
             Some(regexes) => {
                 let capture = regexes[0].captures(&timezone).unwrap();
@@ -140,6 +188,7 @@ impl SmartCalc {
             Some((timezone, offset)) => {
                 self.config.timezone = timezone.to_uppercase();
                 self.config.timezone_offset = offset;
+                self.config.timezone_zone = None;
                 Ok(())
             },
             None => Err("Timezone information not found".to_string())
@@ -152,9 +201,24 @@ impl SmartCalc {
         }
     }
 
+    /// Load a complete configuration, problems are returned instead of panic.
+    pub fn try_load_from_json(json_data: &str) -> Result<Self, Vec<ConfigError>> {
+        Ok(SmartCalc {
+            config: SmartCalcConfig::try_load_from_json(json_data)?
+        })
+    }
+
+    /// Load the default configuration with the partial JSON documents merged over it.
+    pub fn load_with_overlays<T: Borrow<str>>(overlays: &[T]) -> Result<Self, Vec<ConfigError>> {
+        Ok(SmartCalc {
+            config: SmartCalcConfig::load_with_overlays(overlays)?
+        })
+    }
+
     pub fn update_currency(&mut self, currency: &str, rate: f64) -> bool {
         match read_currency(&self.config, currency) {
             Some(real_currency) => {
+                self.config.currency_rate_date.remove(&real_currency);
                 self.config.currency_rate.insert(real_currency, rate);
                 true
             },
@@ -162,6 +226,22 @@ impl SmartCalc {
         }
     }
 
+    /// Update all currency rates from the provider and return the number of updated currencies.
+    ///
+    /// Rates of the currencies that the provider does not have are kept, they are converted to the provider's base currency.
+    pub fn update_currency_rates(&mut self, provider: &dyn CurrencyRateProvider) -> Result<usize, CurrencyRateError> {
+        self.config.update_currency_rates(provider)
+    }
+
+    pub fn currency_rates_info(&self) -> CurrencyRatesInfo {
+        self.config.currency_rates_info.clone()
+    }
+
+    /// Replace the dated rates used in historical conversions like `100 usd to eur on 1 mar 2021` and return the number of days.
+    pub fn set_currency_rate_history(&mut self, history: &CurrencyRateHistory) -> Result<usize, CurrencyRateError> {
+        self.config.set_currency_rate_history(history)
+    }
+
     pub fn add_rule(&mut self, language: String, rules: Vec<String>, rule: Rc<dyn RuleTrait>) -> bool {
         let mut rule_tokens = Vec::new();
         
@@ -185,6 +265,16 @@ impl SmartCalc {
         true
     }
     
+    pub fn add_function(&mut self, function: Rc<dyn FunctionTrait>) -> bool {
+        let name = function.name().to_lowercase();
+        if self.config.functions.contains_key(&name) {
+            return false;
+        }
+
+        self.config.functions.insert(name, FunctionType::API(function));
+        true
+    }
+    
     pub fn format_result(&self, session: &Session, result: Rc<SmartCalcAstType>) -> String {
         format_result(&self.config, session, result)
     }
@@ -214,24 +304,37 @@ impl SmartCalc {
         let mut syntax = SyntaxParser::new(session, &tokinizer);
         log::debug!(" > parse starting");
 
-        let execution_result = match syntax.parse() {
+        let execution_result = match tokinizer.rule_error.clone() {
+            /* Rule matched but could not be calculated, like unknown currency in conversion */
+            Some(error) => Err(error),
+            None => self.execute_syntax(session, &mut syntax)
+        }.map_err(|error| error.localize(&self.config, &session.get_language()));
+        
+        Some(ExecuteLine::new(execution_result, tokinizer.ui_tokens.get_tokens(), tokinizer.tokens, tokinizer.token_infos.clone()))
+    }
+
+    fn execute_syntax(&self, session: &Session, syntax: &mut SyntaxParser) -> Result<ExecuteLineResult, SmartCalcError> {
+        match syntax.parse() {
             Ok(ast) => {
                 log::debug!(" > parse Ok {:?}", ast);
                 let ast_rc = Rc::new(ast);
 
                 match Interpreter::execute(&self.config, ast_rc, session) {
-                    Ok(ast) => Ok(ExecuteLineResult::new(self.format_result(session, ast.clone()), ast)),
+                    Ok(ast) => {
+                        let rate_dates = session.get_line_state(session.current_line_index()).rate_dates;
+                        let mut result = ExecuteLineResult::new(self.format_result(session, ast.clone()), ast);
+                        result.rate_date = rate_dates.into_iter().collect::<Option<Vec<_>>>().and_then(|dates| dates.into_iter().min());
+                        Ok(result)
+                    },
                     Err(error) => Err(error)
                 }
             },
-            Err((error, _, _)) => {
+            Err(error) => {
                 log::debug!(" > parse Err");
                 log::info!("Syntax parse error, {}", error);
-                Err(error.to_string())
+                Err(error)
             }
-        };
-        
-        Some(ExecuteLine::new(execution_result, tokinizer.ui_tokens.get_tokens(), tokinizer.tokens, tokinizer.token_infos.clone()))
+        }
     }
 
     pub fn execute<Tlan: Borrow<str>, Tdata: Borrow<str>>(&self, language: Tlan, data: Tdata) -> ExecuteResult {
@@ -242,6 +345,50 @@ impl SmartCalc {
         self.execute_session(&session)
     }
 
+    /// Execute the text and return the serializable result, useful for JSON and WASM consumers.
+    pub fn execute_json<Tlan: Borrow<str>, Tdata: Borrow<str>>(&self, language: Tlan, data: Tdata) -> JsonExecuteResult {
+        JsonExecuteResult::from(&self.execute(language, data))
+    }
+
+    /// Execute the `samples` of every language rule in the configuration and return the mismatched ones.
+    ///
+    /// Samples are calculated with the currency rates written in the configuration, updated rates are restored after.
+    pub fn check_samples(&mut self) -> Vec<SampleMismatch> {
+        let configured_rates = self.config.configured_currency_rates();
+        let currency_rate = core::mem::replace(&mut self.config.currency_rate, configured_rates);
+        let currency_rate_date = core::mem::take(&mut self.config.currency_rate_date);
+        let mut mismatches = Vec::new();
+
+        for (language, language_constant) in self.config.json_data.languages.iter() {
+            for (rule, language_rule) in language_constant.rules.iter() {
+                for sample in language_rule.samples.iter() {
+                    let result = self.execute(language.as_str(), sample.query.as_str());
+                    let actual = match result.lines.last() {
+                        Some(Some(line)) => Some(match &line.result {
+                            Ok(result) => result.output.to_string(),
+                            Err(error) => error.to_string()
+                        }),
+                        _ => None
+                    };
+
+                    if actual.as_deref() != Some(sample.result.as_str()) {
+                        mismatches.push(SampleMismatch {
+                            language: language.to_string(),
+                            rule: rule.to_string(),
+                            query: sample.query.to_string(),
+                            expected: sample.result.to_string(),
+                            actual
+                        });
+                    }
+                }
+            }
+        }
+
+        self.config.currency_rate = currency_rate;
+        self.config.currency_rate_date = currency_rate_date;
+        mismatches
+    }
+
     pub fn basic_execute<T: Borrow<str>>(data: T, config: &SmartCalcConfig) -> anyhow::Result<f64> {
         let mut session = Session::new();
 
@@ -284,7 +431,7 @@ impl SmartCalc {
                     Err(error) => Err(anyhow!(error))
                 }
             },
-            Err((error, _, _)) => {
+            Err(error) => {
                 log::debug!(" > parse Err");
                 log::info!("Syntax parse error, {}", error);
                 Err(anyhow!(error))
@@ -292,14 +439,25 @@ impl SmartCalc {
         }
     }
 
+    fn execute_line(&self, session: &Session) -> ExecutionLine {
+        session.start_line();
+        let line_result = self.execute_text(session);
+        let result = line_result.as_ref().and_then(|line| line.result.as_ref().ok()).map(|result| result.ast.clone());
+        let output = line_result.as_ref().map(|line| line.result.as_ref().map(|result| result.output.to_string()).map_err(|error| error.to_string()));
+        session.set_line_result(result, output);
+        line_result
+    }
+
     pub fn execute_session(&self, session: &Session) -> ExecuteResult {
         let mut results = ExecuteResult::default();
+        session.set_current_line_index(0);
+        session.save_initial_variables();
+        session.take_updated_lines();
 
         if session.has_value() {
             results.status = true;
             loop {
-                let line_result = self.execute_text(session);
-                results.lines.push(line_result);
+                results.lines.push(self.execute_line(session));
                 if session.next_line().is_none() {
                     break;
                 }
@@ -308,6 +466,62 @@ impl SmartCalc {
 
         results
     }
+
+    /// Calculate lines changed with `Session::update_line` or added with `Session::add_line` and the lines depending on them.
+    ///
+    /// Session must be executed with `execute_session` before, a new session could be calculated line by line with `add_line`. Only the lines whose results changed are returned.
+    pub fn reexecute(&self, session: &Session) -> ReexecuteResult {
+        let mut results = ReexecuteResult { status: true, ..Default::default() };
+        let updated_lines = session.take_updated_lines();
+
+        let first_line = match updated_lines.iter().next() {
+            Some(index) => *index,
+            None => return results
+        };
+
+        let mut changed_variables = BTreeSet::new();
+        let mut definition_changed = false;
+        let mut result_changed = false;
+
+        for index in first_line..session.line_count() {
+            let old_state = session.get_line_state(index);
+            let require_execution = updated_lines.contains(&index) ||
+                definition_changed ||
+                (result_changed && old_state.use_line_reference) ||
+                old_state.used_variables.iter().any(|name| changed_variables.contains(name));
+
+            if !require_execution {
+                continue;
+            }
+
+            session.restore_variables(index);
+            session.set_current_line_index(index);
+            let line_result = self.execute_line(session);
+            let new_state = session.get_line_state(index);
+
+            let old_variable = old_state.assignment.as_ref().map(|(variable, _)| variable.to_string());
+            let new_variable = new_state.assignment.as_ref().map(|(variable, _)| variable.to_string());
+
+            /* Variable defined or removed, following lines could resolve names differently */
+            if old_variable != new_variable {
+                definition_changed = true;
+            }
+
+            if old_state.value() != new_state.value() {
+                result_changed = true;
+                changed_variables.extend(old_variable);
+                changed_variables.extend(new_variable);
+            }
+
+            if old_state.output != new_state.output || updated_lines.contains(&index) {
+                results.changed_lines.push((index, line_result));
+            }
+        }
+
+        session.restore_variables(session.line_count());
+        session.set_current_line_index(session.line_count().saturating_sub(1));
+        results
+    }
 }
 
 #[cfg(test)]
@@ -315,7 +529,9 @@ mod test {
     use core::ops::Deref;
     use alloc::{collections::BTreeMap, string::{String, ToString}, vec, rc::Rc};
 
-    use crate::{SmartCalc, types::{TokenType, NumberType}, RuleTrait, SmartCalcConfig};
+    use crate::{SmartCalc, types::{TokenType, NumberType}, RuleTrait, FunctionTrait, DataItem, SmartCalcConfig, ErrorKind};
+    use crate::compiler::OperationType;
+    use crate::compiler::number::NumberItem;
 
     #[derive(Default)]
     pub struct Test1;
@@ -327,7 +543,7 @@ mod test {
             match fields.get("surname") {
                 Some(TokenType::Text(surname)) => {
                     assert_eq!(surname, &"baris".to_string());
-                    Some(TokenType::Number(2022.0, NumberType::Decimal))
+                    Some(TokenType::Number(2022.0.into(), NumberType::Decimal))
                 },
                 _ => None
             }
@@ -383,10 +599,10 @@ mod test {
         let test1 = Rc::new(Test1::default());
         calculater.add_rule("en".to_string(), vec!["erhan {TEXT:surname}".to_string(), "{TEXT:surname} erhan".to_string()], test1.clone());
         let result = calculater.execute("en".to_string(), "erhan baris");
-        check_basic_rule_output!(result, TokenType::Number(2022.0, NumberType::Decimal));
+        check_basic_rule_output!(result, TokenType::Number(2022.0.into(), NumberType::Decimal));
 
         let result = calculater.execute("en".to_string(), "baris erhan");
-        check_basic_rule_output!(result, TokenType::Number(2022.0, NumberType::Decimal));
+        check_basic_rule_output!(result, TokenType::Number(2022.0.into(), NumberType::Decimal));
 
         Ok(())
     }
@@ -397,10 +613,10 @@ mod test {
         let test1 = Rc::new(Test1::default());
         calculater.add_rule("en".to_string(), vec!["erhan {TEXT:surname:baris}".to_string(), "{TEXT:surname:baris} erhan".to_string()], test1.clone());
         let result = calculater.execute("en".to_string(), "erhan baris");
-        check_basic_rule_output!(result, TokenType::Number(2022.0, NumberType::Decimal));
+        check_basic_rule_output!(result, TokenType::Number(2022.0.into(), NumberType::Decimal));
 
         let result = calculater.execute("en".to_string(), "baris erhan");
-        check_basic_rule_output!(result, TokenType::Number(2022.0, NumberType::Decimal));
+        check_basic_rule_output!(result, TokenType::Number(2022.0.into(), NumberType::Decimal));
 
         Ok(())
     }
@@ -499,7 +715,7 @@ mod test {
         assert_eq!(error.to_string(), "Calculation empty".to_string());
         Ok(())
     }
-    
+    /* Bitcoin and ether are in the assets of the configuration, custom rules use the other coins */
     #[derive(Default)]
     pub struct Coin;
 
@@ -510,7 +726,7 @@ mod test {
 
         fn call(&self, smartcalc: &SmartCalcConfig, fields: &BTreeMap<String, TokenType>) -> Option<TokenType> {
             let count = match fields.get("count") {
-                Some(TokenType::Number(number, NumberType::Decimal)) => *number,
+                Some(TokenType::Number(number, NumberType::Decimal)) => number.to_f64(),
                 _ => return None
             };
             let coin = match fields.get("coin") {
@@ -519,12 +735,12 @@ mod test {
             };
             
             let price = match &coin[..] {
-                "btc" => 1000.0 * count,
-                "eth" => 800.0 * count,
+                "nmc" => 1000.0 * count,
+                "ppc" => 800.0 * count,
                 _ => return None
             };
             
-            return Some(TokenType::Money(price, smartcalc.get_currency("usd".to_string()).unwrap()));
+            return Some(TokenType::Money(price.into(), smartcalc.get_currency("usd".to_string()).unwrap()));
         }
     }
     
@@ -533,8 +749,8 @@ mod test {
         let mut calculater = SmartCalc::default();
         let test1 = Rc::new(Coin::default());
         calculater.add_rule("en".to_string(), vec!["{NUMBER:count} {TEXT:coin}".to_string()], test1.clone());
-        let result = calculater.execute("en".to_string(), "10 btc to usd");
-        check_basic_rule_output!(result, TokenType::Money(10000.0, calculater.config.get_currency("usd".to_string()).unwrap()));
+        let result = calculater.execute("en".to_string(), "10 nmc to usd");
+        check_basic_rule_output!(result, TokenType::Money(10000.0.into(), calculater.config.get_currency("usd".to_string()).unwrap()));
         Ok(())
     }
     
@@ -543,8 +759,8 @@ mod test {
         let mut calculater = SmartCalc::default();
         let test1 = Rc::new(Coin::default());
         calculater.add_rule("en".to_string(), vec!["{NUMBER:count} {TEXT:coin}".to_string()], test1.clone());
-        let result = calculater.execute("en".to_string(), "10 eth to usd");
-        check_basic_rule_output!(result, TokenType::Money(8000.0, calculater.config.get_currency("usd".to_string()).unwrap()));
+        let result = calculater.execute("en".to_string(), "10 ppc to usd");
+        check_basic_rule_output!(result, TokenType::Money(8000.0.into(), calculater.config.get_currency("usd".to_string()).unwrap()));
         Ok(())
     }
     
@@ -553,8 +769,37 @@ mod test {
         let mut calculater = SmartCalc::default();
         let test1 = Rc::new(Coin::default());
         calculater.add_rule("en".to_string(), vec!["{NUMBER:count} {TEXT:coin}".to_string()], test1.clone());
-        let result = calculater.execute("en".to_string(), "10 eth to dkk");
-        check_basic_rule_output!(result, TokenType::Money(49644.9970792, calculater.config.get_currency("dkk".to_string()).unwrap()));
+        let result = calculater.execute("en".to_string(), "10 ppc to dkk");
+        check_basic_rule_output!(result, TokenType::Money(49644.9970792.into(), calculater.config.get_currency("dkk".to_string()).unwrap()));
+        Ok(())
+    }
+    
+    pub struct Vat;
+
+    impl FunctionTrait for Vat {
+        fn name(&self) -> String {
+            "vat".to_string()
+        }
+
+        fn call(&self, smartcalc: &SmartCalcConfig, arguments: &[Rc<dyn DataItem>]) -> Result<Rc<dyn DataItem>, ErrorKind> {
+            match arguments.first() {
+                Some(item) => item.calculate(smartcalc, true, &NumberItem(1.2.into(), NumberType::Decimal), OperationType::Mul).ok_or_else(|| ErrorKind::Custom("vat calculation failed".to_string())),
+                None => Err(ErrorKind::ArgumentCount { function: "vat".to_string(), min: 1, max: Some(1) })
+            }
+        }
+    }
+    
+    #[test]
+    fn add_function_1() ->  Result<(), ()> {
+        let mut calculater = SmartCalc::default();
+        assert!(calculater.add_function(Rc::new(Vat)));
+        assert!(!calculater.add_function(Rc::new(Vat)));
+
+        let result = calculater.execute("en".to_string(), "vat($100)");
+        assert_eq!(result.lines[0].as_ref().unwrap().result.as_ref().unwrap().output, "$120,00".to_string());
+
+        let result = calculater.execute("en".to_string(), "VAT(10) + 1");
+        assert_eq!(result.lines[0].as_ref().unwrap().result.as_ref().unwrap().output, "13".to_string());
         Ok(())
     }
 }
@@ -4,34 +4,86 @@
  * Licensed under the GNU General Public License v2.0.
  */
 
+use crate::compiler::number::NumberItem;
 use crate::types::*;
 use crate::syntax::{SyntaxParser, SyntaxParserTrait};
 use crate::syntax::unary::UnaryParser;
+use crate::syntax::primative::PrimativeParser;
+use crate::error::ErrorKind;
 use alloc::rc::Rc;
+use core::ops::Deref;
 
-pub struct ModuloParser;
+pub struct PowerParser;
 pub struct MultiplyDivideParser;
 pub struct AddSubtractParser;
 
-impl SyntaxParserTrait for ModuloParser {
+impl SyntaxParserTrait for PowerParser {
     fn parse(parser: &mut SyntaxParser) -> AstResult {
-        parse_binary::<MultiplyDivideParser>(parser, &['%'])
+        let index_backup = parser.get_index();
+
+        /* Sign is part of the number literal, but "-2^2" must be calculated as "-(2^2)" */
+        let negative_base = match parser.peek_token() {
+            Ok(token) => match token.deref() {
+                TokenType::Number(number, number_type) if number.to_f64().is_sign_negative() => Some((*number, *number_type)),
+                _ => None
+            },
+            Err(_) => None
+        };
+
+        if let Some((number, number_type)) = negative_base {
+            parser.consume_token();
+            if parser.check_operator('^') {
+                let ast = parse_power(parser, SmartCalcAstType::Item(Rc::new(NumberItem(-number, number_type))), index_backup)?;
+                return Ok(SmartCalcAstType::PrefixUnary('-', Rc::new(ast)));
+            }
+            parser.set_index(index_backup);
+        }
+
+        let base = PrimativeParser::parse(parser)?;
+        if let SmartCalcAstType::None = base {
+            return Ok(base)
+        }
+
+        parse_power(parser, base, index_backup)
     }
 }
 
 impl SyntaxParserTrait for MultiplyDivideParser {
     fn parse(parser: &mut SyntaxParser) -> AstResult {
-        parse_binary::<UnaryParser>(parser, &['*', '/'])
+        parse_binary::<UnaryParser>(parser, &['*', '/', '%', '\\'])
     }
 }
 
 impl SyntaxParserTrait for AddSubtractParser {
     fn parse(parser: &mut SyntaxParser) -> AstResult {
-        parse_binary::<ModuloParser>(parser, &['+', '-'])
+        parse_binary::<MultiplyDivideParser>(parser, &['+', '-'])
     }
 }
 
+fn parse_power(parser: &mut SyntaxParser, base: SmartCalcAstType, start_index: usize) -> AstResult {
+    let index_backup = parser.get_index();
+
+    if let Some(operator) = parser.match_operator(&['^']) {
+        /* Exponent parsed with unary parser, so power operator is right associative */
+        let exponent = UnaryParser::parse(parser)?;
+        if let SmartCalcAstType::None = exponent {
+            parser.set_index(index_backup);
+            return Err(parser.error(ErrorKind::InvalidExpression));
+        }
+
+        return Ok(SmartCalcAstType::Binary {
+            left: Rc::new(base),
+            operator,
+            right: Rc::new(exponent),
+            span: parser.span_between(start_index, parser.get_index())
+        });
+    }
+
+    Ok(base)
+}
+
 pub fn parse_binary<T: SyntaxParserTrait>(parser: &mut SyntaxParser, operators: &[char]) -> AstResult {
+    let start_index = parser.get_index();
     let mut left_expr = T::parse(parser)?;
     
     if let SmartCalcAstType::None = left_expr {
@@ -50,7 +102,8 @@ pub fn parse_binary<T: SyntaxParserTrait>(parser: &mut SyntaxParser, operators:
                         left_expr = SmartCalcAstType::Binary {
                             left: Rc::new(left_expr),
                             operator,
-                            right: Rc::new(right_expr.unwrap())
+                            right: Rc::new(right_expr.unwrap()),
+                            span: parser.span_between(start_index, parser.get_index())
                         };
                         break;
                     },
@@ -19,6 +19,7 @@ use crate::tokinizer::Tokinizer;
 use crate::types::*;
 use alloc::rc::Rc;
 use crate::session::Session;
+use crate::error::{ErrorKind, SmartCalcError, Span};
 use crate::syntax::assignment::AssignmentParser;
 use crate::syntax::binary::AddSubtractParser;
 use core::ops::Deref;
@@ -46,7 +47,20 @@ impl<'a> SyntaxParser<'a> {
 
     pub fn parse(&mut self) -> AstResult {
         let ast = map_parser(self, &[AssignmentParser::parse, AddSubtractParser::parse])?;
-        Ok(ast)
+        let (unit, span) = match &self.tokinizer.conversion {
+            Some((unit, span)) => (unit.clone(), *span),
+            None => return Ok(ast)
+        };
+
+        /* Assigned value is the converted one, "speed = 100 km / 2 hours to m/s" */
+        Ok(match ast {
+            SmartCalcAstType::Assignment { variable, expression } => SmartCalcAstType::Assignment {
+                variable,
+                expression: Rc::new(SmartCalcAstType::Conversion { expression, unit, span })
+            },
+            SmartCalcAstType::None => SmartCalcAstType::None,
+            expression => SmartCalcAstType::Conversion { expression: Rc::new(expression), unit, span }
+        })
     }
 
     pub fn set_index(&self, index: usize) {
@@ -65,6 +79,22 @@ impl<'a> SyntaxParser<'a> {
         }
     }
 
+    /* End of the line is used for missing tokens */
+    pub fn token_span(&self, index: usize) -> Span {
+        match self.tokinizer.token_spans.get(index) {
+            Some(span) => *span,
+            None => Span::new(&self.tokinizer.data, self.tokinizer.data.len(), self.tokinizer.data.len())
+        }
+    }
+
+    pub fn span_between(&self, start_index: usize, end_index: usize) -> Span {
+        self.token_span(start_index).merge(&self.token_span(end_index.max(start_index + 1) - 1))
+    }
+
+    pub fn error(&self, kind: ErrorKind) -> SmartCalcError {
+        SmartCalcError::new(kind, self.token_span(self.get_index()))
+    }
+
     pub fn consume_token(&self) -> Option<Rc<TokenType>> {
         self.index.set(self.index.get() + 1);
         self.tokinizer.tokens.get(self.index.get()).cloned()
@@ -6,16 +6,19 @@
 
 
 use alloc::rc::Rc;
+use alloc::vec::Vec;
 
 use crate::compiler::date::DateItem;
 use crate::compiler::date_time::DateTimeItem;
 use crate::compiler::duration::DurationItem;
 use crate::compiler::money::MoneyItem;
 use crate::compiler::dynamic_type::DynamicTypeItem;
+use crate::compiler::compound_type::CompoundTypeItem;
 use crate::compiler::number::NumberItem;
 use crate::compiler::percent::PercentItem;
 use crate::compiler::time::TimeItem;
 use crate::types::*;
+use crate::error::{ErrorKind, SmartCalcError};
 use crate::syntax::util::*;
 use crate::syntax::{SyntaxParser, SyntaxParserTrait};
 use crate::syntax::binary::AddSubtractParser;
@@ -29,7 +32,7 @@ impl PrimativeParser {
         let token = parser.peek_token();
 
         if token.is_err() {
-            return Err(("No more token", 0, 0));
+            return Err(parser.error(ErrorKind::UnexpectedEnd));
         }
 
         let result = match token.unwrap().deref() {
@@ -39,6 +42,7 @@ impl PrimativeParser {
                 return Ok(SmartCalcAstType::None);
             },
             TokenType::DynamicType(number, dynamic_type)     => Ok(SmartCalcAstType::Item(Rc::new(DynamicTypeItem(*number, dynamic_type.clone())))),
+            TokenType::CompoundType(number, unit)            => Ok(SmartCalcAstType::Item(Rc::new(CompoundTypeItem(*number, unit.clone())))),
             TokenType::Money(price, currency)     => Ok(SmartCalcAstType::Item(Rc::new(MoneyItem(*price, currency.clone())))),
             TokenType::Number(double, number_type)     => Ok(SmartCalcAstType::Item(Rc::new(NumberItem(*double, *number_type)))),
             TokenType::Field(field_type)  => Ok(SmartCalcAstType::Field(field_type.clone())),
@@ -49,8 +53,9 @@ impl PrimativeParser {
             TokenType::Duration(duration)         => Ok(SmartCalcAstType::Item(Rc::new(DurationItem(*duration)))),
             TokenType::Variable(variable) => Ok(SmartCalcAstType::Variable(variable.clone())),
             _ => {
+                let error = parser.error(ErrorKind::UnexpectedToken);
                 parser.consume_token();
-                return Err(("No more token", 0, 0));
+                return Err(error);
             }
         };
 
@@ -63,7 +68,55 @@ impl PrimativeParser {
                 parser.consume_token();
                 Ok(ast)
             },
-            Err((message, line, column)) => Err((message, line, column))
+            Err(error) => Err(error)
+        }
+    }
+
+    pub fn parse_function_call(parser: &mut SyntaxParser) -> AstResult {
+        let index_backup = parser.get_index();
+        let name = match parser.peek_token() {
+            Ok(token) => match token.deref() {
+                TokenType::Text(text) if parser.tokinizer.config.functions.contains_key(&text.to_lowercase()) => text.to_lowercase(),
+                _ => return Ok(SmartCalcAstType::None)
+            },
+            Err(_) => return Ok(SmartCalcAstType::None)
+        };
+
+        parser.consume_token();
+        if parser.match_operator(&['(']).is_none() {
+            parser.set_index(index_backup);
+            return Ok(SmartCalcAstType::None);
+        }
+
+        let mut arguments = Vec::new();
+        if parser.match_operator(&[')']).is_some() {
+            return Ok(SmartCalcAstType::Call { name, arguments, span: parser.span_between(index_backup, parser.get_index()) });
+        }
+
+        loop {
+            let ast = AddSubtractParser::parse(parser);
+            if is_ast_empty(&ast) {
+                parser.set_index(index_backup);
+                return err_or_message(parser, &ast, ErrorKind::InvalidFunctionArgument);
+            }
+
+            arguments.push(Rc::new(ast.unwrap()));
+
+            /* Arguments are separated with ";", "," could be the decimal or thousands separator like "max(1,2)" */
+            match parser.match_operator(&[';', ',', ')']) {
+                Some(';') => (),
+                Some(',') => {
+                    let span = parser.span_between(index_backup, parser.get_index());
+                    parser.set_index(index_backup);
+                    return Err(SmartCalcError::new(ErrorKind::InvalidFunctionArgument, span));
+                },
+                Some(_) => return Ok(SmartCalcAstType::Call { name, arguments, span: parser.span_between(index_backup, parser.get_index()) }),
+                None => {
+                    let span = parser.span_between(index_backup, parser.get_index());
+                    parser.set_index(index_backup);
+                    return Err(SmartCalcError::new(ErrorKind::UnclosedParenthesis, span));
+                }
+            };
         }
     }
 
@@ -74,12 +127,13 @@ impl PrimativeParser {
             let ast = AddSubtractParser::parse(parser);
             if is_ast_empty(&ast) {
                 parser.set_index(index_backup);
-                return err_or_message(&ast, "Invalid expression");
+                return err_or_message(parser, &ast, ErrorKind::InvalidExpression);
             }
 
             if parser.match_operator(&[')']).is_none() {
+                let span = parser.span_between(index_backup, parser.get_index());
                 parser.set_index(index_backup);
-                return Err(("Parentheses not closed", 0, 0));
+                return Err(SmartCalcError::new(ErrorKind::UnclosedParenthesis, span));
             }
 
             return Ok(ast.unwrap());
@@ -92,6 +146,6 @@ impl PrimativeParser {
 
 impl SyntaxParserTrait for PrimativeParser {
     fn parse(parser: &mut SyntaxParser) -> AstResult {
-        map_parser(parser, &[Self::parse_parenthesis, Self::parse_basic_primatives])
+        map_parser(parser, &[Self::parse_function_call, Self::parse_parenthesis, Self::parse_basic_primatives])
     }
 }
\ No newline at end of file
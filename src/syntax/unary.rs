@@ -4,25 +4,18 @@
  * Licensed under the GNU General Public License v2.0.
  */
 
-use crate::compiler::money::MoneyItem;
-use crate::compiler::number::NumberItem;
-use crate::compiler::percent::PercentItem;
 use crate::types::*;
 use crate::syntax::{SyntaxParser, SyntaxParserTrait};
 use crate::syntax::util::map_parser;
-use crate::syntax::primative::PrimativeParser;
-use core::ops::Deref;
+use crate::syntax::binary::PowerParser;
+use crate::error::ErrorKind;
 use alloc::rc::Rc;
 
 pub struct UnaryParser;
 
 impl SyntaxParserTrait for UnaryParser {
     fn parse(parser: &mut SyntaxParser) -> AstResult {
-        let ast = map_parser(parser, &[Self::parse_prefix_unary, PrimativeParser::parse])?;
-        
-        let index_backup = parser.get_index();
-        parser.set_index(index_backup);
-        Ok(ast)
+        map_parser(parser, &[Self::parse_prefix_unary, PowerParser::parse])
     }
 }
 
@@ -31,29 +24,20 @@ impl UnaryParser {
         let index_backup = parser.get_index();
 
         if let Some(operator) = parser.match_operator(&['-', '+']) {
-            match parser.peek_token() {
-                Ok(token) => {
-                    let opt = match operator {
-                        '+' => 1_f64,
-                        '-' => -1_f64,
-                        _   => 1_f64
-                    };
-
-                    match token.deref() {
-                        TokenType::Number(double, number_type)         => return Ok(SmartCalcAstType::Item(Rc::new(NumberItem(double * opt, *number_type)))),
-                        TokenType::Variable(variable)     => return Ok(SmartCalcAstType::PrefixUnary(operator, Rc::new(SmartCalcAstType::Variable(variable.clone())))),
-                        TokenType::Percent(percent)       => return Ok(SmartCalcAstType::PrefixUnary(operator, Rc::new(SmartCalcAstType::Item(Rc::new(PercentItem(*percent)))))),
-                        TokenType::Money(money, currency) => return Ok(SmartCalcAstType::PrefixUnary(operator, Rc::new(SmartCalcAstType::PrefixUnary(operator, Rc::new(SmartCalcAstType::Item(Rc::new(MoneyItem(*money, currency.clone())))))))),
-                        _ => {
-                            parser.set_index(index_backup);
-                            return Err(("Unary works with number", 0, 0));
-                        }
-                    };
+            /* Power has higher precedence than unary operators, "-x^2" equals "-(x^2)" */
+            let ast = PowerParser::parse(parser);
+            return match ast {
+                Ok(SmartCalcAstType::None) | Err(_) => {
+                    parser.set_index(index_backup);
+                    Err(parser.error(ErrorKind::InvalidUnaryOperand))
                 },
-                 _=> return Ok(SmartCalcAstType::None)
-            }
+                Ok(ast) => match operator {
+                    '-' => Ok(SmartCalcAstType::PrefixUnary(operator, Rc::new(ast))),
+                    _   => Ok(ast)
+                }
+            };
         }
 
         Ok(SmartCalcAstType::None)
     }
-}
\ No newline at end of file
+}
@@ -7,6 +7,7 @@
 use crate::types::*;
 use crate::syntax::{SyntaxParser};
 use crate::syntax::ParseType;
+use crate::error::ErrorKind;
 
 pub fn map_parser(parser: &mut SyntaxParser, parser_funcs: &[ParseType]) -> AstResult {
     for parser_func in parser_funcs {
@@ -28,10 +29,10 @@ pub fn is_ast_empty(ast: &AstResult) -> bool {
     }
 }
 
-pub fn err_or_message(ast: &AstResult, message: &'static str) -> AstResult {
+pub fn err_or_message(parser: &SyntaxParser, ast: &AstResult, kind: ErrorKind) -> AstResult {
     match &ast {
-        Ok(SmartCalcAstType::None) => Err((message, 0, 0,)),
+        Ok(SmartCalcAstType::None) => Err(parser.error(kind)),
         Ok(_) => Ok(SmartCalcAstType::None),
-        Err((m, l, c)) => Err((m, *l, *c))
+        Err(error) => Err(error.clone())
     }
 }
@@ -0,0 +1,225 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::vec;
+use crate::{SmartCalc, SmartCalcConfig, SampleMismatch, ConfigError, ConfigErrorKind, CurrencyRates};
+
+fn outputs(calculator: &SmartCalc, text: &str) -> Vec<String> {
+    calculator.execute("en", text).lines.iter().map(|line| match line {
+        Some(line) => match &line.result {
+            Ok(result) => result.output.to_string(),
+            Err(error) => error.to_string()
+        },
+        None => String::new()
+    }).collect()
+}
+
+fn overlay_errors(overlays: &[&str]) -> Vec<ConfigError> {
+    match SmartCalc::load_with_overlays(overlays) {
+        Ok(_) => Vec::new(),
+        Err(errors) => errors
+    }
+}
+
+#[test]
+fn config_overlay_merge() {
+    let calculator = SmartCalc::load_with_overlays(&[
+        r#"{ "currency_alias": { "bucks": "usd" } }"#,
+        r#"{
+            "types": [{
+                "name": "metric-length",
+                "items": [{
+                    "index": 8,
+                    "format": "{value} Megameter",
+                    "parse": ["{NUMBER:value} {TEXT:type:megameter}"],
+                    "upgrade_code": "{value}",
+                    "downgrade_code": "{value} * 1000",
+                    "names": ["megameter"]
+                }]
+            }],
+            "languages": { "en": { "named_constants": { "answer": { "value": 42 } } } }
+        }"#
+    ]).unwrap();
+
+    assert_eq!(outputs(&calculator, "10 bucks\n2 megameter to km\n1 km to m\nanswer + 1\n$1 to eur"), vec![
+        "$10,00".to_string(),
+        "2.000 Kilometer".to_string(),
+        "1.000 Meter".to_string(),
+        "43".to_string(),
+        "0,83 €".to_string()
+    ]);
+}
+
+#[test]
+fn config_overlay_remove() {
+    let calculator = SmartCalc::load_with_overlays(&[r#"{ "languages": { "en": { "named_constants": { "pi": null } } } }"#]).unwrap();
+    let results = outputs(&calculator, "tau / 2\npi * 2");
+    assert_eq!(results[0], "3,14");
+    assert_ne!(results[1], "6,28");
+}
+
+#[test]
+fn config_overlay_errors() {
+    let errors = overlay_errors(&[r#"{ "currency_alias": { "bucks": "usd" }"#]);
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(errors[0].kind, ConfigErrorKind::Syntax(_)));
+
+    assert_eq!(overlay_errors(&[r#"{ "currency_rates": [1, 2], "timezones": { "UTC": "zero" } }"#]), vec![
+        ConfigError::new("currency_rates", ConfigErrorKind::TypeMismatch { expected: "object", found: "array" }),
+        ConfigError::new("timezones.UTC", ConfigErrorKind::TypeMismatch { expected: "number", found: "string" })
+    ]);
+
+    assert_eq!(overlay_errors(&[r#"{ "currency_alias": { "bucks": "xyz" }, "currency_rates": { "abc": 1.0 } }"#]), vec![
+        ConfigError::new("currency_alias.bucks", ConfigErrorKind::UnknownCurrency("xyz".to_string())),
+        ConfigError::new("currency_rates.abc", ConfigErrorKind::UnknownCurrency("abc".to_string()))
+    ]);
+
+    assert_eq!(overlay_errors(&[r#"{ "types": [{ "items": [] }] }"#]), vec![
+        ConfigError::new("types[0]", ConfigErrorKind::InvalidValue("'name' fields are required".to_string()))
+    ]);
+
+    let path = format!("types[{}].items[0].upgrade_code", SmartCalcConfig::default().json_data.types.len());
+    assert_eq!(overlay_errors(&[r#"{ "types": [{ "name": "broken", "items": [{ "index": 1, "format": "{value} B", "parse": [], "names": ["broken"], "upgrade_code": "{value} *", "downgrade_code": "{value}" }] }] }"#]), vec![
+        ConfigError::new(path, ConfigErrorKind::InvalidValue("'{value} *' is not a valid formula".to_string()))
+    ]);
+
+    let path = format!("types[{}].unit", SmartCalcConfig::default().json_data.types.len());
+    assert_eq!(overlay_errors(&[r#"{ "types": [{ "name": "flow", "unit": "m³/fortnight", "items": [{ "index": 1, "format": "{value} Flow", "parse": [], "names": ["flow"], "scale": 1 }] }] }"#]), vec![
+        ConfigError::new(path, ConfigErrorKind::UnknownDynamicType("fortnight".to_string()))
+    ]);
+
+    let errors = overlay_errors(&[r#"{ "languages": { "de": { "alias": {} } } }"#]);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path, "languages.de");
+
+    let errors = overlay_errors(&[r#"{ "languages": { "en": { "named_constants": { "mile": { "value": 1, "unit": "parsec" } } } } }"#]);
+    assert_eq!(errors, vec![ConfigError::new("languages.en.named_constants.mile.unit", ConfigErrorKind::UnknownDynamicType("parsec".to_string()))]);
+}
+
+#[test]
+fn config_overlay_assets() {
+    let calculator = SmartCalc::load_with_overlays(&[r#"{
+        "assets": { "XPT": {
+            "code": "XPT", "symbol": "XPT", "thousandsSeparator": ",", "decimalSeparator": ".",
+            "symbolOnLeft": false, "spaceBetweenAmountAndSymbol": true, "decimalDigits": 4,
+            "rate": 0.001, "names": ["platinum"], "units": { "oz": 1.0 }
+        } },
+        "currency_alias": { "sats": "btc" }
+    }"#]).unwrap();
+
+    assert_eq!(outputs(&calculator, "2 oz platinum to usd
+1 sats"), vec![
+        "$2.000,00".to_string(),
+        "₿1,00000000".to_string()
+    ]);
+
+    assert_eq!(overlay_errors(&[r#"{ "assets": { "USD": { "code": "USD", "symbol": "$", "thousandsSeparator": ",", "decimalSeparator": ".", "symbolOnLeft": true, "spaceBetweenAmountAndSymbol": false, "decimalDigits": 2, "rate": 0, "units": { "oz": -1 } } } }"#]), vec![
+        ConfigError::new("assets.USD", ConfigErrorKind::DuplicateName("USD".to_string())),
+        ConfigError::new("assets.USD.rate", ConfigErrorKind::InvalidValue("0 is not a positive rate".to_string())),
+        ConfigError::new("assets.USD.units.oz", ConfigErrorKind::InvalidValue("-1 is not a positive amount".to_string()))
+    ]);
+
+    assert_eq!(overlay_errors(&[r#"{ "assets": { "XPT": { "code": "XPT" } } }"#])[0].path, "assets.XPT");
+}
+
+#[test]
+fn config_overlay_scaled_types() {
+    let calculator = SmartCalc::load_with_overlays(&[r#"{ "types": [{
+        "name": "reaumur",
+        "items": [
+            { "index": 1, "format": "{value} °Ré", "parse": ["{NUMBER:value} {TEXT:type:réaumur}"], "names": ["réaumur"], "scale": 1.25, "offset": 273.15 },
+            { "index": 2, "format": "{value} K", "parse": ["{NUMBER:value} {TEXT:type:kelvins}"], "names": ["kelvins"], "scale": 1 }
+        ]
+    }] }"#]).unwrap();
+    assert_eq!(outputs(&calculator, "80 réaumur to kelvins\nchange = 80 réaumur - 0 réaumur\nchange to kelvins"), vec!["373,15 K".to_string(), "Δ80 °Ré".to_string(), "Δ100 K".to_string()]);
+
+    let path = format!("types[{}]", SmartCalcConfig::default().json_data.types.len());
+    assert_eq!(overlay_errors(&[r#"{ "types": [{
+        "name": "ratio",
+        "items": [
+            { "index": 1, "format": "{value} R", "parse": [], "names": ["ratio"], "scale": 0 },
+            { "index": 2, "format": "{value} IR", "parse": [], "names": ["inverse-ratio"], "scale": 2, "offset": 1, "inverse": true },
+            { "index": 3, "format": "{value} CR", "parse": [], "names": ["code-ratio"], "upgrade_code": "{value}", "downgrade_code": "{value}" }
+        ]
+    }] }"#]), vec![
+        ConfigError::new(format!("{}.items[0].scale", path), ConfigErrorKind::InvalidValue("0 is not a valid scale".to_string())),
+        ConfigError::new(format!("{}.items[1].offset", path), ConfigErrorKind::InvalidValue("inverse units can not have offset".to_string())),
+        ConfigError::new(path, ConfigErrorKind::InvalidValue("'scale' is required for all items when one of them has it".to_string()))
+    ]);
+}
+
+#[test]
+fn config_overlay_locations() {
+    let calculator = SmartCalc::load_with_overlays(&[r#"{ "languages": { "en": { "locations": { "gotham": "America/New_York" } } } }"#]).unwrap();
+    assert_eq!(outputs(&calculator, "1/7/2021 at 10:00 to gotham"), vec!["1 Jul 2021 06:00:00 EDT".to_string()]);
+
+    assert_eq!(overlay_errors(&[r#"{ "languages": { "en": { "locations": { "atlantis": "Ocean/Atlantis" } } } }"#]), vec![
+        ConfigError::new("languages.en.locations.atlantis", ConfigErrorKind::InvalidValue("Ocean/Atlantis is not an IANA timezone".to_string()))
+    ]);
+}
+
+#[test]
+fn config_try_load() {
+    assert!(SmartCalc::try_load_from_json(crate::constants::JSON_DATA).is_ok());
+
+    let errors = SmartCalc::try_load_from_json("{}").err().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path, "");
+}
+
+#[test]
+fn config_validate() {
+    assert!(SmartCalcConfig::default().validate().is_empty());
+
+    let errors = overlay_errors(&[r#"{
+        "parse": { "test": ["(unclosed"] },
+        "languages": { "en": {
+            "long_months": { "january": null },
+            "short_months": { "jan": 13 },
+            "rules": { "unknown_rule": { "rules": ["{NUMBER:value} test"], "samples": [] } }
+        } },
+        "types": [{ "name": "custom", "items": [{ "index": 1, "format": "{value} Custom", "parse": [], "names": ["custom", "m"] }] }]
+    }"#]);
+
+    /* New type is added after the default types */
+    let item_path = format!("types[{}].items[0]", SmartCalcConfig::default().json_data.types.len());
+    let names_path = format!("{}.names", item_path);
+
+    let errors = errors.iter().map(|error| (error.path.as_str(), &error.kind)).collect::<Vec<_>>();
+    assert!(matches!(errors[0], ("parse.test[0]", ConfigErrorKind::InvalidRegex(_))));
+    assert_eq!(&errors[1..], &[
+        ("languages.en.long_months", &ConfigErrorKind::MissingMonth(1)),
+        ("languages.en.short_months.jan", &ConfigErrorKind::InvalidValue("13 is not a month number".to_string())),
+        ("languages.en.short_months", &ConfigErrorKind::MissingMonth(1)),
+        ("languages.en.rules.unknown_rule", &ConfigErrorKind::UnknownFunction("unknown_rule".to_string())),
+        (item_path.as_str(), &ConfigErrorKind::MissingField("upgrade_code".to_string())),
+        (item_path.as_str(), &ConfigErrorKind::MissingField("downgrade_code".to_string())),
+        (names_path.as_str(), &ConfigErrorKind::DuplicateName("m".to_string()))
+    ]);
+}
+
+#[test]
+fn config_samples() {
+    assert_eq!(SmartCalc::default().check_samples(), Vec::new());
+
+    /* Samples use the configured rates, updated rates are kept */
+    let mut calculator = SmartCalc::default();
+    calculator.update_currency_rates(&CurrencyRates::parse(r#"{"eur": 0.5, "try": 40}"#).unwrap()).unwrap();
+    assert_eq!(calculator.check_samples(), Vec::new());
+    assert_eq!(outputs(&calculator, "10 usd to eur"), vec!["5,00 €".to_string()]);
+
+    let mut calculator = SmartCalc::load_with_overlays(&[r#"{ "languages": { "en": { "rules": { "number_of": { "samples": [{ "query": "10% of 50", "result": "10" }] } } } } }"#]).unwrap();
+    assert_eq!(calculator.check_samples(), vec![SampleMismatch {
+        language: "en".to_string(),
+        rule: "number_of".to_string(),
+        query: "10% of 50".to_string(),
+        expected: "10".to_string(),
+        actual: Some("5".to_string())
+    }]);
+}
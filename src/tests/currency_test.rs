@@ -0,0 +1,201 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::vec;
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use crate::{SmartCalc, CurrencyRates, CurrencyRateHistory, CurrencyRateError};
+
+const ECB_DAILY: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gesmes:Envelope xmlns:gesmes="http://www.gesmes.org/xml/2002-08-01" xmlns="http://www.ecb.int/vocabulary/2002-08-01/eurofxref">
+    <gesmes:subject>Reference rates</gesmes:subject>
+    <gesmes:Sender>
+        <gesmes:name>European Central Bank</gesmes:name>
+    </gesmes:Sender>
+    <Cube>
+        <Cube time='2024-05-17'>
+            <Cube currency='USD' rate='1.0866'/>
+            <Cube currency='JPY' rate='169.04'/>
+            <Cube currency='TRY' rate='34.9903'/>
+        </Cube>
+    </Cube>
+</gesmes:Envelope>"#;
+
+const ECB_HISTORY: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gesmes:Envelope xmlns:gesmes="http://www.gesmes.org/xml/2002-08-01" xmlns="http://www.ecb.int/vocabulary/2002-08-01/eurofxref">
+    <gesmes:subject>Reference rates</gesmes:subject>
+    <Cube>
+        <Cube time="2021-03-01">
+            <Cube currency="USD" rate="1.2066"/>
+            <Cube currency="TRY" rate="8.9515"/>
+        </Cube>
+        <Cube time="2021-02-26">
+            <Cube currency="USD" rate="1.2121"/>
+            <Cube currency="TRY" rate="8.8953"/>
+        </Cube>
+    </Cube>
+</gesmes:Envelope>"#;
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(0, 0, 0).unwrap()
+}
+
+fn rates(items: &[(&str, f64)]) -> BTreeMap<String, f64> {
+    items.iter().map(|(currency, rate)| (currency.to_string(), *rate)).collect()
+}
+
+fn outputs(calculator: &SmartCalc, text: &str) -> Vec<String> {
+    calculator.execute("en", text).lines.iter().map(|line| match line {
+        Some(line) => match &line.result {
+            Ok(result) => result.output.to_string(),
+            Err(error) => error.to_string()
+        },
+        None => String::new()
+    }).collect()
+}
+
+fn rate_dates(calculator: &SmartCalc, text: &str) -> Vec<Option<NaiveDateTime>> {
+    calculator.execute("en", text).lines.iter().map(|line| match line {
+        Some(line) => line.result.as_ref().ok().and_then(|result| result.rate_date),
+        None => None
+    }).collect()
+}
+
+#[test]
+fn currency_rate_files() {
+    let expected = CurrencyRates::new("EUR", Some(date(2024, 5, 17)), rates(&[("usd", 1.0866), ("jpy", 169.04), ("try", 34.9903)]));
+    assert_eq!(CurrencyRates::from_ecb_xml(ECB_DAILY), Ok(expected.clone()));
+    assert_eq!(CurrencyRates::parse(ECB_DAILY), Ok(expected.clone()));
+    assert_eq!(CurrencyRates::from_csv("Date, USD, JPY, TRY, RUB, \n17 May 2024, 1.0866, 169.04, 34.9903, N/A, \n"), Ok(expected.clone()));
+    assert_eq!(CurrencyRates::from_json(r#"{"base": "EUR", "date": "2024-05-17", "rates": {"USD": 1.0866, "JPY": 169.04, "TRY": 34.9903}}"#), Ok(expected));
+
+    assert_eq!(CurrencyRates::parse(r#"{"base": "USD", "timestamp": 1715904000, "rates": {"EUR": 0.92}}"#), Ok(CurrencyRates::new("USD", Some(date(2024, 5, 17)), rates(&[("eur", 0.92)]))));
+    assert_eq!(CurrencyRates::parse(r#"{"eur": 0.92, "try": 32.2}"#), Ok(CurrencyRates::new("USD", None, rates(&[("eur", 0.92), ("try", 32.2)]))));
+    assert_eq!(CurrencyRates::parse("currency,rate\neur,0.92\n# comment\ntry, 32.2\n"), Ok(CurrencyRates::new("USD", None, rates(&[("eur", 0.92), ("try", 32.2)]))));
+
+    assert_eq!(CurrencyRates::parse("eur,0.92\ntry,abc"), Err(CurrencyRateError::InvalidRate("try".to_string())));
+    assert_eq!(CurrencyRates::parse("eur,0"), Err(CurrencyRateError::InvalidRate("eur".to_string())));
+    assert_eq!(CurrencyRates::parse("eur,0.92,1"), Err(CurrencyRateError::Syntax("'eur,0.92,1' must have currency and rate".to_string())));
+    assert_eq!(CurrencyRates::parse(r#"{"base": "EUR", "date": "17/05/2024", "rates": {"USD": 1.08}}"#), Err(CurrencyRateError::InvalidDate("17/05/2024".to_string())));
+    assert_eq!(CurrencyRates::parse("<Cube></Cube>"), Err(CurrencyRateError::NoRates));
+    assert_eq!(CurrencyRates::parse(""), Err(CurrencyRateError::NoRates));
+}
+
+#[test]
+fn currency_rate_provider() {
+    let mut calculator = SmartCalc::default();
+    let mut rates = CurrencyRates::from_ecb_xml(ECB_DAILY).unwrap();
+    rates.max_age = Some(Duration::days(2));
+
+    assert_eq!(calculator.update_currency_rates(&rates), Ok(3));
+    assert_eq!(outputs(&calculator, "10 eur to usd\n10 usd to try\n$10 + 10 eur\n$10 + $5\n1.000 dkk to eur"), vec![
+        "$10,87".to_string(),
+        "₺322,02".to_string(),
+        "$20,87".to_string(),
+        "$15,00".to_string(),
+        "134,48 €".to_string()
+    ]);
+
+    /* Danish krone rate is not updated, its date is not known */
+    assert_eq!(rate_dates(&calculator, "10 eur to usd\n$10 + 10 eur\n$10 + $5\n1.000 dkk to eur\n$1\n2 eur\ntotal"), vec![
+        Some(date(2024, 5, 17)),
+        Some(date(2024, 5, 17)),
+        None,
+        None,
+        None,
+        None,
+        Some(date(2024, 5, 17))
+    ]);
+
+    let info = calculator.currency_rates_info();
+    assert_eq!((info.base.as_str(), info.as_of), ("EUR", Some(date(2024, 5, 17))));
+    assert!(!info.is_stale(date(2024, 5, 18)));
+    assert!(info.is_stale(date(2024, 5, 20)));
+
+    calculator.update_currency("usd", 1.1);
+    assert_eq!(rate_dates(&calculator, "10 eur to usd"), vec![None]);
+
+    assert_eq!(calculator.update_currency_rates(&CurrencyRates::new("xyz", None, rates.rates.clone())), Err(CurrencyRateError::UnknownCurrency("xyz".to_string())));
+    assert_eq!(calculator.update_currency_rates(&CurrencyRates::new("eur", None, BTreeMap::new())), Err(CurrencyRateError::NoRates));
+}
+
+#[test]
+fn currency_rate_json_result() {
+    let mut calculator = SmartCalc::default();
+    calculator.update_currency_rates(&CurrencyRates::from_ecb_xml(ECB_DAILY).unwrap()).unwrap();
+
+    let result = calculator.execute_json("en", "10 eur to usd\n$10");
+    assert_eq!(result.lines[0].as_ref().unwrap().value.as_ref().unwrap().rate_date, Some("2024-05-17T00:00:00Z".to_string()));
+    assert_eq!(result.lines[1].as_ref().unwrap().value.as_ref().unwrap().rate_date, None);
+}
+
+#[test]
+fn currency_rate_history_files() {
+    let mut days = BTreeMap::new();
+    days.insert(NaiveDate::from_ymd_opt(2021, 3, 1).unwrap(), rates(&[("usd", 1.2066), ("try", 8.9515)]));
+    days.insert(NaiveDate::from_ymd_opt(2021, 2, 26).unwrap(), rates(&[("usd", 1.2121), ("try", 8.8953)]));
+    let expected = CurrencyRateHistory::new("EUR", days);
+
+    assert_eq!(CurrencyRateHistory::from_ecb_xml(ECB_HISTORY), Ok(expected.clone()));
+    assert_eq!(CurrencyRateHistory::parse(ECB_HISTORY), Ok(expected.clone()));
+    assert_eq!(CurrencyRateHistory::from_csv("Date, USD, TRY, \n2021-03-01, 1.2066, 8.9515, \n2021-02-26, 1.2121, 8.8953, \n"), Ok(expected.clone()));
+    assert_eq!(CurrencyRateHistory::from_json(r#"{"base": "EUR", "rates": {"2021-03-01": {"USD": 1.2066, "TRY": 8.9515}, "2021-02-26": {"USD": 1.2121, "TRY": 8.8953}}}"#), Ok(expected));
+
+    let mut days = BTreeMap::new();
+    days.insert(NaiveDate::from_ymd_opt(2021, 3, 1).unwrap(), rates(&[("eur", 0.8288)]));
+    assert_eq!(CurrencyRateHistory::from_csv("date,currency,rate\n2021-03-01,eur,0.8288"), Ok(CurrencyRateHistory::new("USD", days)));
+
+    assert_eq!(CurrencyRateHistory::from_csv("2021-03-01,eur,0.8288\n2021-03-01,try"), Err(CurrencyRateError::Syntax("'2021-03-01,try' must have date, currency and rate".to_string())));
+    assert_eq!(CurrencyRateHistory::from_csv("01.03.2021,eur,0.8288"), Err(CurrencyRateError::InvalidDate("01.03.2021".to_string())));
+    assert_eq!(CurrencyRateHistory::from_json(r#"{"rates": {}}"#), Err(CurrencyRateError::NoRates));
+}
+
+#[test]
+fn currency_rate_history_conversion() {
+    let mut calculator = SmartCalc::default();
+    assert_eq!(calculator.set_currency_rate_history(&CurrencyRateHistory::from_ecb_xml(ECB_HISTORY).unwrap()), Ok(2));
+
+    assert_eq!(outputs(&calculator, "100 usd to eur on 2021-03-01\n100 eur to try on 1 mar 2021\n100 usd to eur at march 1, 2021\n100 usd to eur on 28/02/2021\n100 usd to eur on 2021-02-20\n100 usd to dkk on 2021-03-01\n100 usd to eur"), vec![
+        "82,88 €".to_string(),
+        "₺895,15".to_string(),
+        "82,88 €".to_string(),
+        "82,50 €".to_string(),
+        "Currency rate of 2021-02-20 not found. (USD)".to_string(),
+        "Currency rate of 2021-03-01 not found. (DKK)".to_string(),
+        "83,45 €".to_string()
+    ]);
+
+    /* Sunday uses the rates of the last working day */
+    assert_eq!(rate_dates(&calculator, "100 usd to eur on 2021-03-01\n100 usd to eur on 28 feb 2021\n100 usd to eur"), vec![
+        Some(date(2021, 3, 1)),
+        Some(date(2021, 2, 26)),
+        None
+    ]);
+}
+
+#[test]
+fn asset_conversion() {
+    let mut calculator = SmartCalc::default();
+    assert_eq!(outputs(&calculator, "0,5 btc to usd\n$1000 in eth\n2 oz gold in eur\n10 gram silver\n1 kg gold to usd\n₿0,1 + 1 bitcoin\n0,00000001 btc\n1 eth to btc"), vec![
+        "$25.000,00".to_string(),
+        "Ξ0,40000000".to_string(),
+        "3.338,06 €".to_string(),
+        "0,3215 XAG".to_string(),
+        "$64.301,49".to_string(),
+        "₿1,10000000".to_string(),
+        "₿0,00000001".to_string(),
+        "₿0,05000000".to_string()
+    ]);
+
+    calculator.update_currency("btc", 0.00001);
+    assert_eq!(outputs(&calculator, "1 btc to usd\n1 btc to eur"), vec!["$100.000,00".to_string(), "83.451,56 €".to_string()]);
+
+    /* Assets keep their cross rates to the base currency of the updated rates */
+    calculator.update_currency_rates(&CurrencyRates::from_ecb_xml(ECB_DAILY).unwrap()).unwrap();
+    assert_eq!(outputs(&calculator, "1 btc to eur\n1 btc to usd"), vec!["83.451,56 €".to_string(), "$90.678,46".to_string()]);
+}
@@ -5,17 +5,24 @@
  */
 
 use crate::SmartCalc;
+use crate::smartcalc::ReexecuteResult;
 use crate::Session;
+use crate::UiTokenType;
+use crate::{ErrorKind, SmartCalcError};
+use crate::{LineValue, ValueKind};
 use crate::compiler::date::DateItem;
 use crate::compiler::duration::DurationItem;
 use crate::compiler::dynamic_type::DynamicTypeItem;
 use crate::compiler::time::TimeItem;
 use crate::config::SmartCalcConfig;
 use crate::compiler::money::MoneyItem;
-use crate::types::{SmartCalcAstType, TimeOffset};
+use crate::types::{SmartCalcAstType, TimeOffset, NumericMode};
 use chrono::{Duration, NaiveDate, Utc};
 use chrono::{Datelike};
-use alloc::string::ToString;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::vec;
 use core::ops::Deref;
 
 #[test]
@@ -616,7 +623,7 @@ fn execute_34() {
         SmartCalcAstType::Item(item) => {
             match item.as_any().downcast_ref::<TimeItem>() {
                 Some(time_item) => {
-                    assert_eq!(time_item.get_tz(), TimeOffset { name: "GMT-7".to_string(), offset: -420 } );
+                    assert_eq!(time_item.get_tz(), TimeOffset { name: "GMT-7".to_string(), offset: -420, zone: None } );
                     assert_eq!(time_item.get_time(), chrono::Utc::today().and_hms(16, 0, 0).naive_utc());
                 },
                 _ => assert!(false)
@@ -638,7 +645,7 @@ fn execute_35() {
         SmartCalcAstType::Item(item) => {
             match item.as_any().downcast_ref::<TimeItem>() {
                 Some(time_item) => {
-                    assert_eq!(time_item.get_tz(), TimeOffset { name: "CET".to_string(), offset: 60 } );
+                    assert_eq!(time_item.get_tz(), TimeOffset { name: "CET".to_string(), offset: 60, zone: None } );
                     assert_eq!(time_item.get_time(), chrono::Utc::today().and_hms(16, 0, 0).naive_utc());
                 },
                 _ => assert!(false)
@@ -751,3 +758,412 @@ fn execute_session() {
     session.set_language("en".to_string());
     evaluate_line!(calc with session, r"foo + bar" => Err);
 }
+
+#[test]
+fn execute_numeric_mode() {
+    let mut calc = SmartCalc::default();
+
+    // binary floating point drifts by default
+    let results = calc.execute("en".to_string(), "0,1 + 0,2".to_string());
+    match results.lines[0].as_ref().unwrap().result.as_ref().unwrap().ast.deref() {
+        SmartCalcAstType::Item(item) => assert_ne!(item.get_underlying_number(), 0.3),
+        _ => panic!("Result is not an item")
+    };
+    evaluate_line!(calc, r"1,005 usd" => r"$1,00");
+
+    calc.set_numeric_mode(NumericMode::Decimal);
+    let results = calc.execute("en".to_string(), "0,1 + 0,2".to_string());
+    match results.lines[0].as_ref().unwrap().result.as_ref().unwrap().ast.deref() {
+        SmartCalcAstType::Item(item) => assert_eq!(item.get_underlying_number(), 0.3),
+        _ => panic!("Result is not an item")
+    };
+
+    evaluate_line!(calc, r"1,005 usd" => r"$1,01");
+    evaluate_line!(calc, r"$0,1 + $0,2" => r"$0,30");
+    evaluate_line!(calc, r"1,1k" => r"1.100");
+    evaluate_line!(calc, r"10% of 0,3" => r"0,03");
+    evaluate_line!(calc, r"1 / 0" => Err);
+    evaluate_line!(calc, r"1,1 ^ 2" => r"1,21");
+    evaluate_line!(calc, r"2 ^ 0,5" => r"1,41");
+    evaluate_line!(calc, r"-7 mod 3" => r"2");
+    evaluate_line!(calc, r"-7 // 2" => r"-4");
+    evaluate_line!(calc, r"1234567890,12 usd + 0,01 usd" => r"$1.234.567.890,13");
+    evaluate_line!(calc, r"$12.345.678.901.234.567,89 + $0,01" => r"$12.345.678.901.234.567,90");
+    evaluate_line!(calc, r"12.345.678.901.234.567,89 - 0,01" => r"12.345.678.901.234.567,88");
+    evaluate_line!(calc, r"$12.345.678.901.234.567,89 + 10%" => r"$13.580.246.791.358.024,68");
+    evaluate_line!(calc, r"round($12.345.678.901.234.567,885; 2)" => r"$12.345.678.901.234.567,89");
+    evaluate_line!(calc, r"$12.345.678.901.234.567,89 to usd" => r"$12.345.678.901.234.567,89");
+    evaluate_line!(calc, r"$12.345.678.901.234.567,89 + $0,01 to usd" => r"$12.345.678.901.234.567,90");
+}
+
+#[test]
+fn execute_numeric_mode_variables() {
+    let mut calc = SmartCalc::default();
+    calc.set_numeric_mode(NumericMode::Decimal);
+
+    let results = calc.execute("en".to_string(), "price = $12.345.678.901.234.567,89\nprice + $0,01\nsum".to_string());
+    assert_eq!(results.lines[1].as_ref().unwrap().result.as_ref().unwrap().output, "$12.345.678.901.234.567,90".to_string());
+    assert_eq!(results.lines[2].as_ref().unwrap().result.as_ref().unwrap().output, "$24.691.357.802.469.135,79".to_string());
+}
+
+#[test]
+fn execute_numeric_mode_overflow() {
+    let mut calc = SmartCalc::default();
+    calc.set_numeric_mode(NumericMode::Decimal);
+
+    let results = calc.execute("en".to_string(), "79.228.162.514.264.337.593.543.950.335 + 1".to_string());
+    assert_eq!(results.lines[0].as_ref().unwrap().result.as_ref().unwrap_err().kind, ErrorKind::Overflow);
+
+    let results = calc.execute("en".to_string(), "$50.000.000.000.000.000.000.000.000.000 * 2".to_string());
+    assert_eq!(results.lines[0].as_ref().unwrap().result.as_ref().unwrap_err().kind, ErrorKind::Overflow);
+
+    let results = calc.execute("en".to_string(), "1e30".to_string());
+    assert_eq!(results.lines[0].as_ref().unwrap().result.as_ref().unwrap_err().kind, ErrorKind::Overflow);
+}
+
+#[test]
+fn execute_numeric_mode_results() {
+    let number = |calc: &SmartCalc, text: &str| {
+        let results = calc.execute("en".to_string(), text.to_string());
+        match results.lines[0].as_ref().unwrap().result.as_ref().unwrap().ast.deref() {
+            SmartCalcAstType::Item(item) => item.get_underlying_number(),
+            _ => panic!("Result is not an item")
+        }
+    };
+
+    let mut calc = SmartCalc::default();
+    let samples = [("4,35 * 100", 435.0), ("0,1 * 3", 0.3), ("1,1 + 2,2", 3.3), ("1 - 0,9", 0.1), ("$2,675 * 3", 8.025), ("1,15 * 1,15", 1.3225), ("0,7 * 0,03", 0.021), ("0,3 - 0,1", 0.2)];
+    for (text, result) in samples.iter() {
+        assert_ne!(number(&calc, text), *result, "{}", text);
+    }
+
+    calc.set_numeric_mode(NumericMode::Decimal);
+    for (text, result) in samples.iter() {
+        assert_eq!(number(&calc, text), *result, "{}", text);
+    }
+    assert_eq!(number(&calc, "123456789012,345 + 0,001"), 123456789012.346);
+    assert_eq!(number(&calc, "1 / 3 * 3"), 1.0);
+}
+
+#[test]
+fn execute_power_modulo_and_integer_division() {
+    let calc = SmartCalc::default();
+
+    evaluate_line!(calc, r"2 ^ 10" => r"1.024");
+    evaluate_line!(calc, r"2 ** 3" => r"8");
+    evaluate_line!(calc, r"2 ^ 3 ^ 2" => r"512");
+    evaluate_line!(calc, r"2 * 3 ^ 2" => r"18");
+    evaluate_line!(calc, r"-2 ^ 2" => r"-4");
+    evaluate_line!(calc, r"(-2) ^ 2" => r"4");
+    evaluate_line!(calc, r"2 ^ -1" => r"0,50");
+    evaluate_line!(calc, r"9 ^ 0,5" => r"3");
+
+    evaluate_line!(calc, r"10 mod 3" => r"1");
+    evaluate_line!(calc, r"10 % 4" => r"2");
+    evaluate_line!(calc, r"-7 mod 3" => r"2");
+    evaluate_line!(calc, r"10 mod 0" => Err);
+    evaluate_line!(calc, r"10 usd mod 3" => r"$1,00");
+
+    evaluate_line!(calc, r"7 // 2" => r"3");
+    evaluate_line!(calc, r"-7 // 2" => r"-4");
+    evaluate_line!(calc, r"1 + 7 // 2 * 2" => r"7");
+    evaluate_line!(calc, r"$7 // $2" => r"3");
+    evaluate_line!(calc, r"$7 // 2" => r"$3,00");
+
+    evaluate_line!(calc, r"2 m ^ 2" => r"4 m²");
+    evaluate_line!(calc, r"$2 ^ 2" => Err);
+
+    assert_eq!(execute_error(&calc, "10 ^ 400").kind, ErrorKind::Overflow);
+    assert_eq!(execute_error(&calc, "(-8) ^ 0,5").kind, ErrorKind::UndefinedOperation('^'));
+
+    /* Percentage is not a modulo */
+    evaluate_line!(calc, r"10 % of 200" => r"20");
+    evaluate_line!(calc, r"10 % on 200" => r"220");
+    evaluate_line!(calc, r"10 % off $200" => r"$180,00");
+}
+
+#[test]
+fn execute_function_call() {
+    let calc = SmartCalc::default();
+
+    evaluate_line!(calc, r"sqrt(144)" => r"12");
+    evaluate_line!(calc, r"2 * sqrt(16) + 1" => r"9");
+    evaluate_line!(calc, r"sqrt(2 ^ 4)" => r"4");
+    evaluate_line!(calc, r"round(12,345; 2)" => r"12,35");
+    evaluate_line!(calc, r"round(12,5)" => r"13");
+    evaluate_line!(calc, r"round(1,005 usd; 2)" => r"$1,01");
+    evaluate_line!(calc, r"round(2,5; 28)" => r"2,50");
+    evaluate_line!(calc, r"floor($2,75)" => r"$2,00");
+    evaluate_line!(calc, r"ceil(2,1)" => r"3");
+    evaluate_line!(calc, r"max($5; $8)" => r"$8,00");
+    evaluate_line!(calc, r"min(1; 5; 3) + 1" => r"2");
+    evaluate_line!(calc, r"abs(-3 kg)" => r"3 Kilogram");
+    evaluate_line!(calc, r"log(1000)" => r"3");
+    evaluate_line!(calc, r"log(8; 2)" => r"3");
+    evaluate_line!(calc, r"ln(1)" => r"0");
+    evaluate_line!(calc, r"x = sqrt(9)" => r"3");
+
+    evaluate_line!(calc, r"sqrt(-1)" => Err);
+    evaluate_line!(calc, r"sqrt()" => Err);
+    evaluate_line!(calc, r"max($5; 3)" => Err);
+    evaluate_line!(calc, r"max(1,2)" => r"1,20");
+    evaluate_line!(calc, r"max(1, 2)" => Err);
+    evaluate_line!(calc, r"sqrt(4" => Err);
+}
+
+#[test]
+fn execute_named_constants() {
+    let calc = SmartCalc::default();
+
+    evaluate_line!(calc, r"pi" => r"3,14");
+    evaluate_line!(calc, r"2 * pi" => r"6,28");
+    evaluate_line!(calc, r"pi * 2 m" => r"6,28 Meter");
+    evaluate_line!(calc, r"tau / 2" => r"3,14");
+    evaluate_line!(calc, r"e" => r"2,72");
+    evaluate_line!(calc, r"speed of light / 1000" => r"299.792,46");
+    evaluate_line!(calc, r"g * 2" => r"19,61");
+    evaluate_line!(calc, r"10 g" => r"10 Gram");
+    evaluate_line!(calc, r"light year" => r"9.460.730.472.580,80 Kilometer");
+    evaluate_line!(calc, r"pi = 3" => r"3");
+    evaluate_line!(calc, r"5 c" => Err);
+    evaluate_line!(calc, r"2e" => Err);
+    evaluate_line!(calc, r"1e3" => r"1.000");
+    evaluate_line!(calc, r"1,5e3 + 2E-2" => r"1.500,02");
+    evaluate_line!(calc, r"1e400" => Err);
+    evaluate_line!(calc, r"2 * e" => r"5,44");
+
+    let mut session = Session::new();
+    session.set_language("tr".to_string());
+    evaluate_line!(calc with session, r"ışık hızı / 1000" => r"299.792,46");
+
+    let results = calc.execute("en".to_string(), "pi * 2".to_string());
+    let ui_tokens = &results.lines[0].as_ref().unwrap().ui_tokens;
+    assert_eq!(ui_tokens[0].ui_type, UiTokenType::Constant);
+    assert_eq!(ui_tokens[0].start, 0);
+    assert_eq!(ui_tokens[0].end, 2);
+}
+
+fn execute_document(calc: &SmartCalc, language: &str, lines: &[&str]) -> Vec<Option<String>> {
+    let results = calc.execute(language.to_string(), lines.join("\n"));
+    results.lines.iter().map(|line| line.as_ref().and_then(|line| line.result.as_ref().ok()).map(|result| result.output.to_string())).collect()
+}
+
+#[test]
+fn execute_line_references() {
+    let calc = SmartCalc::default();
+
+    let results = execute_document(&calc, "en", &["10", "20", "prev * 2", "ans + 1", "sum", "", "line 1 + line 2", "average"]);
+    assert_eq!(results[2].as_deref(), Some("40"));
+    assert_eq!(results[3].as_deref(), Some("41"));
+    assert_eq!(results[4].as_deref(), Some("111"));
+    assert_eq!(results[5], None);
+    assert_eq!(results[6].as_deref(), Some("30"));
+    assert_eq!(results[7].as_deref(), Some("30"));
+
+    let results = execute_document(&calc, "en", &["$10", "10 eur", "total"]);
+    assert_eq!(results[2].as_deref(), Some("$21,98"));
+
+    let results = calc.execute("en", "$10\n10 eur\n1 m\ntotal\naverage");
+    for line in &results.lines[3..] {
+        assert_eq!(line.as_ref().unwrap().result.as_ref().unwrap_err().kind, ErrorKind::IncompatibleTypes { left: "MONEY".to_string(), right: "metric-length".to_string() });
+    }
+
+    let results = execute_document(&calc, "en", &["1 m", "50 cm", "sum", "x = prev", "x * 2"]);
+    assert_eq!(results[2].as_deref(), Some("1,50 Meter"));
+    assert_eq!(results[3].as_deref(), Some("1,50 Meter"));
+    assert_eq!(results[4].as_deref(), Some("3 Meter"));
+
+    let results = execute_document(&calc, "tr", &["10", "20", "toplam", "önceki / 2", "satır 2 * 2"]);
+    assert_eq!(results[2].as_deref(), Some("30"));
+    assert_eq!(results[3].as_deref(), Some("15"));
+    assert_eq!(results[4].as_deref(), Some("40"));
+
+    let results = execute_document(&calc, "en", &["prev", "sum", "", "average"]);
+    assert_eq!(results[0], None);
+    assert_eq!(results[1].as_deref(), Some("0"));
+    assert_eq!(results[3], None);
+
+    /* "sum" and "toplam" are also the addition operator */
+    let results = execute_document(&calc, "en", &["10", "3 sum 4", "x = sum", "sum"]);
+    assert_eq!(results[1].as_deref(), Some("7"));
+    assert_eq!(results[2].as_deref(), Some("17"));
+    assert_eq!(results[3].as_deref(), Some("34"));
+
+    let results = execute_document(&calc, "tr", &["10", "3 toplam 4", "toplam"]);
+    assert_eq!(results[1].as_deref(), Some("7"));
+    assert_eq!(results[2].as_deref(), Some("17"));
+}
+
+fn changed_outputs(result: &ReexecuteResult) -> Vec<(usize, String)> {
+    result.changed_lines.iter().map(|(index, line)| (*index, match &line.as_ref().unwrap().result { Ok(result) => result.output.to_string(), Err(error) => error.to_string() })).collect()
+}
+
+#[test]
+fn execute_incremental_update() {
+    let calc = SmartCalc::default();
+    let mut session = Session::new();
+    session.set_language("en".to_string());
+    session.set_text("price = 10\ntax = price * 0,2\nprice + tax\n100\n\n5 * 5\nsum".to_string());
+    calc.execute_session(&session);
+
+    assert!(session.update_line(0, "price = 20".to_string()));
+    let result = calc.reexecute(&session);
+    assert_eq!(changed_outputs(&result), vec![(0, "20".to_string()), (1, "4".to_string()), (2, "24".to_string())]);
+
+    assert!(session.update_line(3, "50 + 50".to_string()));
+    let result = calc.reexecute(&session);
+    assert_eq!(changed_outputs(&result), vec![(3, "100".to_string())]);
+
+    assert!(session.update_line(5, "5 * 6".to_string()));
+    let result = calc.reexecute(&session);
+    assert_eq!(changed_outputs(&result), vec![(5, "30".to_string()), (6, "30".to_string())]);
+
+    assert!(session.update_line(1, "tax = price * 0,5".to_string()));
+    let result = calc.reexecute(&session);
+    assert_eq!(changed_outputs(&result), vec![(1, "10".to_string()), (2, "30".to_string())]);
+
+    assert!(!session.update_line(7, "1".to_string()));
+    assert!(calc.reexecute(&session).changed_lines.is_empty());
+
+    let mut session = Session::new();
+    session.set_language("en".to_string());
+    session.set_text("x = 2\ny = x * 3\nx = 10\ny + x".to_string());
+    calc.execute_session(&session);
+
+    assert!(session.update_line(0, "x = 4".to_string()));
+    let result = calc.reexecute(&session);
+    assert_eq!(changed_outputs(&result), vec![(0, "4".to_string()), (1, "12".to_string()), (3, "22".to_string())]);
+
+    let mut session = Session::new();
+    session.set_language("en".to_string());
+    session.set_text("1\n2 + a".to_string());
+    calc.execute_session(&session);
+
+    assert!(session.update_line(0, "a = 5".to_string()));
+    let result = calc.reexecute(&session);
+    assert_eq!(changed_outputs(&result), vec![(0, "5".to_string()), (1, "7".to_string())]);
+
+    /* Values printed the same are still changed */
+    let mut session = Session::new();
+    session.set_language("en".to_string());
+    session.set_text("x = 1,001\nx * 1000".to_string());
+    calc.execute_session(&session);
+
+    assert!(session.update_line(0, "x = 1,002".to_string()));
+    let result = calc.reexecute(&session);
+    assert_eq!(changed_outputs(&result), vec![(0, "1".to_string()), (1, "1.002".to_string())]);
+
+    /* New session is calculated line by line */
+    let mut session = Session::new();
+    session.set_language("en".to_string());
+    for (line, expected) in [("x = 2", (0, "2")), ("x * 3", (1, "6")), ("sum", (2, "8")), ("prev * x", (3, "16"))] {
+        assert!(session.add_line(line.to_string()));
+        let result = calc.reexecute(&session);
+        assert_eq!(changed_outputs(&result), vec![(expected.0, expected.1.to_string())], "{}", line);
+    }
+    assert!(!session.add_line("1\n2".to_string()));
+}
+
+fn execute_error(calc: &SmartCalc, data: &str) -> SmartCalcError {
+    let results = calc.execute("en".to_string(), data.to_string());
+    results.lines[0].as_ref().unwrap().result.as_ref().unwrap_err().clone()
+}
+
+#[test]
+fn execute_structured_errors() {
+    let calc = SmartCalc::default();
+
+    let error = execute_error(&calc, "1 / 0");
+    assert_eq!(error.kind, ErrorKind::DivisionByZero);
+    assert_eq!((error.span.start, error.span.end), (0, 5));
+
+    let error = execute_error(&calc, "ğ 1 / 0");
+    assert_eq!(error.kind, ErrorKind::DivisionByZero);
+    assert_eq!((error.span.start, error.span.end), (3, 8));
+    assert_eq!((error.span.char_start, error.span.char_end), (2, 7));
+
+    assert_eq!(execute_error(&calc, "5 is what % of 0").kind, ErrorKind::DivisionByZero);
+    assert_eq!(execute_error(&calc, "5 is %0 of what").kind, ErrorKind::DivisionByZero);
+
+    let error = execute_error(&calc, "10 + (2 * 3");
+    assert_eq!(error.kind, ErrorKind::UnclosedParenthesis);
+    assert_eq!((error.span.start, error.span.end), (5, 11));
+
+    let error = execute_error(&calc, "1 m + $1");
+    assert_eq!(error.kind, ErrorKind::IncompatibleTypes { left: "metric-length".to_string(), right: "MONEY".to_string() });
+
+    let error = execute_error(&calc, "2 * sqrt(-1)");
+    assert_eq!(error.kind, ErrorKind::UndefinedResult { function: "sqrt".to_string() });
+    assert_eq!((error.span.start, error.span.end), (4, 12));
+
+    let error = execute_error(&calc, "round(1; 2; 3)");
+    assert_eq!(error.kind, ErrorKind::ArgumentCount { function: "round".to_string(), min: 1, max: Some(2) });
+
+    for digits in ["-1", "1,5", "29", "256"] {
+        assert_eq!(execute_error(&calc, &format!("round(1,25; {})", digits)).kind, ErrorKind::InvalidArgument { function: "round".to_string() }, "{}", digits);
+    }
+
+    let error = execute_error(&calc, "10 usd to xyz");
+    assert_eq!(error.kind, ErrorKind::UnknownCurrency("xyz".to_string()));
+    assert_eq!((error.span.start, error.span.end), (0, 13));
+
+    let error = execute_error(&calc, "10 m to foo");
+    assert_eq!(error.kind, ErrorKind::UnknownUnit("foo".to_string()));
+
+    let error = execute_error(&calc, "1 +");
+    assert_eq!(error.kind, ErrorKind::UnexpectedEnd);
+    assert_eq!((error.span.start, error.span.end), (3, 3));
+}
+
+#[test]
+fn execute_localized_errors() {
+    let calc = SmartCalc::default();
+
+    let error = execute_error(&calc, "1 m + $1");
+    assert_eq!(error.message, "metric-length and money are not compatible");
+    assert_eq!(error.to_string(), "metric-length and money are not compatible");
+
+    let error = execute_error(&calc, "round(1; 2; 3)");
+    assert_eq!(error.message, "round function requires 1 to 2 argument(s)");
+
+    let results = calc.execute("tr".to_string(), "1 / 0\n1 m + $1\nsqrt()\n(1 + 2".to_string());
+    let messages = results.lines.iter().map(|line| line.as_ref().unwrap().result.as_ref().unwrap_err().message.to_string()).collect::<Vec<_>>();
+    assert_eq!(messages, vec![
+        "Sıfıra bölme hatası".to_string(),
+        "metric-length ve para birlikte hesaplanamaz".to_string(),
+        "sqrt fonksiyonu 1 parametre alır".to_string(),
+        "Parantez kapatılmadı".to_string()
+    ]);
+}
+
+#[test]
+fn execute_json_result() {
+    let calc = SmartCalc::default();
+    let result = calc.execute_json("en", "$10 + $5\n\n1 km to m\n10%\n2 hours\n1 / 0");
+    assert!(result.status);
+    assert_eq!(result.lines.len(), 6);
+    assert!(result.lines[1].is_none());
+
+    let money = result.lines[0].as_ref().unwrap();
+    assert_eq!(money.output, Some("$15,00".to_string()));
+    assert_eq!(money.value, Some(LineValue { kind: ValueKind::Money, number: Some(15.0), unit: None, currency: Some("USD".to_string()), rate_date: None }));
+    assert!(money.error.is_none());
+    assert!(!money.ui_tokens.is_empty());
+
+    let length = result.lines[2].as_ref().unwrap().value.clone().unwrap();
+    assert_eq!((length.kind, length.number, length.unit), (ValueKind::DynamicType, Some(1000.0), Some("m".to_string())));
+
+    let percent = result.lines[3].as_ref().unwrap().value.clone().unwrap();
+    assert_eq!((percent.kind, percent.number), (ValueKind::Percent, Some(10.0)));
+
+    let duration = result.lines[4].as_ref().unwrap().value.clone().unwrap();
+    assert_eq!((duration.kind, duration.number), (ValueKind::Duration, Some(7200.0)));
+
+    let error = result.lines[5].as_ref().unwrap();
+    assert!(error.output.is_none() && error.value.is_none());
+    assert_eq!(error.error.as_ref().unwrap().kind, ErrorKind::DivisionByZero);
+
+    let json = result.to_json();
+    assert!(json.starts_with("{\"status\":true,\"lines\":[{\"output\":\"$15,00\",\"value\":{\"kind\":\"money\",\"number\":15.0,\"unit\":null,\"currency\":\"USD\",\"rate_date\":null}"));
+    assert!(json.contains("\"error\":{\"kind\":{\"type\":\"division_by_zero\"},\"span\":{\"start\":0,\"end\":5,\"char_start\":0,\"char_end\":5},\"message\":\"Division by zero\"}"));
+}
@@ -59,7 +59,7 @@ fn execute_1() {
 1024mb + (1024kb * 24)          | 1.048MB
 1000mb / 10MB                   | 100
 1 gb to mb                      | 1.024MB
-1 gb to byte                    | 1.073.741.824byte
+1 gb to byte                    | 1.073.741.824 byte
 x = 2                           | 2
 h = 2 * 2                       | 4
 10 $                            | $10,00
@@ -83,7 +83,7 @@ fn execute_2() {
 1024mb + (1024kb * 24)          | 1,048MB
 1000mb / 10MB                   | 100
 1 gb to mb                      | 1,024MB
-1 gb to byte                    | 1,073,741,824byte
+1 gb to byte                    | 1,073,741,824 byte
 x = 2                           | 2
 h = 2 * 2                       | 4
 10 $                            | $10.00
@@ -107,7 +107,7 @@ fn execute_3() {
 1024mb + (1024kb * 24)          | 1048MB
 1000mb / 10MB                   | 100
 1 gb to mb                      | 1024MB
-1 gb to byte                    | 1073741824byte
+1 gb to byte                    | 1073741824 byte
 x = 2                           | 2
 h = 2 * 2                       | 4
 10 $                            | $10.00
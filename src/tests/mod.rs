@@ -7,3 +7,10 @@
 mod executer_test;
 mod general_test;
 mod variable_test;
+mod config_test;
+mod currency_test;
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+mod wasm_test;
+mod timezone_test;
+mod unit_test;
@@ -0,0 +1,105 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use chrono_tz::Tz;
+use crate::config::SmartCalcConfig;
+use crate::smartcalc::SmartCalc;
+use crate::tools::find_zone;
+
+fn outputs(calculator: &SmartCalc, text: &str) -> Vec<String> {
+    let results = calculator.execute("en".to_string(), text.to_string());
+    results.lines.iter().map(|line| match line {
+        Some(line) => match &line.result {
+            Ok(result) => result.output.to_string(),
+            Err(error) => error.to_string()
+        },
+        None => String::new()
+    }).collect()
+}
+
+#[test]
+fn zone_names() {
+    assert_eq!(find_zone("Europe/Istanbul"), Some(Tz::Europe__Istanbul));
+    assert_eq!(find_zone("america/new_york"), Some(Tz::America__New_York));
+    assert_eq!(find_zone("New York"), Some(Tz::America__New_York));
+    assert_eq!(find_zone("istanbul"), Some(Tz::Europe__Istanbul));
+    assert_eq!(find_zone("Buenos Aires"), Some(Tz::America__Argentina__Buenos_Aires));
+    assert_eq!(find_zone("North"), None);
+    assert_eq!(find_zone("Factory"), None);
+}
+
+#[test]
+fn zone_conversions() {
+    let calculator = SmartCalc::default();
+    assert_eq!(outputs(&calculator, "1/7/2021 at 15:00 New York to Berlin\n1/1/2021 at 15:00 New York to Berlin\n1/7/2021 at 15:00 Europe/London to Asia/Tokyo\n1/7/2021 at 10:00 to America/New_York"), [
+        "1 Jul 2021 21:00:00 CEST",
+        "1 Jan 2021 21:00:00 CET",
+        "1 Jul 2021 23:00:00 JST",
+        "1 Jul 2021 06:00:00 EDT"]);
+}
+
+#[test]
+fn zone_daylight_saving() {
+    let calculator = SmartCalc::default();
+
+    /* Clocks are moved forward at 2021-03-28 in Berlin and back at 2021-11-07 in New York */
+    assert_eq!(outputs(&calculator, "27/03/2021 at 10:00 Berlin + 1 day\n28/03/2021 at 01:30 Berlin + 2 hours\n6/11/2021 at 12:00 New York + 1 day\n28/03/2021 at 10:00 Berlin - 1 day"), [
+        "28 Mar 2021 10:00:00 CEST",
+        "28 Mar 2021 04:30:00 CEST",
+        "7 Nov 2021 12:00:00 EST",
+        "27 Mar 2021 10:00:00 CET"]);
+}
+
+#[test]
+fn zone_default_timezone() {
+    let mut calculator = SmartCalc::default();
+    calculator.set_timezone("Europe/Berlin".to_string()).unwrap();
+    assert_eq!(outputs(&calculator, "1/7/2021 at 10:00\n1/1/2021 at 10:00\n1/7/2021 at 10:00 to EST"), [
+        "1 Jul 2021 10:00:00 CEST",
+        "1 Jan 2021 10:00:00 CET",
+        "1 Jul 2021 03:00:00 EST"]);
+
+    /* Abbreviations are still fixed offsets */
+    calculator.set_timezone("CET".to_string()).unwrap();
+    assert_eq!(outputs(&calculator, "1/7/2021 at 10:00"), ["1 Jul 2021 10:00:00 CET"]);
+
+    calculator.set_timezone("Asia/Istanbul".to_string()).unwrap();
+    assert_eq!(outputs(&calculator, "1/7/2021 at 10:00 to UTC"), ["1 Jul 2021 07:00:00 UTC"]);
+    assert!(calculator.set_timezone("Moon/Base".to_string()).is_err());
+}
+
+#[test]
+fn location_names() {
+    let config = SmartCalcConfig::default();
+    assert_eq!(config.find_location("en", "Japan"), Some(Tz::Asia__Tokyo));
+    assert_eq!(config.find_location("en", "san francisco"), Some(Tz::America__Los_Angeles));
+    assert_eq!(config.find_location("en", "Tokyo"), Some(Tz::Asia__Tokyo));
+    assert_eq!(config.find_location("tr", "Londra"), Some(Tz::Europe__London));
+    assert_eq!(config.find_location("tr", "germany"), None);
+    assert_eq!(config.find_location("en", "londra"), None);
+}
+
+#[test]
+fn location_conversions() {
+    let calculator = SmartCalc::default();
+    assert_eq!(outputs(&calculator, "1/7/2021 at 9:00 London to Sydney\n1/7/2021 at 9:00 london to sydney\n1/7/2021 at 14:00 Istanbul in Los Angeles\n1/1/2021 at 10:00 Japan to Germany\n1/1/2021 at 10:00 to usa\n2021-01-01 at 10:00 to Berlin\n2021-01-01 at 15:00 New York"), [
+        "1 Jul 2021 18:00:00 AEST",
+        "1 Jul 2021 18:00:00 AEST",
+        "1 Jul 2021 04:00:00 PDT",
+        "1 Jan 2021 02:00:00 CET",
+        "1 Jan 2021 05:00:00 EST",
+        "1 Jan 2021 11:00:00 CET",
+        "1 Jan 2021 15:00:00 EST"]);
+
+    /* Current time at the location, only the timezone is checked */
+    for (language, text) in [("en", "time in Tokyo"), ("en", "time in japan"), ("en", "time for Los Angeles"), ("en", "meeting at 14:00 Istanbul in Los Angeles"), ("tr", "tokyo saat kaç"), ("tr", "saat kaç Japonya")] {
+        let results = calculator.execute(language.to_string(), text.to_string());
+        let output = results.lines[0].as_ref().unwrap().result.as_ref().unwrap().output.to_string();
+        assert!(output.ends_with("JST") || output.ends_with("PDT") || output.ends_with("PST"), "{} => {}", text, output);
+    }
+}
@@ -0,0 +1,365 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use crate::config::{DynamicType, SmartCalcConfig, UnitBase};
+use crate::error::ErrorKind;
+use crate::result::ValueKind;
+use crate::smartcalc::SmartCalc;
+
+fn outputs(calculator: &SmartCalc, language: &str, text: &str) -> Vec<String> {
+    let results = calculator.execute(language.to_string(), text.to_string());
+    results.lines.iter().map(|line| match line {
+        Some(line) => match &line.result {
+            Ok(result) => result.output.to_string(),
+            Err(error) => error.to_string()
+        },
+        None => String::new()
+    }).collect()
+}
+
+fn error_kind(calculator: &SmartCalc, text: &str) -> ErrorKind {
+    let results = calculator.execute("en".to_string(), text.to_string());
+    results.lines[0].as_ref().unwrap().result.as_ref().unwrap_err().kind.clone()
+}
+
+#[test]
+fn compound_unit_calculations() {
+    let calculator = SmartCalc::default();
+    assert_eq!(outputs(&calculator, "en", "5 m * 2 m\n5 m * 200 cm\n2 m ^ 2\n4 m² ^ 0,5\n100 km / 2 hours\n100 km / 90 minutes\n10 km / (5 km/h)\n2 hours * 50 km/h\n9,8 m/s² * 2 s\n3 kg * 2 m / 4 s\n10 / 4 m\n10 m² / 2 m\n6 m / 3 m"), [
+        "10 m²",
+        "10 m²",
+        "4 m²",
+        "2 Meter",
+        "50 km/h",
+        "66,67 km/h",
+        "2 hours",
+        "100 Kilometer",
+        "19,60 m/s",
+        "1,50 kg·m/s",
+        "2,50 m⁻¹",
+        "5 Meter",
+        "2"]);
+
+    /* Only addition and multiplication are calculated by the right item */
+    assert_eq!(outputs(&calculator, "en", "10 / $2\n2 - 3 m\n5 + $10\n10 - $2\n2 ^ 3 m"), [
+        "5 USD⁻¹",
+        "number and metric-length are not compatible",
+        "$15,00",
+        "number and money are not compatible",
+        "number and metric-length are not compatible"]);
+}
+
+#[test]
+fn compound_unit_rates() {
+    let calculator = SmartCalc::default();
+    assert_eq!(outputs(&calculator, "en", "$12/hour\n$12/hour * 3 hours\n$300 / 3 days\n$10 / 2 kg\n$5/kg * 3 kg\n$25/hour * 14 hours of work\n$10/month"), [
+        "$12,00/h",
+        "$36,00",
+        "$100,00/day",
+        "$5,00/kg",
+        "$15,00",
+        "$350,00",
+        "$10,00"]);
+}
+
+#[test]
+fn compound_unit_parse() {
+    let calculator = SmartCalc::default();
+    assert_eq!(outputs(&calculator, "en", "50 km/h\n12 m²\n2 m³\n5 m²/s\n0,5 h\n10 GB / 2 s"), [
+        "50 km/h",
+        "12 m²",
+        "2 m³",
+        "5 m²/s",
+        "30 minutes",
+        "5 GB/s"]);
+
+    assert_eq!(outputs(&calculator, "tr", "50 km/sa\n12 m²"), ["50 km/h", "12 m²"]);
+}
+
+#[test]
+fn compound_unit_conversions() {
+    let calculator = SmartCalc::default();
+    assert_eq!(outputs(&calculator, "en", "36 km/h to m/s\n1 mile/h to km/h\n1 m² to cm²\n100 km/h + 10 m/s\n$5/kg to usd/lb\n1 lb to kg\n1 cm to m"), [
+        "10 m/s",
+        "1,61 km/h",
+        "10.000 cm²",
+        "136 km/h",
+        "$2,27/lb",
+        "0,45 Kilogram",
+        "0,01 Meter"]);
+
+    assert_eq!(outputs(&calculator, "en", "100 km / 2 hours to m/s\n10 m * 1 m to ft²\n6 kw·h to kWh\n1 km + 500 m to m\nspeed = 100 km / 2 hours to km/h\nspeed * 2 hours"), [
+        "13,89 m/s",
+        "107,64 ft²",
+        "6 Kilowatt-hour",
+        "1.500 Meter",
+        "50 km/h",
+        "100 Kilometer"]);
+
+    assert_eq!(error_kind(&calculator, "10 m * 1 m to m³"), ErrorKind::IncompatibleTypes { left: "m²".to_string(), right: "m³".to_string() });
+}
+
+#[test]
+fn compound_unit_errors() {
+    let calculator = SmartCalc::default();
+    assert_eq!(error_kind(&calculator, "5 m + 2 kg"), ErrorKind::IncompatibleTypes { left: "metric-length".to_string(), right: "metric-weight".to_string() });
+    assert_eq!(error_kind(&calculator, "5 m/s + 2 kg"), ErrorKind::IncompatibleTypes { left: "m/s".to_string(), right: "metric-weight".to_string() });
+    assert_eq!(error_kind(&calculator, "10 m² + 2 m"), ErrorKind::IncompatibleTypes { left: "m²".to_string(), right: "metric-length".to_string() });
+    assert_eq!(error_kind(&calculator, "1 km/h to kg"), ErrorKind::IncompatibleTypes { left: "km/h".to_string(), right: "kg".to_string() });
+    assert_eq!(error_kind(&calculator, "1 km/h to parsec"), ErrorKind::UnknownUnit("parsec".to_string()));
+    assert_eq!(error_kind(&calculator, "2 m ^ 0,5"), ErrorKind::IncompatibleTypes { left: "metric-length".to_string(), right: "NUMBER".to_string() });
+}
+
+#[test]
+fn compound_unit_value() {
+    let calculator = SmartCalc::default();
+    let result = calculator.execute_json("en", "100 km / 2 hours\n$10 / 2 kg");
+
+    let speed = result.lines[0].as_ref().unwrap().value.clone().unwrap();
+    assert_eq!((speed.kind, speed.number, speed.unit, speed.currency), (ValueKind::CompoundType, Some(50.0), Some("km/h".to_string()), None));
+
+    let price = result.lines[1].as_ref().unwrap().value.clone().unwrap();
+    assert_eq!((price.kind, price.number, price.unit, price.currency), (ValueKind::CompoundType, Some(5.0), Some("USD/kg".to_string()), Some("USD".to_string())));
+}
+
+#[test]
+fn temperature_conversions() {
+    let calculator = SmartCalc::default();
+    assert_eq!(outputs(&calculator, "en", "20 °C to °F\n68 fahrenheit to celsius\n300 kelvin to °C\n0 kelvin to °F\n-40 °F to °C\n37 °C to fahrenheit"), [
+        "68 °F",
+        "20 °C",
+        "26,85 °C",
+        "-459,67 °F",
+        "-40 °C",
+        "98,60 °F"]);
+
+    assert_eq!(outputs(&calculator, "tr", "20 santigrat\n68 °F"), ["20 °C", "68 °F"]);
+
+    /* Absolute temperatures could not be below 0 K, differences could */
+    assert_eq!(outputs(&calculator, "en", "-273,15 °C to kelvin\ndifference = 20 °C - 320 °C\ndifference to °F"), ["0 K", "Δ-300 °C", "Δ-540 °F"]);
+    assert_eq!(error_kind(&calculator, "-300 celsius to kelvin"), ErrorKind::BelowAbsoluteZero);
+    assert_eq!(error_kind(&calculator, "-500 °F to °C"), ErrorKind::BelowAbsoluteZero);
+    assert_eq!(error_kind(&calculator, "-5 kelvin to °F"), ErrorKind::BelowAbsoluteZero);
+    assert_eq!(ErrorKind::BelowAbsoluteZero.localize(&SmartCalcConfig::default(), "tr"), "Değer mutlak sıfırın altında");
+}
+
+#[test]
+fn temperature_differences() {
+    let calculator = SmartCalc::default();
+    assert_eq!(outputs(&calculator, "en", "difference = 30 °C - 20 °C\ndifference to °F\ndifference to kelvin\n20 °C + 10 °F\n20 °C - 10 °F\ndifference + 5 °F\ndifference * 2\n10 °C / 2 hours"), [
+        "Δ10 °C",
+        "Δ18 °F",
+        "Δ10 K",
+        "25,56 °C",
+        "Δ32,22 °C",
+        "23 °F",
+        "Δ20 °C",
+        "5 °C/h"]);
+
+    assert_eq!(error_kind(&calculator, "(30 °C - 20 °C) - 20 °C"), ErrorKind::IncompatibleTypes { left: "temperature".to_string(), right: "temperature".to_string() });
+    assert_eq!(error_kind(&calculator, "20 °C to mpg"), ErrorKind::IncompatibleTypes { left: "temperature".to_string(), right: "fuel-economy".to_string() });
+}
+
+#[test]
+fn fuel_economy_conversions() {
+    let calculator = SmartCalc::default();
+    assert_eq!(outputs(&calculator, "en", "30 mpg to l/100km\n8 l/100km to mpg\n30 mpg + 8 l/100km\n8 l/100km"), [
+        "7,84 L/100 km",
+        "29,40 mpg",
+        "59,40 mpg",
+        "8 L/100 km"]);
+
+    assert_eq!(error_kind(&calculator, "0 l/100km to mpg"), ErrorKind::DivisionByZero);
+    assert_eq!(error_kind(&calculator, "0 mpg to l/100km"), ErrorKind::DivisionByZero);
+}
+
+#[test]
+fn conversion_formulas() {
+    /* Linear codes are compiled into factors of the first unit of the group */
+    let config = SmartCalcConfig::default();
+    assert_eq!(config.find_dynamic_type("mm").unwrap().base, Some(UnitBase::Affine { scale: 1.0, offset: 0.0 }));
+    assert_eq!(config.find_dynamic_type("km").unwrap().base, Some(UnitBase::Affine { scale: 1_000_000.0, offset: 0.0 }));
+    assert_eq!(config.find_dynamic_type("mile").unwrap().base, Some(UnitBase::Affine { scale: 63_360.0, offset: 0.0 }));
+
+    let calculator = SmartCalc::default();
+    assert_eq!(outputs(&calculator, "en", "1 mile to mm\n1 km to mm\n1 cm to m\n3 foot to inch\n1 lb to kg"), [
+        "1.609.344 Millimeter",
+        "1.000.000 Millimeter",
+        "0,01 Meter",
+        "36 Inch",
+        "0,45 Kilogram"]);
+
+    let mut calculator = SmartCalc::default();
+    assert!(calculator.add_dynamic_type("box"));
+    assert!(calculator.add_dynamic_type_item("box", 1, "{value} Small", vec!["{NUMBER:value} {TEXT:type:small}"], "({value} - 1) / 2", "{value} * 2 + 1", vec!["small".to_string()], None, None, None));
+    assert!(calculator.add_dynamic_type_item("box", 2, "{value} Big", vec!["{NUMBER:value} {TEXT:type:big}"], "{value} / 2", "{value} * 2 + 1", vec!["big".to_string()], None, None, None));
+    assert_eq!(outputs(&calculator, "en", "3 big to small\n7 small to big"), ["7 Small", "3 Big"]);
+}
+
+#[test]
+fn conversion_graph() {
+    /* Nautical units are only connected to metric units, imperial units are two conversions away */
+    let nautical = r#"{
+        "types": [{ "name": "nautical-length", "items": [
+            { "index": 1, "format": "{value} Cable", "parse": ["{NUMBER:value} {TEXT:type:cable}"], "names": ["cable"], "upgrade_code": "{value} / 10", "downgrade_code": "{value}" },
+            { "index": 2, "format": "{value} Nautical Mile", "parse": ["{NUMBER:value} {TEXT:type:nmi}"], "names": ["nmi"], "upgrade_code": "{value}", "downgrade_code": "{value} * 10" }
+        ] }],
+        "type_conversion": [{ "source": { "name": "nautical-length", "index": 2 }, "target": { "name": "metric-length", "index": 4 }, "to_source_calculation": "{value} * 1852", "to_target_calculation": "{value} / 1852" }]
+    }"#;
+
+    let calculator = SmartCalc::load_with_overlays(&[nautical]).unwrap();
+    assert_eq!(outputs(&calculator, "en", "1 nmi to inch\n1 mile to nmi\n5 cable to m\n1 inch to mm"), [
+        "72.913,39 Inch",
+        "0,87 Nautical Mile",
+        "926 Meter",
+        "25,40 Millimeter"]);
+    assert_eq!(error_kind(&calculator, "1 nmi to kg"), ErrorKind::IncompatibleTypes { left: "nautical-length".to_string(), right: "metric-weight".to_string() });
+
+    /* Foot to meter is a second way between imperial and metric units */
+    let calculator = SmartCalc::load_with_overlays(&[nautical, r#"{
+        "type_conversion": [{ "source": { "name": "imperial-unit-length", "index": 2 }, "target": { "name": "metric-length", "index": 4 }, "to_source_calculation": "{value} * 0.3048", "to_target_calculation": "{value} / 0.3048" }]
+    }"#]).unwrap();
+    assert_eq!(error_kind(&calculator, "1 inch to mm"), ErrorKind::AmbiguousConversion { left: "imperial-unit-length".to_string(), right: "metric-length".to_string() });
+    assert_eq!(error_kind(&calculator, "1 nmi to foot"), ErrorKind::AmbiguousConversion { left: "nautical-length".to_string(), right: "imperial-unit-length".to_string() });
+    assert_eq!(outputs(&calculator, "en", "1 nmi to cm"), ["185.200 Centimeter"]);
+
+    let error = ErrorKind::AmbiguousConversion { left: "imperial-unit-length".to_string(), right: "metric-length".to_string() };
+    assert_eq!(error.localize(&SmartCalcConfig::default(), "tr"), "imperial-unit-length ile metric-length arasındaki dönüşüm belirsiz");
+}
+
+#[test]
+fn standard_unit_names() {
+    let calculator = SmartCalc::default();
+    let config = SmartCalcConfig::default();
+
+    /* Every name of the standard units is parsed in both languages and written with the format of the language */
+    for group in ["angle", "area", "data-rate", "energy", "power", "pressure", "speed", "volume"] {
+        for dynamic_type in config.types.get(group).unwrap().values() {
+            for language in ["en", "tr"] {
+                let format = config.format.get(language).unwrap().dynamic_type.iter()
+                    .find(|item| item.group == group && item.index == dynamic_type.index)
+                    .map_or(&dynamic_type.format, |item| &item.format);
+
+                for name in dynamic_type.names.iter() {
+                    assert_eq!(outputs(&calculator, language, &format!("2 {}", name)), [format.replace("{value}", "2")], "{} {}", language, name);
+                }
+            }
+        }
+    }
+
+    assert_eq!(outputs(&calculator, "tr", "1 hektar\n1 hektar + 5000 m²\n2 bardak\n3 paynt\n5 mil/sa\n10 düğüm\n2 libre/inç²\n760 milimetre cıva\n100 kb/sn\n8 mbit/sn\n4 kilovat\n2 beygir\n90 derece"), [
+        "1 Hektar",
+        "1,50 Hektar",
+        "2 Bardak",
+        "3 Paynt",
+        "5 mil/sa",
+        "10 Düğüm",
+        "2 psi",
+        "760 mmHg",
+        "100 kB/sn",
+        "8 Mbit/sn",
+        "4 Kilovat",
+        "2 Beygir",
+        "90 Derece"]);
+
+    /* Every singular and plural name converts to the first unit of its group */
+    for group in ["angle", "area", "data-rate", "energy", "power", "pressure", "speed", "volume"] {
+        let types = config.types.get(group).unwrap();
+        let target = types.get(&1).unwrap();
+        let scale = |dynamic_type: &DynamicType| match dynamic_type.base {
+            Some(UnitBase::Affine { scale, .. }) => scale,
+            _ => panic!("{} has no base scale", dynamic_type.names[0])
+        };
+
+        for dynamic_type in types.values() {
+            for name in dynamic_type.names.iter() {
+                let result = calculator.execute_json("en", format!("1 {} to {}", name, target.names[0]));
+                let number = result.lines[0].as_ref().unwrap().value.as_ref().and_then(|value| value.number).unwrap();
+                let expected = scale(dynamic_type) / scale(target);
+                assert!((number - expected).abs() <= expected * 1e-9, "1 {} to {}: {} != {}", name, target.names[0], number, expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn standard_unit_conversions() {
+    let calculator = SmartCalc::default();
+    assert_eq!(outputs(&calculator, "en", "1 hectare to acre\n1 acre to m²\n10000 m² to hectare\n1 dönüm to m²\n1 hectare + 5000 m²\narea = 20 m * 50 m\narea to dönüm"), [
+        "2,47 Acre",
+        "4.046,86 m²",
+        "1 Hectare",
+        "1.000 m²",
+        "1,50 Hectare",
+        "1.000 m²",
+        "1 Dönüm"]);
+
+    assert_eq!(outputs(&calculator, "en", "1 l to ml\n1 gallon to l\n2 cups to ml\n1 pint to cups\n1 m³ to l\n1 gallon to dm³\n1 cup to ml\n1 liter to cup\n2 liters to gallons"), [
+        "1.000 Milliliter",
+        "3,79 Liter",
+        "473,18 Milliliter",
+        "2 Cup",
+        "1.000 Liter",
+        "3,79 dm³",
+        "236,59 Milliliter",
+        "4,23 Cup",
+        "0,53 Gallon"]);
+
+    assert_eq!(outputs(&calculator, "en", "60 mph to km/h\n10 knot to km/h\n1 knot to m/s\nrate = 100 km / 2 h\nrate to mph"), [
+        "96,56 km/h",
+        "18,52 km/h",
+        "0,51 m/s",
+        "50 km/h",
+        "31,07 mph"]);
+
+    assert_eq!(outputs(&calculator, "en", "1 kcal to kj\n1000 cal to kcal\n1 kWh to J\n1 wh to kj\nenergy = 2 kW * 3 hours\nenergy to kWh"), [
+        "4,18 Kilojoule",
+        "1 Kilocalorie",
+        "3.600.000 Joule",
+        "3,60 Kilojoule",
+        "6 kW·h",
+        "6 Kilowatt-hour"]);
+
+    assert_eq!(outputs(&calculator, "en", "1 bar to psi\n1 atm to mmhg\n20 kpa to pa\n1 atm to bar"), [
+        "14,50 psi",
+        "760 mmHg",
+        "20.000 Pascal",
+        "1,01 Bar"]);
+
+    assert_eq!(outputs(&calculator, "en", "1 hp to w\n2 kw to hp\npower = 1000 J / 10 s\npower to w"), [
+        "745,70 Watt",
+        "2,68 Horsepower",
+        "100 J/s",
+        "100 Watt"]);
+
+    assert_eq!(outputs(&calculator, "en", "180 deg to rad\n1 turn to degree\n100 gon to deg"), [
+        "3,14 Radian",
+        "360 Degree",
+        "90 Degree"]);
+
+    assert_eq!(outputs(&calculator, "en", "100 Mbps to MB/s\n1 Gbps to kbps\n1 MB/s to Mbps\n8000 bps to kbps\n1 byte to bit\n1 GB/s to Gbps\n2 kB/s to bps"), [
+        "12,50 MB/s",
+        "1.000.000 kbps",
+        "8 Mbps",
+        "8 kbps",
+        "8 bit",
+        "8 Gbps",
+        "16.000 bps"]);
+
+    assert_eq!(outputs(&calculator, "en", "1 day to seconds\n1 hour to min\n90 minutes to hours\n1 week to days\n1 day + 2 hours to minutes\n1 GB / 2 hours to kB/s"), [
+        "86.400 Second",
+        "60 Minute",
+        "1,50 Hour",
+        "7 Day",
+        "1.560 Minute",
+        "149,13 kB/s"]);
+
+    assert_eq!(error_kind(&calculator, "1 hour to kg"), ErrorKind::IncompatibleTypes { left: "time".to_string(), right: "metric-weight".to_string() });
+}
@@ -0,0 +1,56 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use alloc::string::ToString;
+use alloc::vec;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::wasm::SmartCalcWeb;
+
+fn to_json(value: JsValue) -> serde_json::Value {
+    serde_wasm_bindgen::from_value(value).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn wasm_execute() {
+    let calc = SmartCalcWeb::new();
+    let result = to_json(calc.execute("en", "$10 + $5\n\n1 / 0").unwrap().into());
+
+    assert_eq!(result["status"], true);
+    assert_eq!(result["lines"][0]["output"], "$15,00");
+    assert_eq!(result["lines"][0]["value"]["kind"], "money");
+    assert_eq!(result["lines"][0]["value"]["number"], 15.0);
+    assert_eq!(result["lines"][0]["value"]["currency"], "USD");
+    assert!(result["lines"][1].is_null());
+    assert_eq!(result["lines"][2]["error"]["kind"]["type"], "division_by_zero");
+}
+
+#[wasm_bindgen_test]
+fn wasm_settings() {
+    let mut calc = SmartCalcWeb::new();
+    calc.set_decimal_separator(".");
+    calc.set_thousand_separator(",");
+    assert!(calc.update_currency("try", 10.0));
+    assert!(calc.set_timezone("UTC".to_string()).is_ok());
+    assert!(calc.set_timezone("Unknown".to_string()).is_err());
+
+    let result = to_json(calc.execute("en", "1 usd to try\n1200.5").unwrap().into());
+    assert_eq!(result["lines"][0]["value"]["number"], 10.0);
+    assert_eq!(result["lines"][1]["output"], "1,200.50");
+
+    assert!(calc.add_dynamic_type("test"));
+    assert!(calc.add_dynamic_type_item("test", 1, "{value} Item", vec!["{NUMBER:value} {TEXT:type:item}".to_string()], "{value}", "{value}", vec!["item".to_string()], None, None, None));
+    let result = to_json(calc.execute("en", "10 item").unwrap().into());
+    assert_eq!(result["lines"][0]["value"]["kind"], "dynamic_type");
+}
+
+#[wasm_bindgen_test]
+fn wasm_load_from_json() {
+    assert!(SmartCalcWeb::load_from_json(include_str!("../json/config.json")).is_ok());
+    assert!(SmartCalcWeb::load_from_json("{ not json").is_err());
+    assert!(SmartCalcWeb::load_from_json("{}").is_err());
+}
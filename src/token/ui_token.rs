@@ -20,7 +20,8 @@ pub enum UiTokenType {
     Comment,
     VariableDefination,
     VariableUse,
-    Month
+    Month,
+    Constant
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq)]
@@ -0,0 +1,85 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use alloc::string::{String, ToString};
+use core::{ops::Deref, cell::{RefCell, Cell}};
+
+use crate::{types::TokenType, UiTokenType};
+use crate::error::{ErrorKind, SmartCalcError, Span};
+
+use super::{Tokinizer, TokenInfoStatus, TokenInfo};
+
+pub fn constant_tokinizer(tokinizer: &mut Tokinizer) {
+    let named_constants = match tokinizer.config.named_constants.get(&tokinizer.language) {
+        Some(named_constants) => named_constants,
+        None => return
+    };
+
+    /* Variable name could be same with constant name */
+    let mut token_start_index = 0;
+    for (index, token) in tokinizer.token_infos.iter().enumerate() {
+        if let Some(TokenType::Operator('=')) = &token.token_type.borrow().deref() {
+            token_start_index = index + 1;
+            break;
+        }
+    }
+
+    let mut update_tokens = true;
+    while update_tokens {
+        update_tokens = false;
+
+        let active_tokens: Vec<usize> = (token_start_index..tokinizer.token_infos.len())
+            .filter(|index| tokinizer.token_infos[*index].status.get() == TokenInfoStatus::Active)
+            .collect();
+
+        'search: for position in 0..active_tokens.len() {
+            for named_constant in named_constants.iter() {
+                let total_constant_token = named_constant.tokens.len();
+                if total_constant_token == 0 || position + total_constant_token > active_tokens.len() {
+                    continue;
+                }
+
+                let is_same = named_constant.tokens.iter().enumerate().all(|(index, constant_token)| &tokinizer.token_infos[active_tokens[position + index]] == constant_token);
+                if !is_same {
+                    continue;
+                }
+
+                let start_token_index   = active_tokens[position];
+                let end_token_index     = active_tokens[position + total_constant_token - 1];
+                let text_start_position = tokinizer.token_infos[start_token_index].start;
+                let text_end_position   = tokinizer.token_infos[end_token_index].end;
+
+                /* "5 c" could be a unit, one letter constants are not used next to a number */
+                let is_number = |index: Option<&usize>| index.is_some_and(|index| matches!(tokinizer.token_infos[*index].token_type.borrow().deref(), Some(TokenType::Number(_, _))));
+                if named_constant.standalone && (is_number(position.checked_sub(1).and_then(|index| active_tokens.get(index))) || is_number(active_tokens.get(position + total_constant_token))) {
+                    if tokinizer.rule_error.is_none() {
+                        let span = Span::new(&tokinizer.data, text_start_position, text_end_position);
+                        tokinizer.rule_error = Some(SmartCalcError::new(ErrorKind::UnknownUnit(tokinizer.data[text_start_position..text_end_position].to_string()), span));
+                    }
+                    continue;
+                }
+
+                for index in start_token_index..end_token_index + 1 {
+                    tokinizer.token_infos[index].status.set(TokenInfoStatus::Removed);
+                }
+
+                tokinizer.ui_tokens.update_tokens(text_start_position, text_end_position, UiTokenType::Constant);
+                tokinizer.token_infos.insert(start_token_index, Rc::new(TokenInfo {
+                    start: text_start_position,
+                    end: text_end_position,
+                    token_type: RefCell::new(Some(named_constant.token_type.clone())),
+                    original_text: String::new(),
+                    status: Cell::new(TokenInfoStatus::Active)
+                }));
+
+                update_tokens = true;
+                break 'search;
+            }
+        }
+    }
+}
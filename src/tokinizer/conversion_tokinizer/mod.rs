@@ -0,0 +1,62 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use alloc::rc::Rc;
+use core::ops::Deref;
+
+use crate::compiler::compound_type::CompoundUnit;
+use crate::error::Span;
+use crate::{types::TokenType, UiTokenType};
+
+use super::{Tokinizer, TokenInfoStatus};
+
+/* Result of an expression written in an other unit, like "100 km / 2 hours to m/s". Single items are converted with the rules */
+pub fn conversion_tokinizer(tokinizer: &mut Tokinizer) {
+    let conversion_words = match tokinizer.config.word_group.get(&tokinizer.language).and_then(|groups| groups.get("conversion_group")) {
+        Some(conversion_words) => conversion_words,
+        None => return
+    };
+
+    let mut expression_start = 0;
+    for (index, token) in tokinizer.token_infos.iter().enumerate() {
+        if let Some(TokenType::Operator('=')) = token.token_type.borrow().deref() {
+            expression_start = index + 1;
+            break;
+        }
+    }
+
+    let is_conversion_word = |index: &usize| {
+        let token = &tokinizer.token_infos[*index];
+        token.status.get() == TokenInfoStatus::Active && matches!(token.token_type.borrow().deref(), Some(TokenType::Text(text)) if conversion_words.contains(&text.to_lowercase()))
+    };
+
+    let word_index = match (expression_start..tokinizer.token_infos.len()).rev().find(is_conversion_word) {
+        Some(word_index) => word_index,
+        None => return
+    };
+
+    let has_operator = tokinizer.token_infos[expression_start..word_index].iter()
+        .any(|token| token.status.get() == TokenInfoStatus::Active && matches!(token.token_type.borrow().deref(), Some(TokenType::Operator(_))));
+    if !has_operator {
+        return;
+    }
+
+    let unit_start = tokinizer.token_infos[word_index].end;
+    let unit_text = &tokinizer.data[unit_start..];
+    let unit = match CompoundUnit::parse_text(tokinizer.config, unit_text) {
+        Some(unit) => unit,
+        None => return
+    };
+
+    let word_start = tokinizer.token_infos[word_index].start;
+    let unit_start = unit_start + (unit_text.len() - unit_text.trim_start().len());
+    for token in tokinizer.token_infos[word_index..].iter() {
+        token.status.set(TokenInfoStatus::Removed);
+    }
+
+    tokinizer.ui_tokens.update_tokens(unit_start, tokinizer.data.trim_end().len(), UiTokenType::Symbol2);
+    tokinizer.conversion = Some((Rc::new(unit), Span::new(&tokinizer.data, word_start, tokinizer.data.trim_end().len())));
+}
@@ -0,0 +1,177 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use alloc::string::{String, ToString};
+use core::{ops::Deref, cell::{RefCell, Cell}};
+
+use crate::compiler::{incompatible_types, DataItem, OperationType};
+use crate::compiler::number::NumberItem;
+use crate::compiler::money::add_currency_conversion;
+use crate::config::SmartCalcConfig;
+use crate::constants::ConstantType;
+use crate::error::{ErrorKind, SmartCalcError, Span};
+use crate::session::Session;
+use crate::types::{NumberType, SmartCalcAstType, TokenType};
+use crate::UiTokenType;
+
+use super::{Tokinizer, TokenInfoStatus, TokenInfo};
+
+fn get_line_item(session: &Session, index: usize) -> Option<Rc<dyn DataItem>> {
+    match session.get_line_result(index)?.deref() {
+        SmartCalcAstType::Item(item) => Some(item.clone()),
+        _ => None
+    }
+}
+
+fn previous_item(session: &Session) -> Option<Rc<dyn DataItem>> {
+    (0..session.current_line_index()).rev().find_map(|index| get_line_item(session, index))
+}
+
+/* Lines above current line, until the first blank line */
+fn block_items(session: &Session) -> Vec<Rc<dyn DataItem>> {
+    let mut items = Vec::new();
+    for index in (0..session.current_line_index()).rev() {
+        if session.is_blank_line(index) {
+            break;
+        }
+
+        if let Some(item) = get_line_item(session, index) {
+            items.insert(0, item);
+        }
+    }
+    items
+}
+
+/* Total is calculated with first line's unit or currency, total of no lines is zero */
+fn sum_items(config: &SmartCalcConfig, items: &[Rc<dyn DataItem>]) -> Result<(Rc<dyn DataItem>, usize), ErrorKind> {
+    let mut total = match items.first() {
+        Some(item) => item.clone(),
+        None => return Ok((Rc::new(NumberItem(0.0.into(), NumberType::Decimal)), 0))
+    };
+
+    for item in items.iter().skip(1) {
+        total = match total.calculate(config, true, item.deref(), OperationType::Add) {
+            Some(result) => result,
+            None => match item.calculate(config, false, total.deref(), OperationType::Add) {
+                Some(result) => result,
+                None => return Err(incompatible_types(total.deref(), item.deref()))
+            }
+        };
+    }
+
+    Ok((total, items.len()))
+}
+
+fn average_items(config: &SmartCalcConfig, items: &[Rc<dyn DataItem>], name: &str) -> Result<Rc<dyn DataItem>, ErrorKind> {
+    match sum_items(config, items)? {
+        (_, 0) => Err(ErrorKind::UndefinedResult { function: name.to_string() }),
+        (total, count) => total.calculate(config, true, &NumberItem((count as f64).into(), NumberType::Decimal), OperationType::Div)
+            .ok_or_else(|| incompatible_types(total.deref(), &NumberItem((count as f64).into(), NumberType::Decimal)))
+    }
+}
+
+/* "sum" is also an alias of the addition operator, it is a line reference only when it is alone */
+fn is_standalone(tokinizer: &Tokinizer, start: usize, index: usize) -> bool {
+    tokinizer.token_infos[start..].iter()
+        .enumerate()
+        .all(|(token_index, token)| start + token_index == index || token.status.get() != TokenInfoStatus::Active)
+}
+
+fn get_line_number(tokinizer: &Tokinizer, index: usize) -> Option<usize> {
+    match tokinizer.token_infos.get(index)?.token_type.borrow().deref() {
+        Some(TokenType::Number(number, _)) if number.to_f64() >= 1.0 && number.to_f64().fract() == 0.0 => Some(number.to_f64() as usize),
+        _ => None
+    }
+}
+
+pub fn line_reference_tokinizer(tokinizer: &mut Tokinizer) {
+    let constants = match tokinizer.config.constant_pair.get(&tokinizer.language) {
+        Some(constants) => constants,
+        None => return
+    };
+
+    let session = tokinizer.session;
+    let mut index = 0;
+
+    /* Variable name could be same with reference name */
+    for (token_index, token) in tokinizer.token_infos.iter().enumerate() {
+        if let Some(TokenType::Operator('=')) = &token.token_type.borrow().deref() {
+            index = token_index + 1;
+            break;
+        }
+    }
+
+    let start = index;
+    while index < tokinizer.token_infos.len() {
+        let token = &tokinizer.token_infos[index];
+        let constant_type = match token.token_type.borrow().deref() {
+            _ if token.status.get() != TokenInfoStatus::Active => None,
+            Some(TokenType::Text(text)) => constants.get(&text.to_lowercase()).cloned(),
+            Some(TokenType::Operator(_)) if is_standalone(tokinizer, start, index) => match constants.get(&token.original_text.to_lowercase()) {
+                Some(ConstantType::Sum) => Some(ConstantType::Sum),
+                _ => None
+            },
+            _ => None
+        };
+
+        if matches!(constant_type, Some(ConstantType::Previous | ConstantType::Sum | ConstantType::Average | ConstantType::Line)) {
+            session.set_line_reference_used();
+        }
+
+        let block_items = || {
+            let items = block_items(session);
+            add_currency_conversion(tokinizer.config, session, items.iter().map(|item| item.deref()));
+            items
+        };
+
+        let (item, total_token) = match constant_type {
+            Some(ConstantType::Previous) => (Ok(previous_item(session)), 1),
+            Some(ConstantType::Sum) => (sum_items(tokinizer.config, &block_items()).map(|(total, _)| Some(total)), 1),
+            Some(ConstantType::Average) => (average_items(tokinizer.config, &block_items(), &tokinizer.token_infos[index].original_text).map(Some), 1),
+            Some(ConstantType::Line) => match get_line_number(tokinizer, index + 1) {
+                Some(line_number) if line_number <= session.current_line_index() => (Ok(get_line_item(session, line_number - 1)), 2),
+                _ => (Ok(None), 1)
+            },
+            _ => (Ok(None), 1)
+        };
+
+        let item = match item {
+            Ok(Some(item)) => item,
+            Ok(None) => {
+                index += 1;
+                continue;
+            },
+            Err(error) => {
+                if tokinizer.rule_error.is_none() {
+                    let span = Span::new(&tokinizer.data, tokinizer.token_infos[index].start, tokinizer.token_infos[index].end);
+                    tokinizer.rule_error = Some(SmartCalcError::new(error, span));
+                }
+                index += 1;
+                continue;
+            }
+        };
+
+        let text_start_position = tokinizer.token_infos[index].start;
+        let text_end_position   = tokinizer.token_infos[index + total_token - 1].end;
+
+        for token_index in index..index + total_token {
+            tokinizer.token_infos[token_index].status.set(TokenInfoStatus::Removed);
+        }
+
+        tokinizer.ui_tokens.update_tokens(text_start_position, text_end_position, UiTokenType::VariableUse);
+        tokinizer.token_infos.insert(index, Rc::new(TokenInfo {
+            start: text_start_position,
+            end: text_end_position,
+            token_type: RefCell::new(Some(item.as_token_type())),
+            original_text: String::new(),
+            status: Cell::new(TokenInfoStatus::Active)
+        }));
+
+        index += total_token + 1;
+    }
+}
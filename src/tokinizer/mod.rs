@@ -8,12 +8,18 @@ mod regex_tokinizer;
 mod alias_tokinizer;
 mod rule_tokinizer;
 mod dynamic_type_tokinizer;
+mod constant_tokinizer;
+mod line_reference_tokinizer;
+mod conversion_tokinizer;
 mod tools;
 
 pub use self::regex_tokinizer::regex_tokinizer;
 pub use self::regex_tokinizer::language_tokinizer;
 pub use self::alias_tokinizer::alias_tokinizer;
 pub use self::dynamic_type_tokinizer::dynamic_type_tokinizer;
+pub use self::constant_tokinizer::constant_tokinizer;
+pub use self::line_reference_tokinizer::line_reference_tokinizer;
+pub use self::conversion_tokinizer::conversion_tokinizer;
 pub use self::tools::*;
 pub use self::rule_tokinizer::{rule_tokinizer, RuleType, RuleItemList, RULE_FUNCTIONS};
 
@@ -31,7 +37,9 @@ use regex::Match;
 use crate::UiTokenType;
 use crate::session::Session;
 use crate::config::SmartCalcConfig;
+use crate::compiler::compound_type::CompoundUnit;
 use crate::variable::update_token_variables;
+use crate::error::{SmartCalcError, Span};
 use crate::{token::ui_token::UiTokenCollection, types::*};
 
 
@@ -48,6 +56,10 @@ pub struct Tokinizer<'a> {
     pub language: String,
     pub token_infos: Vec<Rc<TokenInfo>>,
     pub tokens: Vec<Rc<TokenType>>,
+    pub token_spans: Vec<Span>,
+    pub rule_error: Option<SmartCalcError>,
+    /* Unit that the whole expression is converted into */
+    pub conversion: Option<(Rc<CompoundUnit>, Span)>
 }
 
 #[derive(Debug)]
@@ -86,7 +98,10 @@ impl<'a> Tokinizer<'a> {
             session,
             language: session.get_language(),
             token_infos: Vec::new(),
-            tokens: Vec::new()
+            tokens: Vec::new(),
+            token_spans: Vec::new(),
+            rule_error: None,
+            conversion: None
         }
     }
 
@@ -103,7 +118,10 @@ impl<'a> Tokinizer<'a> {
             session,
             language: session.get_language(),
             token_infos: Vec::new(),
-            tokens: Vec::new()
+            tokens: Vec::new(),
+            token_spans: Vec::new(),
+            rule_error: None,
+            conversion: None
         };
 
         language_tokinizer(&mut tokinizer);
@@ -121,8 +139,14 @@ impl<'a> Tokinizer<'a> {
         log::debug!(" > alias_tokinizer");
         update_token_variables(self);
         log::debug!(" > update_token_variables");
+        line_reference_tokinizer(self);
+        log::debug!(" > line_reference_tokinizer");
         dynamic_type_tokinizer(self);
         log::debug!(" > dynamic_type_tokinizer");
+        conversion_tokinizer(self);
+        log::debug!(" > conversion_tokinizer");
+        constant_tokinizer(self);
+        log::debug!(" > constant_tokinizer");
         rule_tokinizer(self);
         log::debug!(" > rule_tokinizer");
 
@@ -182,13 +206,22 @@ impl<'a> Tokinizer<'a> {
         for token_location in self.token_infos.iter() {
             if token_location.status.get() == TokenInfoStatus::Active {
                 if let Some(token_type) = &token_location.token_type.borrow().deref() {
-                    tokens.push(token_type.clone());
+                    tokens.push((token_type.clone(), Span::new(&self.data, token_location.start, token_location.end)));
                 }
             }
         }
         
-        for token in tokens {
+        for (token, span) in tokens {
             self.tokens.push(Rc::new(token));
+            self.token_spans.push(span);
+        }
+    }
+
+    /* Zero length span at the start of the token, used for generated tokens */
+    fn token_start_span(&self, index: usize) -> Span {
+        match self.token_spans.get(index) {
+            Some(span) => Span { end: span.start, char_end: span.char_start, ..*span },
+            None => Span::new(&self.data, self.data.len(), self.data.len())
         }
     }
 
@@ -203,14 +236,22 @@ impl<'a> Tokinizer<'a> {
 
         while index < self.tokens.len() {
             match self.tokens[index].deref() {
+                /* Function names are required by syntax parser */
+                TokenType::Text(text) if self.is_function_call(index, text) => index += 1,
                 TokenType::Text(_) => {
                     self.tokens.remove(index);
+                    self.token_spans.remove(index);
                 },
                 _ => index += 1
             };
         }
     }
 
+    fn is_function_call(&self, index: usize, text: &str) -> bool {
+        let is_next_parenthesis = matches!(self.tokens.get(index + 1).map(|token| token.deref()), Some(TokenType::Operator('(')));
+        is_next_parenthesis && self.config.functions.contains_key(&text.to_lowercase())
+    }
+
     fn missing_token_adder(&mut self) {
         let mut index = 0;
         
@@ -240,7 +281,8 @@ impl<'a> Tokinizer<'a> {
         let mut operator_required = false;
 
         if let TokenType::Operator(_) = self.tokens[index].deref() {
-            self.tokens.insert(index, Rc::new(TokenType::Number(0.0, NumberType::Decimal)));
+            self.token_spans.insert(index, self.token_start_span(index));
+            self.tokens.insert(index, Rc::new(TokenType::Number(0.0.into(), NumberType::Decimal)));
         }
 
         while index < self.tokens.len() {
@@ -249,6 +291,7 @@ impl<'a> Tokinizer<'a> {
                 _ => {
                     if operator_required {
                         log::debug!("Added missing operator between two token");
+                        self.token_spans.insert(index, self.token_start_span(index));
                         self.tokens.insert(index, Rc::new(TokenType::Operator('+')));
                         index += 1;
                     }
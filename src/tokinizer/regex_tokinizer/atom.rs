@@ -14,6 +14,7 @@ use chrono::Utc;
 use crate::config::SmartCalcConfig;
 use crate::types::*;
 use crate::tokinizer::Tokinizer;
+use crate::tools::parse_number;
 use chrono::NaiveTime;
 use regex::Regex;
 
@@ -37,7 +38,7 @@ pub fn get_atom(config: &SmartCalcConfig, data: &str, group_item: &[Regex]) -> V
                 "MONEY" => {
                     let splited_data: Vec<&str> = data.split(';').collect();
                     match config.get_currency(splited_data[1].to_string()) {
-                        Some(currency_info) => TokenType::Money(splited_data[0].parse::<f64>().unwrap(), currency_info.clone()),
+                        Some(currency_info) => TokenType::Money(parse_number(config, splited_data[0]).unwrap(), currency_info.clone()),
                         None => {
                             log::info!("Currency information not found, {}", splited_data[1]);
                             continue
@@ -45,7 +46,7 @@ pub fn get_atom(config: &SmartCalcConfig, data: &str, group_item: &[Regex]) -> V
                     }
                 },
                 "NUMBER" => {
-                    let number = data.parse::<f64>().unwrap();
+                    let number = parse_number(config, data).unwrap();
                     TokenType::Number(number, NumberType::Decimal)
                 },
                 "PERCENT" => {
@@ -103,9 +104,9 @@ fn operator_test() {
 
     assert_eq!(tokens[3].start, 43);
     assert_eq!(tokens[3].end, 60);
-    assert_eq!(tokens[3].token_type.borrow().deref(), &Some(TokenType::Number(-222.333, NumberType::Decimal)));
+    assert_eq!(tokens[3].token_type.borrow().deref(), &Some(TokenType::Number((-222.333).into(), NumberType::Decimal)));
 
     assert_eq!(tokens[4].start, 61);
     assert_eq!(tokens[4].end, 76);
-    assert_eq!(tokens[4].token_type.borrow().deref(), &Some(TokenType::Money(200.0, config.get_currency("try".to_string()).unwrap())));
+    assert_eq!(tokens[4].token_type.borrow().deref(), &Some(TokenType::Money(200.0.into(), config.get_currency("try".to_string()).unwrap())));
 }
@@ -0,0 +1,59 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use alloc::borrow::ToOwned;
+use chrono::NaiveDate;
+use regex::Regex;
+use crate::config::SmartCalcConfig;
+use crate::tokinizer::Tokinizer;
+use crate::types::TokenType;
+use crate::token::ui_token::UiTokenType;
+
+pub fn date_regex_parser(config: &SmartCalcConfig, tokinizer: &mut Tokinizer, group_item: &[Regex]) {
+    for re in group_item.iter() {
+        for capture in re.captures_iter(&tokinizer.data.to_owned()) {
+            let year  = capture.name("year").unwrap().as_str().parse::<i32>().unwrap();
+            let month = capture.name("month").unwrap().as_str().parse::<u32>().unwrap();
+            let day   = capture.name("day").unwrap().as_str().parse::<u32>().unwrap();
+
+            let date = match NaiveDate::from_ymd_opt(year, month, day) {
+                Some(date) => date,
+                None => continue
+            };
+
+            if tokinizer.add_token_from_match(&capture.get(0), Some(TokenType::Date(date, config.get_time_offset()))) {
+                tokinizer.add_uitoken_from_match(capture.get(0), UiTokenType::DateTime);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn date_test() {
+    use core::ops::Deref;
+    use alloc::string::ToString;
+    use crate::tokinizer::regex_tokinizer;
+    use crate::tokinizer::test::setup_tokinizer;
+    use crate::config::SmartCalcConfig;
+    use crate::session::Session;
+    let mut session = Session::new();
+    let config = SmartCalcConfig::default();
+    let mut tokinizer_mut = setup_tokinizer("2021-03-01 2020-1-5 2021-02-30".to_string(), &mut session, &config);
+
+    regex_tokinizer(&mut tokinizer_mut);
+    let tokens = &tokinizer_mut.token_infos;
+
+    assert_eq!(tokens[0].start, 0);
+    assert_eq!(tokens[0].end, 10);
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Date(NaiveDate::from_ymd_opt(2021, 3, 1).unwrap(), config.get_time_offset())));
+
+    assert_eq!(tokens[1].start, 11);
+    assert_eq!(tokens[1].end, 19);
+    assert_eq!(tokens[1].token_type.borrow().deref(), &Some(TokenType::Date(NaiveDate::from_ymd_opt(2020, 1, 5).unwrap(), config.get_time_offset())));
+
+    assert_ne!(tokens[2].token_type.borrow().deref(), &Some(TokenType::Date(NaiveDate::from_ymd_opt(2021, 3, 2).unwrap(), config.get_time_offset())));
+}
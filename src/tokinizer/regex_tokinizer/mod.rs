@@ -19,14 +19,17 @@ mod field;
 mod percent;
 mod atom;
 mod time;
+mod date;
 mod money;
 mod comment;
 mod month;
 mod timezone;
+mod zone;
 
 use crate::SmartCalcConfig;
 
 pub use self::time::time_regex_parser;
+pub use self::date::date_regex_parser;
 pub use self::number::number_regex_parser;
 pub use self::percent::percent_regex_parser;
 pub use self::money::money_regex_parser;
@@ -36,6 +39,7 @@ pub use self::atom::{atom_regex_parser, get_atom};
 pub use self::whitespace::whitespace_regex_parser;
 pub use self::comment::comment_regex_parser;
 pub use self::timezone::timezone_regex_parser;
+pub use self::zone::zone_regex_parser;
 pub use self::month::month_parser;
 pub use self::operator::operator_regex_parser;
 
@@ -51,9 +55,11 @@ lazy_static! {
         let m = vec![
         ("comment",    comment_regex_parser    as RegexParser),
         ("field",      field_regex_parser      as RegexParser),
+        ("date",       date_regex_parser       as RegexParser),
         ("money",      money_regex_parser      as RegexParser),
         ("atom",       atom_regex_parser       as RegexParser),
         ("percent",    percent_regex_parser    as RegexParser),
+        ("zone",       zone_regex_parser       as RegexParser),
         ("timezone",   timezone_regex_parser   as RegexParser),
         ("time",       time_regex_parser       as RegexParser),
         ("number",     number_regex_parser     as RegexParser),
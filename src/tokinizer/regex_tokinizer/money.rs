@@ -11,22 +11,28 @@ use crate::config::SmartCalcConfig;
 use crate::tokinizer::{Tokinizer, read_currency};
 use crate::types::{TokenType};
 use crate::token::ui_token::{UiTokenType};
+use crate::compiler::OperationType;
+use crate::tools::{calculate_number, parse_number};
 
 pub fn money_regex_parser(config: &SmartCalcConfig, tokinizer: &mut Tokinizer, group_item: &[Regex]) {
     for re in group_item.iter() {
         for capture in re.captures_iter(&tokinizer.data.to_owned()) {
             /* Check price value */
-            let price = match capture.name("PRICE").unwrap().as_str().replace(&config.thousand_separator[..], "").replace(&config.decimal_seperator[..], ".").parse::<f64>() {
-                Ok(price) => match capture.name("NOTATION") {
-                    Some(notation) => price * match notation.as_str() {
-                        "k" | "K" => 1_000.0,
-                        "M" => 1_000_000.0,
-                        "G" => 1_000_000_000.0,
-                        "T" => 1_000_000_000_000.0,
-                        "P" => 1_000_000_000_000_000.0,
-                        "Z" => 1_000_000_000_000_000_000.0,
-                        "Y" => 1_000_000_000_000_000_000_000.0,
-                        _ => 1.0
+            let price = capture.name("PRICE").unwrap().as_str().replace(&config.thousand_separator[..], "").replace(&config.decimal_seperator[..], ".");
+            let price = match parse_number(config, &price) {
+                Some(price) => match capture.name("NOTATION") {
+                    Some(notation) => {
+                        let multiplier = match notation.as_str() {
+                            "k" | "K" => 1_000.0,
+                            "M" => 1_000_000.0,
+                            "G" => 1_000_000_000.0,
+                            "T" => 1_000_000_000_000.0,
+                            "P" => 1_000_000_000_000_000.0,
+                            "Z" => 1_000_000_000_000_000_000.0,
+                            "Y" => 1_000_000_000_000_000_000_000.0,
+                            _ => 1.0
+                        };
+                        calculate_number(config, price, multiplier.into(), OperationType::Mul)
                     },
                     _ => price
                 },
@@ -39,6 +45,11 @@ pub fn money_regex_parser(config: &SmartCalcConfig, tokinizer: &mut Tokinizer, g
                 _ => continue
             };
 
+            /* Lowercase unit names (e.g. cup) win over currency codes, the code still works in uppercase */
+            if currency.chars().all(|ch| !ch.is_uppercase()) && config.find_dynamic_type(currency).is_some() {
+                continue;
+            }
+
             let currency = match read_currency(config, currency) {
                 Some(real_currency) => real_currency,
                 _ => continue
@@ -76,27 +87,27 @@ fn money_test_1() {
     assert_eq!(tokens.len(), 6);
     assert_eq!(tokens[0].start, 0);
     assert_eq!(tokens[0].end, 7);
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(1000.0, config.get_currency("try".to_string()).unwrap())));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(1000.0.into(), config.get_currency("try".to_string()).unwrap())));
     
     assert_eq!(tokens[1].start, 8);
     assert_eq!(tokens[1].end, 15);
-    assert_eq!(tokens[1].token_type.borrow().deref(), &Some(TokenType::Money(1000.0, config.get_currency("try".to_string()).unwrap())));
+    assert_eq!(tokens[1].token_type.borrow().deref(), &Some(TokenType::Money(1000.0.into(), config.get_currency("try".to_string()).unwrap())));
     
     assert_eq!(tokens[2].start, 16);
     assert_eq!(tokens[2].end, 24);
-    assert_eq!(tokens[2].token_type.borrow().deref(), &Some(TokenType::Money(1000.0, config.get_currency("try".to_string()).unwrap())));
+    assert_eq!(tokens[2].token_type.borrow().deref(), &Some(TokenType::Money(1000.0.into(), config.get_currency("try".to_string()).unwrap())));
     
     assert_eq!(tokens[3].start, 25);
     assert_eq!(tokens[3].end, 32);
-    assert_eq!(tokens[3].token_type.borrow().deref(), &Some(TokenType::Money(1000.0, config.get_currency("try".to_string()).unwrap())));
+    assert_eq!(tokens[3].token_type.borrow().deref(), &Some(TokenType::Money(1000.0.into(), config.get_currency("try".to_string()).unwrap())));
     
     assert_eq!(tokens[4].start, 33);
     assert_eq!(tokens[4].end, 41);
-    assert_eq!(tokens[4].token_type.borrow().deref(), &Some(TokenType::Money(1000.0, config.get_currency("try".to_string()).unwrap())));
+    assert_eq!(tokens[4].token_type.borrow().deref(), &Some(TokenType::Money(1000.0.into(), config.get_currency("try".to_string()).unwrap())));
     
     assert_eq!(tokens[5].start, 42);
     assert_eq!(tokens[5].end, 49);
-    assert_eq!(tokens[5].token_type.borrow().deref(), &Some(TokenType::Money(1000.0, config.get_currency("try".to_string()).unwrap())));
+    assert_eq!(tokens[5].token_type.borrow().deref(), &Some(TokenType::Money(1000.0.into(), config.get_currency("try".to_string()).unwrap())));
 }
 
 #[cfg(test)]
@@ -117,5 +128,5 @@ fn money_test_2() {
     assert_eq!(tokens.len(), 1);
     assert_eq!(tokens[0].start, 0);
     assert_eq!(tokens[0].end, 3);
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(2000.0, config.get_currency("usd".to_string()).unwrap())));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(2000.0.into(), config.get_currency("usd".to_string()).unwrap())));
 }
@@ -4,6 +4,7 @@
  * Licensed under the GNU General Public License v2.0.
  */
 
+use alloc::format;
 use alloc::string::ToString;
 use alloc::borrow::ToOwned;
 use crate::config::SmartCalcConfig;
@@ -11,6 +12,9 @@ use crate::types::*;
 use crate::tokinizer::{Tokinizer};
 use regex::Regex;
 use crate::token::ui_token::{UiTokenType};
+use crate::compiler::OperationType;
+use crate::tools::{calculate_number, parse_number};
+use crate::error::{ErrorKind, SmartCalcError, Span};
 
 pub fn number_regex_parser(config: &SmartCalcConfig, tokinizer: &mut Tokinizer, group_item: &[Regex]) {
     for re in group_item.iter() {
@@ -18,38 +22,48 @@ pub fn number_regex_parser(config: &SmartCalcConfig, tokinizer: &mut Tokinizer,
             let mut parse_end = 0;
             let mut number_match = None;
             let mut notation_match = None;
+            let mut exponent_match = None;
 
             /* Check price value */
-            let mut number = 0.0;
+            let mut number = NumberValue::Float(0.0);
             let mut number_type = NumberType::Decimal;
 
             if let Some(binary) = capture.name("BINARY") {
                 parse_end = binary.end();
-                number = i64::from_str_radix(binary.as_str(), 2).unwrap() as f64;
+                number = (i64::from_str_radix(binary.as_str(), 2).unwrap() as f64).into();
                 number_type = NumberType::Binary;
                 number_match = capture.name("BINARY_FULL");
             }
             else if let Some(hex) = capture.name("HEX") { 
                 parse_end = hex.end();
-                number = i64::from_str_radix(hex.as_str(), 16).unwrap() as f64;
+                number = (i64::from_str_radix(hex.as_str(), 16).unwrap() as f64).into();
                 number_type = NumberType::Hexadecimal;
                 number_match = capture.name("HEX_FULL");
             }
             else if let Some(octal) = capture.name("OCTAL") { 
                 parse_end = octal.end();
-                number = i64::from_str_radix(octal.as_str(), 8).unwrap() as f64;
+                number = (i64::from_str_radix(octal.as_str(), 8).unwrap() as f64).into();
                 number_type = NumberType::Octal;
                 number_match = capture.name("OCTAL_FULL");
             }
             else if let Some(decimal) = capture.name("DECIMAL") {
                 parse_end = decimal.end();
-                number = match decimal.as_str().replace(&config.thousand_separator[..], "").replace(&config.decimal_seperator[..], ".").parse::<f64>() {
-                    Ok(num) => {
+
+                /* Scientific notation, "1,5e3" is "1.500" */
+                let mut number_text = decimal.as_str().replace(&config.thousand_separator[..], "").replace(&config.decimal_seperator[..], ".");
+                if let (Some(exponent), Some(exponent_value)) = (capture.name("EXPONENT"), capture.name("EXPONENT_VALUE")) {
+                    parse_end = exponent.end();
+                    exponent_match = Some(exponent);
+                    number_text = format!("{}e{}", number_text, exponent_value.as_str());
+                }
+
+                number = match parse_number(config, &number_text) {
+                    Some(num) => {
                         number_match = Some(decimal);
                         match capture.name("NOTATION") {
                             Some(notation) => {
                                 notation_match = Some(notation);
-                                num * match notation.as_str() {
+                                let multiplier = match notation.as_str() {
                                     "k" | "K" => 1_000.0,
                                     "M" => 1_000_000.0,
                                     "G" => 1_000_000_000.0,
@@ -58,17 +72,25 @@ pub fn number_regex_parser(config: &SmartCalcConfig, tokinizer: &mut Tokinizer,
                                     "Z" => 1_000_000_000_000_000_000.0,
                                     "Y" => 1_000_000_000_000_000_000_000.0,
                                     _ => 1.0
-                                }
+                                };
+                                calculate_number(config, num, multiplier.into(), OperationType::Mul)
                             },
                             _ => num
                         }
                     },
                     _ => continue
                 };
+
+                /* "1e400" could not be represented */
+                if number.to_f64().is_infinite() && tokinizer.rule_error.is_none() {
+                    let span = Span::new(&tokinizer.data, decimal.start(), parse_end);
+                    tokinizer.rule_error = Some(SmartCalcError::new(ErrorKind::Overflow, span));
+                }
             }
 
             if tokinizer.add_token_location(capture.get(0).unwrap().start(), parse_end, Some(TokenType::Number(number, number_type)), capture.get(0).unwrap().as_str().to_string()) {
                 tokinizer.add_uitoken_from_match(number_match, UiTokenType::Number);
+                tokinizer.add_uitoken_from_match(exponent_match, UiTokenType::Number);
                 tokinizer.add_uitoken_from_match(notation_match, UiTokenType::Symbol2);
             }
         }
@@ -95,19 +117,19 @@ fn number_test_1() {
     assert_eq!(tokens.len(), 4);
     assert_eq!(tokens[0].start, 0);
     assert_eq!(tokens[0].end, 4);
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(1024.0, NumberType::Decimal)));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(1024.0.into(), NumberType::Decimal)));
     
     assert_eq!(tokens[1].start, 5);
     assert_eq!(tokens[1].end, 10);
-    assert_eq!(tokens[1].token_type.borrow().deref(), &Some(TokenType::Number(-1024.0, NumberType::Decimal)));
+    assert_eq!(tokens[1].token_type.borrow().deref(), &Some(TokenType::Number((-1024.0).into(), NumberType::Decimal)));
     
     assert_eq!(tokens[2].start, 11);
     assert_eq!(tokens[2].end, 17);
-    assert_eq!(tokens[2].token_type.borrow().deref(), &Some(TokenType::Number(1024.1, NumberType::Decimal)));
+    assert_eq!(tokens[2].token_type.borrow().deref(), &Some(TokenType::Number(1024.1.into(), NumberType::Decimal)));
     
     assert_eq!(tokens[3].start, 18);
     assert_eq!(tokens[3].end, 25);
-    assert_eq!(tokens[3].token_type.borrow().deref(), &Some(TokenType::Number(-1024.1, NumberType::Decimal)));
+    assert_eq!(tokens[3].token_type.borrow().deref(), &Some(TokenType::Number((-1024.1).into(), NumberType::Decimal)));
 }
 
 #[cfg(test)]
@@ -128,27 +150,27 @@ fn number_test_2() {
     assert_eq!(tokens.len(), 6);
     assert_eq!(tokens[0].start, 0);
     assert_eq!(tokens[0].end, 5);
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(256.0, NumberType::Hexadecimal)));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(256.0.into(), NumberType::Hexadecimal)));
     
     assert_eq!(tokens[1].start, 6);
     assert_eq!(tokens[1].end, 11);
-    assert_eq!(tokens[1].token_type.borrow().deref(), &Some(TokenType::Number(256.0, NumberType::Hexadecimal)));
+    assert_eq!(tokens[1].token_type.borrow().deref(), &Some(TokenType::Number(256.0.into(), NumberType::Hexadecimal)));
     
     assert_eq!(tokens[2].start, 12);
     assert_eq!(tokens[2].end, 15);
-    assert_eq!(tokens[2].token_type.borrow().deref(), &Some(TokenType::Number(1.0, NumberType::Hexadecimal)));
+    assert_eq!(tokens[2].token_type.borrow().deref(), &Some(TokenType::Number(1.0.into(), NumberType::Hexadecimal)));
     
     assert_eq!(tokens[3].start, 16);
     assert_eq!(tokens[3].end, 19);
-    assert_eq!(tokens[3].token_type.borrow().deref(), &Some(TokenType::Number(1.0, NumberType::Hexadecimal)));
+    assert_eq!(tokens[3].token_type.borrow().deref(), &Some(TokenType::Number(1.0.into(), NumberType::Hexadecimal)));
     
     assert_eq!(tokens[4].start, 20);
     assert_eq!(tokens[4].end, 23);
-    assert_eq!(tokens[4].token_type.borrow().deref(), &Some(TokenType::Number(0.0, NumberType::Hexadecimal)));
+    assert_eq!(tokens[4].token_type.borrow().deref(), &Some(TokenType::Number(0.0.into(), NumberType::Hexadecimal)));
     
     assert_eq!(tokens[5].start, 24);
     assert_eq!(tokens[5].end, 27);
-    assert_eq!(tokens[5].token_type.borrow().deref(), &Some(TokenType::Number(0.0, NumberType::Hexadecimal)));
+    assert_eq!(tokens[5].token_type.borrow().deref(), &Some(TokenType::Number(0.0.into(), NumberType::Hexadecimal)));
 }
 
 #[cfg(test)]
@@ -169,27 +191,27 @@ fn number_test_3() {
     assert_eq!(tokens.len(), 6);
     assert_eq!(tokens[0].start, 0);
     assert_eq!(tokens[0].end, 5);
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(4.0, NumberType::Binary)));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(4.0.into(), NumberType::Binary)));
     
     assert_eq!(tokens[1].start, 6);
     assert_eq!(tokens[1].end, 11);
-    assert_eq!(tokens[1].token_type.borrow().deref(), &Some(TokenType::Number(4.0, NumberType::Binary)));
+    assert_eq!(tokens[1].token_type.borrow().deref(), &Some(TokenType::Number(4.0.into(), NumberType::Binary)));
     
     assert_eq!(tokens[2].start, 12);
     assert_eq!(tokens[2].end, 15);
-    assert_eq!(tokens[2].token_type.borrow().deref(), &Some(TokenType::Number(1.0, NumberType::Binary)));
+    assert_eq!(tokens[2].token_type.borrow().deref(), &Some(TokenType::Number(1.0.into(), NumberType::Binary)));
     
     assert_eq!(tokens[3].start, 16);
     assert_eq!(tokens[3].end, 19);
-    assert_eq!(tokens[3].token_type.borrow().deref(), &Some(TokenType::Number(1.0, NumberType::Binary)));
+    assert_eq!(tokens[3].token_type.borrow().deref(), &Some(TokenType::Number(1.0.into(), NumberType::Binary)));
     
     assert_eq!(tokens[4].start, 20);
     assert_eq!(tokens[4].end, 23);
-    assert_eq!(tokens[4].token_type.borrow().deref(), &Some(TokenType::Number(0.0, NumberType::Binary)));
+    assert_eq!(tokens[4].token_type.borrow().deref(), &Some(TokenType::Number(0.0.into(), NumberType::Binary)));
     
     assert_eq!(tokens[5].start, 24);
     assert_eq!(tokens[5].end, 27);
-    assert_eq!(tokens[5].token_type.borrow().deref(), &Some(TokenType::Number(0.0, NumberType::Binary)));
+    assert_eq!(tokens[5].token_type.borrow().deref(), &Some(TokenType::Number(0.0.into(), NumberType::Binary)));
 }
 
 
@@ -211,25 +233,25 @@ fn number_test_4() {
     assert_eq!(tokens.len(), 6);
     assert_eq!(tokens[0].start, 0);
     assert_eq!(tokens[0].end, 5);
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(64.0, NumberType::Octal)));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(64.0.into(), NumberType::Octal)));
     
     assert_eq!(tokens[1].start, 6);
     assert_eq!(tokens[1].end, 11);
-    assert_eq!(tokens[1].token_type.borrow().deref(), &Some(TokenType::Number(64.0, NumberType::Octal)));
+    assert_eq!(tokens[1].token_type.borrow().deref(), &Some(TokenType::Number(64.0.into(), NumberType::Octal)));
     
     assert_eq!(tokens[2].start, 12);
     assert_eq!(tokens[2].end, 15);
-    assert_eq!(tokens[2].token_type.borrow().deref(), &Some(TokenType::Number(1.0, NumberType::Octal)));
+    assert_eq!(tokens[2].token_type.borrow().deref(), &Some(TokenType::Number(1.0.into(), NumberType::Octal)));
     
     assert_eq!(tokens[3].start, 16);
     assert_eq!(tokens[3].end, 19);
-    assert_eq!(tokens[3].token_type.borrow().deref(), &Some(TokenType::Number(1.0, NumberType::Octal)));
+    assert_eq!(tokens[3].token_type.borrow().deref(), &Some(TokenType::Number(1.0.into(), NumberType::Octal)));
     
     assert_eq!(tokens[4].start, 20);
     assert_eq!(tokens[4].end, 23);
-    assert_eq!(tokens[4].token_type.borrow().deref(), &Some(TokenType::Number(0.0, NumberType::Octal)));
+    assert_eq!(tokens[4].token_type.borrow().deref(), &Some(TokenType::Number(0.0.into(), NumberType::Octal)));
     
     assert_eq!(tokens[5].start, 24);
     assert_eq!(tokens[5].end, 27);
-    assert_eq!(tokens[5].token_type.borrow().deref(), &Some(TokenType::Number(0.0, NumberType::Octal)));
+    assert_eq!(tokens[5].token_type.borrow().deref(), &Some(TokenType::Number(0.0.into(), NumberType::Octal)));
 }
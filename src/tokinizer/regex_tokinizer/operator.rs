@@ -14,7 +14,13 @@ use crate::token::ui_token::UiTokenType;
 pub fn operator_regex_parser(_: &SmartCalcConfig, tokinizer: &mut Tokinizer, group_item: &[Regex]) {
     for re in group_item.iter() {
         for capture in re.captures_iter(&tokinizer.data.to_owned()) {
-            if tokinizer.add_token_from_match(&capture.get(0), Some(TokenType::Operator(capture.get(0).unwrap().as_str().chars().next().unwrap())))  {
+            let operator = match capture.get(0).unwrap().as_str() {
+                "**" => '^',
+                "//" => '\\',
+                operator => operator.chars().next().unwrap()
+            };
+
+            if tokinizer.add_token_from_match(&capture.get(0), Some(TokenType::Operator(operator)))  {
                 tokinizer.add_uitoken_from_match(capture.get(0), UiTokenType::Operator);
             }
         }
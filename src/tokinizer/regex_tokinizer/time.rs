@@ -9,11 +9,9 @@ use alloc::borrow::ToOwned;
 use regex::Regex;
 use crate::config::SmartCalcConfig;
 use crate::tokinizer::Tokinizer;
-use crate::types::{TokenType, TimeOffset};
+use crate::types::TokenType;
 use crate::token::ui_token::{UiTokenType};
-use chrono::{Utc, FixedOffset, Datelike};
-
-use chrono::{TimeZone};
+use chrono::Utc;
 
 pub fn time_regex_parser(config: &SmartCalcConfig, tokinizer: &mut Tokinizer, group_item: &[Regex]) {
     for re in group_item.iter() {
@@ -43,15 +41,13 @@ pub fn time_regex_parser(config: &SmartCalcConfig, tokinizer: &mut Tokinizer, gr
                 end_position = meridiem.end();
             }
             
-            let time_offset = TimeOffset {
-                name: config.timezone.to_string(),
-                offset: config.timezone_offset
+            let time_offset = config.get_time_offset();
+            let date = time_offset.to_local(&Utc::now().naive_utc()).date();
+            let date_as_utc = match date.and_hms_opt(hour as u32, minute as u32, second as u32) {
+                Some(datetime) => time_offset.to_utc(&datetime),
+                None => continue
             };
             
-            let date = Utc::today().naive_utc();
-            let datetime = FixedOffset::east(time_offset.offset * 60).ymd(date.year(), date.month(), date.day()).and_hms(hour as u32, minute as u32, second as u32);
-            let date_as_utc = Utc.from_utc_datetime(&datetime.naive_utc()).naive_utc();
-            
             if tokinizer.add_token_location(capture.get(0).unwrap().start(), end_position, Some(TokenType::Time(date_as_utc, time_offset)), capture.get(0).unwrap().as_str().to_string()) {
                 tokinizer.add_uitoken_from_match(capture.get(0), UiTokenType::DateTime);
             }
@@ -64,6 +60,7 @@ pub fn time_regex_parser(config: &SmartCalcConfig, tokinizer: &mut Tokinizer, gr
 fn time_test() {
     use core::ops::Deref;
     use chrono::NaiveTime;
+    use crate::types::TimeOffset;
 
     use crate::tokinizer::regex_tokinizer;
     use crate::tokinizer::test::setup_tokinizer;
@@ -122,7 +119,8 @@ fn time_test() {
         assert_eq!(time.time(), NaiveTime::from_hms(1, 0, 0));
         assert_eq!(timezone, TimeOffset {
             name: "UTC".to_string(),
-            offset: 0
+            offset: 0,
+            zone: None
         });
     } else {
         assert_eq!(false, true);
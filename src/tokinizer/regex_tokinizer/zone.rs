@@ -0,0 +1,85 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use alloc::borrow::ToOwned;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use regex::Regex;
+use crate::config::SmartCalcConfig;
+use crate::tokinizer::Tokinizer;
+use crate::types::{TokenType, TimeOffset};
+use crate::token::ui_token::UiTokenType;
+
+/* IANA zone names like "Europe/Istanbul", city and country names like "New York", longest known name is used */
+pub fn zone_regex_parser(config: &SmartCalcConfig, tokinizer: &mut Tokinizer, group_item: &[Regex]) {
+    for re in group_item.iter() {
+        for capture in re.captures_iter(&tokinizer.data.to_owned()) {
+            let zone_match = match capture.name("zone") {
+                Some(zone_match) => zone_match,
+                None => continue
+            };
+
+            /* Every word could start a new name, "New York Berlin" has two cities */
+            let text = zone_match.as_str();
+            let words = text.split(' ').collect::<Vec<_>>();
+            let mut index = 0;
+            let mut word_start = 0;
+
+            while index < words.len() {
+                let zone = (index + 1..=words.len()).rev().find_map(|end| {
+                    let name = words[index..end].join(" ");
+                    config.find_location(&tokinizer.language, &name).map(|zone| (zone, end, name.len()))
+                });
+
+                let (end, length) = match zone {
+                    Some((zone, end, length)) => {
+                        let time_offset = TimeOffset::from_zone(zone);
+                        let start = zone_match.start() + word_start;
+                        let name = &text[word_start..word_start + length];
+                        if tokinizer.add_token_location(start, start + length, Some(TokenType::Timezone(time_offset.name, time_offset.offset)), name.to_string()) {
+                            /* Ui token positions are character positions */
+                            let char_start = tokinizer.data[..start].chars().count();
+                            tokinizer.ui_tokens.add(char_start, char_start + name.chars().count(), UiTokenType::Symbol1);
+                        }
+                        (end, length)
+                    },
+                    None => (index + 1, words[index].len())
+                };
+
+                word_start += length + 1;
+                index = end;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn zone_test() {
+    use core::ops::Deref;
+    use crate::tokinizer::regex_tokinizer;
+    use crate::tokinizer::test::setup_tokinizer;
+    use crate::session::Session;
+    let mut session = Session::new();
+    let config = SmartCalcConfig::default();
+    let mut tokinizer_mut = setup_tokinizer("Europe/Istanbul america/new_york New York Berlin Time North".to_string(), &mut session, &config);
+
+    regex_tokinizer(&mut tokinizer_mut);
+    let tokens = &tokinizer_mut.token_infos;
+    let zone_name = |index: usize| match tokens[index].token_type.borrow().deref() {
+        Some(TokenType::Timezone(name, _)) => Some(name.to_string()),
+        _ => None
+    };
+
+    assert_eq!((tokens[0].start, tokens[0].end), (0, 15));
+    assert_eq!(zone_name(0), Some("Europe/Istanbul".to_string()));
+    assert_eq!(zone_name(1), Some("America/New_York".to_string()));
+    assert_eq!((tokens[2].start, tokens[2].end), (33, 41));
+    assert_eq!(zone_name(2), Some("America/New_York".to_string()));
+    assert_eq!(zone_name(3), Some("Europe/Berlin".to_string()));
+    assert_eq!(zone_name(4), None);
+    assert_eq!(zone_name(5), None);
+}
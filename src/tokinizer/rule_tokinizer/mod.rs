@@ -21,6 +21,7 @@ use crate::UiTokenType;
 use crate::types::TokenType;
 use crate::types::{ExpressionFunc};
 use crate::tokinizer::{TokenInfo};
+use crate::error::{SmartCalcError, Span};
 
 use self::rules::date_time_rules::*;
 use self::rules::percent_rules::*;
@@ -60,6 +61,8 @@ lazy_static! {
         m.insert("small_date".to_string(),         small_date as ExpressionFunc);
         
         m.insert("convert_money".to_string(),      convert_money as ExpressionFunc);
+        m.insert("convert_historical_money".to_string(), convert_historical_money as ExpressionFunc);
+        m.insert("asset_amount".to_string(),       asset_amount as ExpressionFunc);
 
         m.insert("number_on".to_string(),          number_on as ExpressionFunc);
         m.insert("number_of".to_string(),          number_of as ExpressionFunc);
@@ -79,6 +82,8 @@ lazy_static! {
         m.insert("number_type_convert".to_string(),     number_type_convert as ExpressionFunc);
         
         m.insert("dynamic_type_convert".to_string(),     dynamic_type_convert as ExpressionFunc);
+        m.insert("compound_type_parse".to_string(),      compound_type_parse as ExpressionFunc);
+        m.insert("compound_type_convert".to_string(),    compound_type_convert as ExpressionFunc);
 
         m
     };
@@ -147,9 +152,13 @@ pub fn rule_tokinizer(tokinizer: &mut Tokinizer) {
     if let Some(language) = tokinizer.config.rule.get(&tokinizer.language) {
 
         let mut execute_rules = true;
+        let previous_error = tokinizer.rule_error.clone();
         while execute_rules {
             execute_rules = false;
 
+            /* Only errors from the last pass are valid, previous passes could be resolved by other rules. Errors of the line references stay */
+            tokinizer.rule_error = previous_error.clone();
+
             for rule in language.iter() {
                 if cfg!(feature="debug-rules") {
                     //log::debug!("# Checking for '{}'", function_name);
@@ -191,7 +200,13 @@ pub fn rule_tokinizer(tokinizer: &mut Tokinizer) {
                                         }));
                                         break;
                                     },
-                                    Err(error) => log::info!("Rule execution error, {}", error)
+                                    Err(error) => {
+                                        log::info!("Rule execution error, {}", error);
+                                        if error.is_reportable() && tokinizer.rule_error.is_none() {
+                                            let span = Span::new(&tokinizer.data, tokinizer.token_infos[start_token_index].start, tokinizer.token_infos[target_token_index - 1].end);
+                                            tokinizer.rule_error = Some(SmartCalcError::new(error, span));
+                                        }
+                                    }
                                 }
                             }
                         }
@@ -11,9 +11,9 @@ use alloc::collections::btree_map::BTreeMap;
 
 use chrono::Utc;
 use chrono::{NaiveDate, Datelike};
-use chrono::Timelike;
 
 use crate::config::SmartCalcConfig;
+use crate::error::ErrorKind;
 use crate::tokinizer::get_date;
 use crate::tokinizer::get_number;
 use crate::tokinizer::get_number_or_month;
@@ -21,16 +21,16 @@ use crate::tokinizer::get_number_or_time;
 use crate::{tokinizer::Tokinizer, types::TokenType};
 use crate::tokinizer::{TokenInfo};
 
-pub fn small_date(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn small_date(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if (fields.contains_key("day")) && fields.contains_key("month") {
         let day = match get_number("day", fields) {
             Some(number) => number,
-            _ => return Err("Number information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Number information not valid".to_string()))
         };
 
         let month = match get_number_or_month("month", fields) {
             Some(number) => number,
-            _ => return Err("Month information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Month information not valid".to_string()))
         };
 
         let year = match get_number("year", fields) {
@@ -42,27 +42,28 @@ pub fn small_date(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<Str
             Some(date) => {
                 Ok(TokenType::Date(date, config.get_time_offset()))
             },
-            None => Err("Date is not valid".to_string())
+            None => Err(ErrorKind::RuleMismatch("Date is not valid".to_string()))
         };
     }
-    Err("Date type not valid".to_string())
+    Err(ErrorKind::RuleMismatch("Date type not valid".to_string()))
 }
 
-pub fn at_date(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn at_date(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if (fields.contains_key("source")) && fields.contains_key("time") {
-        let (date, date_tz) = match get_date("source", fields) {
+        let (date, _) = match get_date("source", fields) {
             Some(number) => number,
-            _ => return Err("Date information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Date information not valid".to_string()))
         };
         
-        //todo: convert timezone informations
-        let (time, _) = match get_number_or_time(config, "time", fields) {
+        /* The local clock of the time is used at the given date, so the offset of that day is applied */
+        let (time, time_tz) = match get_number_or_time(config, "time", fields) {
             Some(number) => number,
-            _ => return Err("Date information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Date information not valid".to_string()))
         };
-        return Ok(TokenType::DateTime(date.and_hms(time.hour(), time.minute(), time.second()), date_tz));
+        let date_time = time_tz.to_utc(&date.and_time(time_tz.to_local(&time).time()));
+        return Ok(TokenType::DateTime(date_time, time_tz));
     }
-    Err("Date type not valid".to_string())
+    Err(ErrorKind::RuleMismatch("Date type not valid".to_string()))
 }
 
 
@@ -86,7 +87,7 @@ fn small_date_test_2() {
     let tokens = get_executed_raw_tokens("32 january".to_string());
     assert_eq!(tokens.len(), 3);
     
-    assert_eq!(*tokens[0], TokenType::Number(32.0, NumberType::Decimal));
+    assert_eq!(*tokens[0], TokenType::Number(32.0.into(), NumberType::Decimal));
     assert_eq!(*tokens[1], TokenType::Operator('+'));
     assert_eq!(*tokens[2], TokenType::Month(1));
 }
@@ -7,84 +7,67 @@
 use alloc::rc::Rc;
 use alloc::string::String;
 use alloc::string::ToString;
-use chrono::FixedOffset;
-use chrono::NaiveDateTime;
-use chrono::TimeZone;
+use chrono::{NaiveDateTime, Utc};
 use core::ops::Deref;
 
 use alloc::collections::btree_map::BTreeMap;
 
 use crate::config::SmartCalcConfig;
+use crate::error::ErrorKind;
 use crate::tokinizer::get_date;
 use crate::tokinizer::get_date_time;
+use crate::tokinizer::get_location;
 use crate::tokinizer::get_number;
 use crate::tokinizer::get_time;
 use crate::tokinizer::get_timezone;
 use crate::types::NumberType;
 use crate::types::TimeOffset;
 use crate::{tokinizer::Tokinizer, types::{TokenType}};
-use crate::tokinizer::{TokenInfo};
+use crate::tokinizer::{TokenInfo, TokenInfoStatus};
 
-pub fn time_for_location(_: &SmartCalcConfig, _: &Tokinizer, atoms: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
-    if let Some(TokenType::Text(_location)) = &atoms.get("location").unwrap().token_type.borrow().deref()  {
-        /*{
-            let json_data = fs::read_to_string("/Users/erhanbaris/ClionProjects/smartcalculator/smartcalc/src/json/city_informations.json").expect("{}");
-            let json_value: Result<Value> = from_str(&json_data);
-
-            return match json_value {
-                Ok(data) => {
-                    for item in data.as_array().unwrap() {
-                        if let Value::String(city) = item.get("city_ascii").unwrap() {
-
-                            if city.to_lowercase() == location.to_lowercase() {
-                                let timezone = item.get("timezone").unwrap().as_str().unwrap();
-                                let tz: Tz = match timezone.parse() {
-                                    Ok(v) => v,
-                                    Err(_) => return Err("Time not found".to_string())
-                                };
-                                return Ok(TokenType::Time(Utc::now().with_timezone(&tz).naive_local().time()));
-                            }
-                        }
-                    }
-
-                    Err("Time not found".to_string())
-                },
-                Err(error) => {
-                    //println!("{}", error);
-                    Err("Internal error".to_string())
-                }
-            };
-        }*/
+pub fn time_for_location(config: &SmartCalcConfig, tokinizer: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
+    match get_location(config, &tokinizer.language, "location", fields) {
+        Some(offset) => Ok(TokenType::Time(Utc::now().naive_utc(), offset)),
+        None => Err(ErrorKind::RuleMismatch("Location not found".to_string()))
     }
+}
 
-    Err("Location not found".to_string())
+/* "1/1/2021 at 10:00 to Berlin", the time is converted after it is combined with the date */
+fn is_time_of_date(tokinizer: &Tokinizer, time: &Rc<TokenInfo>) -> bool {
+    let mut previous_tokens = tokinizer.token_infos.iter()
+        .rev()
+        .skip_while(|token| !Rc::ptr_eq(token, time))
+        .skip(1)
+        .filter(|token| token.status.get() == TokenInfoStatus::Active);
+
+    let word = previous_tokens.next().map(|token| matches!(token.token_type.borrow().deref(), Some(TokenType::Text(_))));
+    let date = previous_tokens.next().map(|token| matches!(token.token_type.borrow().deref(), Some(TokenType::Number(_, _)) | Some(TokenType::Date(_, _))));
+    word == Some(true) && date == Some(true)
 }
 
-pub fn time_with_timezone(_: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn time_with_timezone(config: &SmartCalcConfig, tokinizer: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if fields.contains_key("time") && fields.contains_key("timezone") {
-        
-        let (time, current_offset) = get_time("time", fields).unwrap();
-        let (target_timezone, target_offset) = get_timezone("timezone", fields).unwrap();
-
-        // To source timezone
-        let timezone_offset = FixedOffset::east(current_offset.offset * 60);
-        let date_with_timezone = timezone_offset.from_utc_datetime(&time);
-        let new_time = chrono::Local.from_local_datetime(&date_with_timezone.naive_local()).unwrap().naive_local();
-
-        // To target timezone
-        let timezone_offset = FixedOffset::east(target_offset * 60);
-        let date_with_timezone = timezone_offset.from_local_datetime(&new_time).unwrap();
-        let new_time = chrono::Utc.from_utc_datetime(&date_with_timezone.naive_utc()).naive_utc();
+        let target = match get_location(config, &tokinizer.language, "timezone", fields) {
+            Some(target) => target,
+            None => return Err(ErrorKind::RuleMismatch("Timezone information not found".to_string()))
+        };
 
-        return Ok(TokenType::Time(new_time, TimeOffset { 
-            name: target_timezone.to_uppercase(),
-            offset: target_offset
-        }));
+        /* Same clock time at the target timezone */
+        return match get_time("time", fields) {
+            Some((time, current_offset)) => Ok(TokenType::Time(target.to_utc(&current_offset.to_local(&time)), target)),
+            None => match get_date("time", fields) {
+                Some((date, _)) => Ok(TokenType::Date(date, target)),
+                None => match get_date_time("time", fields) {
+                    Some((date_time, current_offset)) => Ok(TokenType::DateTime(target.to_utc(&current_offset.to_local(&date_time)), target)),
+                    None => Err(ErrorKind::RuleMismatch("Time information not found".to_string()))
+                }
+            }
+        };
     }
-    Err("Timezone or time informations not found".to_string())
+    Err(ErrorKind::RuleMismatch("Timezone or time informations not found".to_string()))
 }
 
-pub fn to_unixtime(_: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn to_unixtime(_: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if fields.contains_key("data") {
         let timestamp = match get_time("data", fields) {
             Some((time, _)) => time.timestamp(),
@@ -97,45 +80,42 @@ pub fn to_unixtime(_: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String,
             }
         };
 
-        return Ok(TokenType::Number(timestamp as f64, NumberType::Raw));
+        return Ok(TokenType::Number((timestamp as f64).into(), NumberType::Raw));
     }
-    Err("Date with time/date/time information not found".to_string())
+    Err(ErrorKind::RuleMismatch("Date with time/date/time information not found".to_string()))
 }
 
-pub fn from_unixtime(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn from_unixtime(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if fields.contains_key("number") {
         let timestamp = get_number("number", fields).unwrap();
         let date = NaiveDateTime::from_timestamp(timestamp as i64, 0);
         
         return match get_timezone("timezone", fields) {
-            Some((target_timezone, target_offset)) => Ok(TokenType::DateTime(date, TimeOffset { 
-                name: target_timezone.to_uppercase(),
-                offset: target_offset
-            })),
+            Some((target_timezone, target_offset)) => Ok(TokenType::DateTime(date, TimeOffset::from_timezone(&target_timezone, target_offset))),
             None => Ok(TokenType::DateTime(date, config.get_time_offset()))
         };
     }
-    Err("Date with time/date/time information not found".to_string())
+    Err(ErrorKind::RuleMismatch("Date with time/date/time information not found".to_string()))
 }
 
-pub fn convert_timezone(_: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn convert_timezone(config: &SmartCalcConfig, tokinizer: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if fields.contains_key("time") && fields.contains_key("timezone") {
-        let (target_timezone, target_offset) = get_timezone("timezone", fields).unwrap();
-        let offset = TimeOffset { 
-            name: target_timezone.to_uppercase(),
-            offset: target_offset
+        let offset = match get_location(config, &tokinizer.language, "timezone", fields) {
+            Some(offset) => offset,
+            None => return Err(ErrorKind::RuleMismatch("Timezone information not found".to_string()))
         };
         
         return match get_time("time", fields) {
+            Some(_) if is_time_of_date(tokinizer, &fields["time"]) => Err(ErrorKind::RuleMismatch("Time of the date is not ready".to_string())),
             Some((time, _)) => Ok(TokenType::Time(time, offset)),
             None => match get_date("time", fields) {
                 Some((date, _)) => Ok(TokenType::Date(date, offset)),
                 None => match get_date_time("time", fields) {
                     Some((date_time, _)) => Ok(TokenType::DateTime(date_time, offset)),
-                    None => Err("Timezone or time informations not found".to_string())
+                    None => Err(ErrorKind::RuleMismatch("Timezone or time informations not found".to_string()))
                 }
             }
         };
     }
-    Err("Timezone or time informations not found".to_string())
+    Err(ErrorKind::RuleMismatch("Timezone or time informations not found".to_string()))
 }
@@ -13,6 +13,9 @@ use core::ops::Deref;
 use chrono::{Duration, Timelike};
 
 use crate::config::SmartCalcConfig;
+use crate::compiler::compound_type::TIME_GROUP;
+use crate::compiler::dynamic_type::DynamicTypeItem;
+use crate::error::ErrorKind;
 use crate::tokinizer::get_date;
 use crate::tokinizer::get_duration;
 use crate::tokinizer::get_number;
@@ -22,21 +25,21 @@ use crate::{constants::ConstantType, tokinizer::Tokinizer, types::TokenType};
 use crate::tokinizer::TokenInfo;
 use crate::formatter::{MINUTE, HOUR, DAY, WEEK, MONTH, YEAR};
 
-pub fn duration_parse(config: &SmartCalcConfig, tokinizer: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn duration_parse(config: &SmartCalcConfig, tokinizer: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if (fields.contains_key("duration")) && fields.contains_key("type") {
         let duration = match get_number("duration", fields) {
             Some(number) => number as i64,
-            _ => return Err("Duration information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Duration information not valid".to_string()))
         };
 
         let duration_type = match get_text("type", fields) {
             Some(number) => number,
-            _ => return Err("Duration type information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Duration type information not valid".to_string()))
         };
 
         let constant_type = match config.constant_pair.get(&tokinizer.language).unwrap().get(&duration_type) {
             Some(constant) => constant.clone(),
-            None => return Err("Duration type not valid".to_string())
+            None => return Err(ErrorKind::RuleMismatch("Duration type not valid".to_string()))
         };
 
         let calculated_duration = match constant_type {
@@ -58,22 +61,22 @@ pub fn duration_parse(config: &SmartCalcConfig, tokinizer: &Tokinizer, fields: &
             ConstantType::Hour => Duration::hours(duration),
             ConstantType::Minute => Duration::minutes(duration),
             ConstantType::Second => Duration::seconds(duration),            
-            _ => return Err("Duration type not valid".to_string()) 
+            _ => return Err(ErrorKind::RuleMismatch("Duration type not valid".to_string())) 
         };
 
         return Ok(TokenType::Duration(calculated_duration));
     }
-    Err("Date type not valid".to_string())
+    Err(ErrorKind::RuleMismatch("Date type not valid".to_string()))
 }
 
-pub fn combine_durations(_: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn combine_durations(_: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if (fields.contains_key("1")) && fields.contains_key("2") {
         let mut sum_duration = Duration::zero();
 
         for key in fields.keys() {
             let duration = match get_duration(key, fields) {
                 Some(duration) => duration,
-                _ => return Err("Duration information not valid".to_string())
+                _ => return Err(ErrorKind::RuleMismatch("Duration information not valid".to_string()))
             };
 
             sum_duration = sum_duration + duration;
@@ -81,35 +84,36 @@ pub fn combine_durations(_: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<S
 
         return Ok(TokenType::Duration(sum_duration));
     }
-    Err("Date type not valid".to_string())
+    Err(ErrorKind::RuleMismatch("Date type not valid".to_string()))
 }
 
-pub fn as_duration(config: &SmartCalcConfig, tokinizer: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn as_duration(config: &SmartCalcConfig, tokinizer: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if (fields.contains_key("source")) && fields.contains_key("type") {
         let duration_type = match get_text("type", fields) {
             Some(number) => number,
-            _ => return Err("Duration type information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Duration type information not valid".to_string()))
         };
 
+        /* "1 day to seconds" is written in the time unit, months and years do not have a fixed length */
+        if let Some(TokenType::Duration(duration)) = fields.get("source").and_then(|token_info| token_info.token_type.borrow().clone()) {
+            return match (config.find_dynamic_type(&duration_type), config.types.get(TIME_GROUP).and_then(|group| group.values().next())) {
+                (Some(time_type), Some(second_type)) if time_type.group_name == TIME_GROUP => {
+                    let seconds = duration.num_milliseconds() as f64 / 1000.0;
+                    let (number, time_type) = DynamicTypeItem::try_convert(config, seconds, second_type.clone(), time_type.names[0].to_string())?;
+                    Ok(TokenType::DynamicType(number, time_type))
+                },
+                (Some(target_type), _) => Err(ErrorKind::IncompatibleTypes { left: TIME_GROUP.to_string(), right: target_type.group_name.to_string() }),
+                _ => Err(ErrorKind::RuleMismatch("Duration type not valid".to_string()))
+            };
+        }
+
         let constant_type = match config.constant_pair.get(&tokinizer.language).unwrap().get(&duration_type) {
             Some(constant) => constant.clone(),
-            None => return Err("Duration type not valid".to_string())
+            None => return Err(ErrorKind::RuleMismatch("Duration type not valid".to_string()))
         };
 
         match fields.get("source") {
             Some(token_info) => match token_info.token_type.borrow().deref()  {
-                Some(TokenType::Duration(duration)) => {
-                    let seconds = duration.num_seconds().abs() as i64;
-                    
-                    return match constant_type {
-                        ConstantType::Day => Ok(TokenType::Duration(Duration::days(seconds / DAY))),
-                        ConstantType::Second => Ok(TokenType::Duration(Duration::seconds(seconds))),
-                        ConstantType::Minute => Ok(TokenType::Duration(Duration::minutes(seconds / MINUTE as i64))),
-                        ConstantType::Hour => Ok(TokenType::Duration(Duration::hours(seconds / HOUR as i64))),
-                        ConstantType::Week => Ok(TokenType::Duration(Duration::weeks(seconds / WEEK as i64))),
-                        _ => return Err("Duration type not valid".to_string()) 
-                    };
-                },
                 Some(TokenType::Time(time, _)) => {
                     let seconds = time.num_seconds_from_midnight() as i64;
                     
@@ -122,18 +126,18 @@ pub fn as_duration(config: &SmartCalcConfig, tokinizer: &Tokinizer, fields: &BTr
                         ConstantType::Hour => Ok(TokenType::Duration(Duration::hours(seconds / HOUR as i64))),
                         ConstantType::Week => Ok(TokenType::Duration(Duration::weeks(seconds / WEEK as i64))),
 
-                        _ => return Err("Duration type not valid".to_string()) 
+                        _ => return Err(ErrorKind::RuleMismatch("Duration type not valid".to_string())) 
                     };
                 }
                 _ => ()
             },
-            None => return Err("Source information not valid".to_string())
+            None => return Err(ErrorKind::RuleMismatch("Source information not valid".to_string()))
         };
         
         
         let duration = match get_number("duration", fields) {
             Some(number) => number as i64,
-            _ => return Err("Duration information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Duration information not valid".to_string()))
         };
 
         let calculated_duration = match constant_type {
@@ -143,15 +147,15 @@ pub fn as_duration(config: &SmartCalcConfig, tokinizer: &Tokinizer, fields: &BTr
             ConstantType::Second => Duration::seconds(duration),
             ConstantType::Minute => Duration::minutes(duration),
             ConstantType::Hour => Duration::hours(duration),
-            _ => return Err("Duration type not valid".to_string()) 
+            _ => return Err(ErrorKind::RuleMismatch("Duration type not valid".to_string())) 
         };
 
         return Ok(TokenType::Duration(calculated_duration));
     }
-    Err("Date type not valid".to_string())
+    Err(ErrorKind::RuleMismatch("Date type not valid".to_string()))
 }
 
-pub fn to_duration(_: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn to_duration(_: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if (fields.contains_key("source")) && fields.contains_key("target") {
         //todo: calculate with timezone
         if let (Some((source, _)), Some((target, _))) = (get_time("source", fields), get_time("target", fields)) {
@@ -165,11 +169,11 @@ pub fn to_duration(_: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String,
                 let diff = if target > source { target - source } else { source - target};
                 return Ok(TokenType::Duration(diff));
             },
-            _ => Err("Time information not valid".to_string())
+            _ => Err(ErrorKind::RuleMismatch("Time information not valid".to_string()))
         }
     }
 
-    Err("Time diff not valid".to_string())
+    Err(ErrorKind::RuleMismatch("Time diff not valid".to_string()))
 }
 
 #[cfg(test)]
@@ -211,6 +215,14 @@ fn duration_parse_test_3() {
     assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Duration(Duration::minutes(60))));
 }
 
+#[cfg(test)]
+fn assert_time_unit(token: &Option<TokenType>, expected_number: f64, expected_index: usize) {
+    match token {
+        Some(TokenType::DynamicType(number, dynamic_type)) => assert_eq!((*number, dynamic_type.group_name.as_str(), dynamic_type.index), (expected_number, TIME_GROUP, expected_index)),
+        token => panic!("Time unit expected, {:?}", token)
+    }
+}
+
 #[cfg(test)]
 #[test]
 fn duration_parse_test_4() {
@@ -220,7 +232,7 @@ fn duration_parse_test_4() {
     let tokens = execute("5 weeks as seconds".to_string());
     assert_eq!(tokens.len(), 6);
     
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Duration(Duration::seconds(3024000))));
+    assert_time_unit(tokens[0].token_type.borrow().deref(), 3024000.0, 1);
 }
 
 #[cfg(test)]
@@ -233,7 +245,7 @@ fn duration_parse_test_5() {
 
     assert_eq!(tokens.len(), 6);
     
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Duration(Duration::hours(8064))));
+    assert_time_unit(tokens[0].token_type.borrow().deref(), 8064.0, 3);
 }
 
 #[cfg(test)]
@@ -259,7 +271,7 @@ fn duration_parse_test_7() {
 
     assert_eq!(tokens.len(), 10);
     
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Duration(Duration::hours(341))));
+    assert_time_unit(tokens[0].token_type.borrow().deref(), 341.0, 3);
 }
 
 #[cfg(test)]
@@ -4,27 +4,103 @@
  * Licensed under the GNU General Public License v2.0.
  */
 
+use alloc::format;
 use alloc::rc::Rc;
 use alloc::string::String;
 use alloc::string::ToString;
 use alloc::collections::btree_map::BTreeMap;
 
 use crate::config::SmartCalcConfig;
+use crate::error::ErrorKind;
+use crate::compiler::OperationType;
 use crate::compiler::dynamic_type::DynamicTypeItem;
+use crate::compiler::compound_type::{from_compound, CompoundUnit, UnitKind, TIME_GROUP};
 use crate::tokinizer::get_dynamic_type;
 use crate::tokinizer::get_text;
+use crate::tokinizer::get_unit;
+use crate::tools::do_calculation;
 use crate::{tokinizer::Tokinizer, types::{TokenType}};
 use crate::tokinizer::{TokenInfo};
 
-pub fn dynamic_type_convert(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn dynamic_type_convert(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if fields.contains_key("source") && fields.contains_key("type") {
-        let target_type = get_text("type", fields).unwrap();
+        let target_type = match (get_dynamic_type("per", fields), get_text("per", fields)) {
+            /* Unit names like "l/100km" are split by the tokinizer */
+            (Some((number, per_type)), _) => format!("{}/{}{}", get_text("type", fields).unwrap(), number, per_type.names[0]),
+            (None, Some(per)) => format!("{}/{}", get_text("type", fields).unwrap(), per),
+            (None, None) => get_text("type", fields).unwrap()
+        }.to_lowercase();
         let (number, source_type) = get_dynamic_type("source", fields).unwrap();
-        
-        if let Some((new_number, new_type)) = DynamicTypeItem::convert(config, number, source_type, target_type) {
-            return Ok(TokenType::DynamicType(new_number, new_type))
+
+        let result = match source_type.difference {
+            true => DynamicTypeItem::convert_difference(config, number, source_type.clone(), target_type.clone()),
+            false => DynamicTypeItem::try_convert(config, number, source_type.clone(), target_type.clone())
         };
+
+        return match result {
+            Ok((new_number, new_type)) => Ok(TokenType::DynamicType(new_number, new_type.with_difference(source_type.difference))),
+
+            /* Groups could have the same dimensions, "1 hectare to m²" or "60 mph to km/h" */
+            Err(error @ (ErrorKind::IncompatibleTypes { .. } | ErrorKind::UnknownUnit(_))) => match (source_type.difference, CompoundUnit::parse_text(config, &target_type)) {
+                (false, Some(target)) => match CompoundUnit::new(UnitKind::Dynamic(source_type), 1).convert(config, number, &target) {
+                    Some(number) => Ok(from_compound(config, number, target).as_token_type()),
+                    None => Err(error)
+                },
+                _ => Err(error)
+            },
+            Err(error) => Err(error)
+        };
+    }
+
+    Err(ErrorKind::RuleMismatch("Dynamic type not valid".to_string()))
+}
+
+pub fn compound_type_parse(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
+    let (mut number, mut unit) = match get_unit(config, "value", fields) {
+        Some(value) => value,
+        None => return Err(ErrorKind::RuleMismatch("Compound type not valid".to_string()))
+    };
+
+    /* "5 m" is already parsed, plain number only takes a powered unit like "5 m²" or a time unit like "2 s" */
+    let is_new_unit = get_text("unit", fields).is_some_and(|text| text.ends_with(['²', '³']) || config.find_dynamic_type(&text).is_some_and(|dynamic_type| dynamic_type.group_name == TIME_GROUP));
+    if unit.is_empty() && !is_new_unit && !fields.contains_key("per") {
+        return Err(ErrorKind::RuleMismatch("Compound type not valid".to_string()));
     }
 
-    Err("Dynamic type not valid".to_string())
+    for (field_name, exponent) in [("unit", 1), ("per", -1)] {
+        if let Some(text) = get_text(field_name, fields) {
+            let part = match CompoundUnit::parse(config, &text) {
+                Some(part) => part.powi(exponent),
+                None => return Err(ErrorKind::RuleMismatch("Compound type not valid".to_string()))
+            };
+
+            let (factor, combined) = unit.combine(config, &part);
+            number = do_calculation(config, number, factor, OperationType::Mul);
+            unit = combined;
+        }
+    }
+
+    Ok(from_compound(config, number, unit).as_token_type())
+}
+
+pub fn compound_type_convert(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
+    let (number, source) = match get_unit(config, "value", fields) {
+        Some((number, source)) if !source.is_empty() => (number, source),
+        _ => return Err(ErrorKind::RuleMismatch("Compound type not valid".to_string()))
+    };
+
+    let mut target = CompoundUnit::default();
+    for (field_name, exponent) in [("unit", 1), ("per", -1)] {
+        if let Some(text) = get_text(field_name, fields) {
+            match CompoundUnit::parse(config, &text) {
+                Some(part) => target.terms.extend(part.powi(exponent).terms),
+                None => return Err(ErrorKind::UnknownUnit(text))
+            };
+        }
+    }
+
+    match source.convert(config, number, &target) {
+        Some(number) => Ok(from_compound(config, number, target).as_token_type()),
+        None => Err(ErrorKind::IncompatibleTypes { left: source.format(), right: target.format() })
+    }
 }
@@ -8,40 +8,145 @@ use alloc::rc::Rc;
 use alloc::string::String;
 use alloc::string::ToString;
 use alloc::collections::btree_map::BTreeMap;
+use core::ops::Deref;
+use chrono::NaiveTime;
 
 use crate::config::SmartCalcConfig;
+use crate::error::ErrorKind;
 use crate::tokinizer::get_currency;
 use crate::tokinizer::get_money;
+use crate::tokinizer::get_text;
+use crate::tokinizer::get_date;
+use crate::tokinizer::get_number;
+use crate::tokinizer::get_dynamic_type;
+use crate::tokinizer::read_currency;
+use crate::tokinizer::TokenInfoStatus;
 use crate::{tokinizer::Tokinizer, types::{TokenType}};
 use crate::tokinizer::TokenInfo;
-use crate::tools::do_divition;
+use crate::compiler::OperationType;
+use crate::tools::{calculate_number, do_calculation};
+use super::date_rules::small_date;
+
+/* "100 usd to eur on 1 jan 2020" is converted with the historical rates */
+fn is_historical_conversion(tokinizer: &Tokinizer, currency: &Rc<TokenInfo>) -> bool {
+    let group = match tokinizer.config.word_group.get(&tokinizer.language).and_then(|groups| groups.get("historical_group")) {
+        Some(group) => group,
+        None => return false
+    };
+
+    let next_token = tokinizer.token_infos.iter()
+        .skip_while(|token| !Rc::ptr_eq(token, currency))
+        .skip(1)
+        .find(|token| token.status.get() == TokenInfoStatus::Active);
+
+    match next_token {
+        Some(token) => matches!(token.token_type.borrow().deref(), Some(TokenType::Text(text)) if group.contains(&text.to_lowercase())),
+        None => false
+    }
+}
 
-pub fn convert_money(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn convert_money(config: &SmartCalcConfig, tokinizer: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if fields.contains_key("money") && fields.contains_key("currency") {
         let money = match get_money(config, "money", fields) {
             Some(money) => money,
-            _ => return Err("Money information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Money information not valid".to_string()))
         };
 
-        let to_currency = match get_currency(config, "currency", fields) {
-            Some(to_currency) => to_currency,
-            _ => return Err("Currency information not valid".to_string())
+        let to_currency = match (get_currency(config, "currency", fields), get_text("currency", fields)) {
+            (Some(to_currency), _) => to_currency,
+
+            /* "10 usd to abc" is a conversion request, "10 usd abc" is not */
+            (None, Some(currency)) if fields.contains_key("conversion") => return Err(ErrorKind::UnknownCurrency(currency)),
+            _ => return Err(ErrorKind::RuleMismatch("Currency information not valid".to_string()))
         };
 
+        if is_historical_conversion(tokinizer, &fields["currency"]) {
+            return Err(ErrorKind::RuleMismatch("Historical conversion".to_string()));
+        }
+
         let as_usd = match config.currency_rate.get(&money.get_currency()) {
-            Some(l_rate) => do_divition(money.get_price(), *l_rate),
-            _ => return Err("Currency information not valid".to_string())
+            Some(l_rate) => calculate_number(config, money.get_value(), (*l_rate).into(), OperationType::Div),
+            _ => return Err(ErrorKind::MissingRate(money.get_currency().code.to_string()))
         };
 
         let calculated_price = match config.currency_rate.get(&to_currency) {
-            Some(r_rate) => as_usd * r_rate,
-            _ => return Err("Currency information not valid".to_string())
+            Some(r_rate) => calculate_number(config, as_usd, (*r_rate).into(), OperationType::Mul),
+            _ => return Err(ErrorKind::MissingRate(to_currency.code.to_string()))
+        };
+
+        if money.get_currency() != to_currency {
+            tokinizer.session.add_rate_date(config.currency_rate_date([money.get_currency(), to_currency.clone()].iter()));
+        }
+
+        return Ok(TokenType::Money(calculated_price, to_currency));
+    }
+
+    Err(ErrorKind::RuleMismatch("Money type not valid".to_string()))
+}
+
+/* "2 oz gold" and "10 gram silver", units of the asset are in the configuration */
+pub fn asset_amount(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
+    let (asset, units) = match get_text("asset", fields).and_then(|asset| read_currency(config, &asset)) {
+        Some(asset) => match config.asset_units.get(&asset) {
+            Some(units) => (asset, units),
+            None => return Err(ErrorKind::RuleMismatch("Asset does not have unit".to_string()))
+        },
+        None => return Err(ErrorKind::RuleMismatch("Asset information not valid".to_string()))
+    };
+
+    let (amount, unit_amount) = match (get_dynamic_type("amount", fields), get_number("amount", fields), get_text("unit", fields)) {
+        (Some((amount, dynamic_type)), _, _) => (amount, dynamic_type.names.iter().find_map(|name| units.get(&name.to_lowercase()))),
+        (None, Some(amount), Some(unit)) => (amount, units.get(&unit.to_lowercase())),
+        _ => return Err(ErrorKind::RuleMismatch("Amount information not valid".to_string()))
+    };
+
+    match unit_amount {
+        Some(unit_amount) => Ok(TokenType::Money(do_calculation(config, amount, *unit_amount, OperationType::Mul).into(), asset)),
+        None => Err(ErrorKind::RuleMismatch("Unit information not valid".to_string()))
+    }
+}
+
+pub fn convert_historical_money(config: &SmartCalcConfig, tokinizer: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
+    if fields.contains_key("money") && fields.contains_key("currency") {
+        let money = match get_money(config, "money", fields) {
+            Some(money) => money,
+            _ => return Err(ErrorKind::RuleMismatch("Money information not valid".to_string()))
+        };
+
+        let to_currency = match (get_currency(config, "currency", fields), get_text("currency", fields)) {
+            (Some(to_currency), _) => to_currency,
+            (None, Some(currency)) => return Err(ErrorKind::UnknownCurrency(currency)),
+            _ => return Err(ErrorKind::RuleMismatch("Currency information not valid".to_string()))
         };
 
+        let date = match get_date("date", fields) {
+            Some((date, _)) => date,
+            None => match small_date(config, tokinizer, fields)? {
+                TokenType::Date(date, _) => date,
+                _ => return Err(ErrorKind::RuleMismatch("Date information not valid".to_string()))
+            }
+        };
+
+        let from_currency = money.get_currency();
+        let (day, from_rate, to_rate) = match config.historical_rates(date, &from_currency, &to_currency) {
+            Ok(rates) => rates,
+            Err(currency) => return Err(ErrorKind::MissingHistoricalRate {
+                currency: currency.code.to_string(),
+                date: date.format("%Y-%m-%d").to_string()
+            })
+        };
+
+        let as_base = calculate_number(config, money.get_value(), from_rate.into(), OperationType::Div);
+        let calculated_price = calculate_number(config, as_base, to_rate.into(), OperationType::Mul);
+
+        if from_currency != to_currency {
+            tokinizer.session.add_rate_date(Some(day.and_time(NaiveTime::MIN)));
+        }
+
         return Ok(TokenType::Money(calculated_price, to_currency));
     }
 
-    Err("Money type not valid".to_string())
+    Err(ErrorKind::RuleMismatch("Money type not valid".to_string()))
 }
 
 #[cfg(test)]
@@ -55,7 +160,7 @@ fn convert_money_1() {
     let tokens = execute("10 usd as try".to_string());
 
     assert_eq!(tokens.len(), 4);
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(70.727697572, conf.get_currency("try".to_string()).unwrap())));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(70.727697572.into(), conf.get_currency("try".to_string()).unwrap())));
 
 }
 
@@ -71,7 +176,7 @@ fn convert_money_2() {
     let tokens = execute("10 usd try".to_string());
 
     assert_eq!(tokens.len(), 3);
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(70.727697572, conf.get_currency("try".to_string()).unwrap())));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(70.727697572.into(), conf.get_currency("try".to_string()).unwrap())));
 
 }
 
@@ -86,7 +191,7 @@ fn convert_money_3() {
     let tokens = execute("10 usd into try".to_string());
 
     assert_eq!(tokens.len(), 4);
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(70.727697572, conf.get_currency("try".to_string()).unwrap())));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(70.727697572.into(), conf.get_currency("try".to_string()).unwrap())));
 
 }
 
@@ -103,7 +208,7 @@ fn convert_money_4() {
 
     assert_eq!(tokens.len(), 5);
     assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Text("salary".to_string())));
-    assert_eq!(tokens[2].token_type.borrow().deref(), &Some(TokenType::Money(134.4772867837901, conf.get_currency("eur".to_string()).unwrap())));
+    assert_eq!(tokens[2].token_type.borrow().deref(), &Some(TokenType::Money(134.4772867837901.into(), conf.get_currency("eur".to_string()).unwrap())));
 
 }
 
@@ -119,7 +224,7 @@ fn convert_money_5() {
     let conf = SmartCalcConfig::default();
     let tokens = execute("$9 in Euro".to_string());
 
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(7.5106400733, conf.get_currency("eur".to_string()).unwrap())));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(7.5106400733.into(), conf.get_currency("eur".to_string()).unwrap())));
 
 }
 
@@ -138,7 +243,7 @@ fn convert_money_6() {
     assert_eq!(tokens.len(), 3);
 
     let token = tokens[0].token_type.borrow().deref().clone();
-    assert_eq!(token, Some(TokenType::Money(2_000_000.0, conf.get_currency("eur".to_string()).unwrap())));
+    assert_eq!(token, Some(TokenType::Money(2_000_000.0.into(), conf.get_currency("eur".to_string()).unwrap())));
 }
 
 
@@ -152,7 +257,7 @@ fn money_on_1() {
     let conf = SmartCalcConfig::default();
     let tokens = execute("6% on 40 EUR".to_string());
 
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(42.4, conf.get_currency("eur".to_string()).unwrap())));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(42.4.into(), conf.get_currency("eur".to_string()).unwrap())));
 }
 
 
@@ -166,7 +271,7 @@ fn money_of_1() {
     let conf = SmartCalcConfig::default();
     let tokens = execute("6% of 40 EUR".to_string());
 
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(2.4, conf.get_currency("eur".to_string()).unwrap())));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(2.4.into(), conf.get_currency("eur".to_string()).unwrap())));
 }
 
 
@@ -180,5 +285,5 @@ fn money_off_1() {
     let conf = SmartCalcConfig::default();
     let tokens = execute("6% off 40 EUR".to_string());
 
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(37.6, conf.get_currency("eur".to_string()).unwrap())));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Money(37.6.into(), conf.get_currency("eur".to_string()).unwrap())));
 }
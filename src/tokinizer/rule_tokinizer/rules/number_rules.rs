@@ -10,6 +10,7 @@ use alloc::string::ToString;
 use alloc::collections::btree_map::BTreeMap;
 
 use crate::config::SmartCalcConfig;
+use crate::error::ErrorKind;
 use crate::tokinizer::get_currency;
 use crate::tokinizer::get_number;
 use crate::tokinizer::get_number_or_price;
@@ -18,82 +19,83 @@ use crate::tokinizer::get_text;
 use crate::types::NumberType;
 use crate::{tokinizer::Tokinizer, types::{TokenType}};
 use crate::tokinizer::{TokenInfo};
-use crate::tools::do_divition;
+use crate::compiler::OperationType;
+use crate::tools::{calculate_number, percent_of_number};
 
-pub fn number_on(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn number_on(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if fields.contains_key("number") && fields.contains_key("p") {
         let number = match get_number_or_price(config, "number", fields) {
             Some(number) => number,
-            _ => return Err("Number information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Number information not valid".to_string()))
         };
 
-        let percent = match get_percent("p", fields) {
+        let percent = match get_percent("p", fields).or_else(|| get_number("p", fields)) {
             Some(percent) => percent,
-            _ => return Err("Percent information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Percent information not valid".to_string()))
         };
 
-        let calculated_number = number + do_divition(number * percent, 100.0);
+        let calculated_number = calculate_number(config, number, percent_of_number(config, number, percent), OperationType::Add);
         return Ok(match get_currency(config, "number", fields) {
             Some(currency) => TokenType::Money(calculated_number, currency),
             None => TokenType::Number(calculated_number, NumberType::Decimal)
         });
     }
 
-    Err("Number type not valid".to_string())
+    Err(ErrorKind::RuleMismatch("Number type not valid".to_string()))
 }
 
 
-pub fn number_of(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn number_of(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if fields.contains_key("number") && fields.contains_key("p") {
         let number = match get_number_or_price(config, "number", fields) {
             Some(number) => number,
-            _ => return Err("Number information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Number information not valid".to_string()))
         };
 
-        let percent = match get_percent("p", fields) {
+        let percent = match get_percent("p", fields).or_else(|| get_number("p", fields)) {
             Some(percent) => percent,
-            _ => return Err("Percent information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Percent information not valid".to_string()))
         };
 
-        let calculated_number = do_divition(number * percent, 100.0);
+        let calculated_number = percent_of_number(config, number, percent);
         return Ok(match get_currency(config, "number", fields) {
             Some(currency) => TokenType::Money(calculated_number, currency),
             None => TokenType::Number(calculated_number, NumberType::Decimal)
         });
     }
 
-    Err("Number type not valid".to_string())
+    Err(ErrorKind::RuleMismatch("Number type not valid".to_string()))
 }
 
 
-pub fn number_off(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn number_off(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if fields.contains_key("number") && fields.contains_key("p") {
         let number = match get_number_or_price(config, "number", fields) {
             Some(number) => number,
-            _ => return Err("Number information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Number information not valid".to_string()))
         };
 
-        let percent = match get_percent("p", fields) {
+        let percent = match get_percent("p", fields).or_else(|| get_number("p", fields)) {
             Some(percent) => percent,
-            _ => return Err("Percent information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Percent information not valid".to_string()))
         };
 
-        let calculated_number = number - do_divition(number * percent, 100.0);
+        let calculated_number = calculate_number(config, number, percent_of_number(config, number, percent), OperationType::Sub);
         return Ok(match get_currency(config, "number", fields) {
             Some(currency) => TokenType::Money(calculated_number, currency),
             None => TokenType::Number(calculated_number, NumberType::Decimal)
         });
     }
 
-    Err("Number type not valid".to_string())
+    Err(ErrorKind::RuleMismatch("Number type not valid".to_string()))
 }
 
-pub fn number_type_convert(_: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn number_type_convert(_: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if fields.contains_key("number") && fields.contains_key("type") {
         let number = get_number("number", fields).unwrap().round();
         let number_type = match get_text("type", fields) {
             Some(text) => text,
-            None => return Err("Number type not valid".to_string())
+            None => return Err(ErrorKind::RuleMismatch("Number type not valid".to_string()))
         };
         
         let number_type = match &number_type[..] {
@@ -101,13 +103,13 @@ pub fn number_type_convert(_: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap
             "octal"               => NumberType::Octal,
             "binary"              => NumberType::Binary,
             "decimal"             => NumberType::Decimal,
-            _ => return Err("Target number type not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Target number type not valid".to_string()))
         };
 
-        return Ok(TokenType::Number(number, number_type));
+        return Ok(TokenType::Number(number.into(), number_type));
     }
 
-    Err("Number type not valid".to_string())
+    Err(ErrorKind::RuleMismatch("Number type not valid".to_string()))
 }
 
 #[cfg(test)]
@@ -118,7 +120,7 @@ fn number_on_1() {
     
     let tokens = execute("6% on 40".to_string());
     
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(42.4, NumberType::Decimal)));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(42.4.into(), NumberType::Decimal)));
 }
 
 
@@ -130,7 +132,7 @@ fn number_of_1() {
     
     let tokens = execute("6% of 40".to_string());
 
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(2.4, NumberType::Decimal)));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(2.4.into(), NumberType::Decimal)));
 }
 
 
@@ -142,7 +144,7 @@ fn number_off_1() {
     
     let tokens = execute("6% off 40".to_string());
 
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(37.6, NumberType::Decimal)));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(37.6.into(), NumberType::Decimal)));
 }
 
 #[cfg(test)]
@@ -152,7 +154,7 @@ fn number_type_convert_1() {
     use crate::tokinizer::test::execute;
 
     let tokens = execute("100 to hex".to_string());
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(100.0, NumberType::Hexadecimal)));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(100.0.into(), NumberType::Hexadecimal)));
 }
 
 #[cfg(test)]
@@ -162,7 +164,7 @@ fn number_type_convert_2() {
     use crate::tokinizer::test::execute;
     
     let tokens = execute("100,0 to hex".to_string());
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(100.0, NumberType::Hexadecimal)));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(100.0.into(), NumberType::Hexadecimal)));
 }
 
 #[cfg(test)]
@@ -172,7 +174,7 @@ fn number_type_convert_3() {
     use crate::tokinizer::test::execute;
     
     let tokens = execute("100,0 to hexadecimal".to_string());
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(100.0, NumberType::Hexadecimal)));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(100.0.into(), NumberType::Hexadecimal)));
 }
 
 #[cfg(test)]
@@ -182,7 +184,7 @@ fn number_type_convert_4() {
     use crate::tokinizer::test::execute;
     
     let tokens = execute("100,0 to octal".to_string());
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(100.0, NumberType::Octal)));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(100.0.into(), NumberType::Octal)));
 }
 
 #[cfg(test)]
@@ -192,7 +194,7 @@ fn number_type_convert_5() {
     use crate::tokinizer::test::execute;
     
     let tokens = execute("100,0 to oct".to_string());
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(100.0, NumberType::Octal)));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(100.0.into(), NumberType::Octal)));
 }
 
 #[cfg(test)]
@@ -202,7 +204,7 @@ fn number_type_convert_6() {
     use crate::tokinizer::test::execute;
     
     let tokens = execute("100,0 to bin".to_string());
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(100.0, NumberType::Binary)));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(100.0.into(), NumberType::Binary)));
 }
 
 #[cfg(test)]
@@ -212,5 +214,5 @@ fn number_type_convert_7() {
     use crate::tokinizer::test::execute;
     
     let tokens = execute("100,0 to binary".to_string());
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(100.0, NumberType::Binary)));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(100.0.into(), NumberType::Binary)));
 }
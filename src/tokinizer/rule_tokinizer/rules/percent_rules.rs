@@ -9,69 +9,80 @@ use alloc::string::String;
 use alloc::string::ToString;
 use alloc::collections::btree_map::BTreeMap;
 use crate::config::SmartCalcConfig;
+use crate::error::ErrorKind;
 use crate::tokinizer::get_currency;
-use crate::tokinizer::get_number;
+use crate::tokinizer::get_number_value;
 use crate::tokinizer::get_number_or_price;
 use crate::tokinizer::get_percent;
 use crate::types::NumberType;
 use crate::{tokinizer::{TokenInfo, Tokinizer}, types::TokenType};
 
-use crate::tools::do_divition;
+use crate::compiler::OperationType;
+use crate::tools::{calculate_number, do_calculation, percent_of_number};
 
-pub fn percent_calculator(_: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn percent_calculator(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if fields.contains_key("p") && fields.contains_key("number") {
-        let number = match get_number("number", fields) {
+        let number = match get_number_value("number", fields) {
             Some(number) => number,
-            _ => return Err("Number information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Number information not valid".to_string()))
         };
 
         let percent = match get_percent("p", fields) {
             Some(number) => number,
-            _ => return Err("Percent information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Percent information not valid".to_string()))
         };
-        return Ok(TokenType::Number(do_divition(percent * number, 100.0), NumberType::Decimal));
+        return Ok(TokenType::Number(percent_of_number(config, number, percent), NumberType::Decimal));
     }
 
-    Err("Percent not valid".to_string())
+    Err(ErrorKind::RuleMismatch("Percent not valid".to_string()))
 }
 
-pub fn find_numbers_percent(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn find_numbers_percent(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if fields.contains_key("part") && fields.contains_key("total") {
         let total = match get_number_or_price(config, "total", fields) {
-            Some(number) => number,
-            _ => return Err("Total number information not valid".to_string())
+            Some(number) => number.to_f64(),
+            _ => return Err(ErrorKind::RuleMismatch("Total number information not valid".to_string()))
         };
 
         let part = match get_number_or_price(config, "part", fields) {
-            Some(number) => number,
-            _ => return Err("Part number information not valid".to_string())
+            Some(number) => number.to_f64(),
+            _ => return Err(ErrorKind::RuleMismatch("Part number information not valid".to_string()))
         };
+
+        if total == 0.0 {
+            return Err(ErrorKind::DivisionByZero);
+        }
         
-        return Ok(TokenType::Percent(do_divition(part * 100.0, total)));
+        return Ok(TokenType::Percent(do_calculation(config, do_calculation(config, part, 100.0, OperationType::Mul), total, OperationType::Div)));
     }
 
-    Err("Find percent not valid".to_string())
+    Err(ErrorKind::RuleMismatch("Find percent not valid".to_string()))
 }
 
-pub fn find_total_from_percent(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String> {
+pub fn find_total_from_percent(config: &SmartCalcConfig, _: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind> {
     if fields.contains_key("number_part") && fields.contains_key("percent_part") {
         let number_part = match get_number_or_price(config, "number_part", fields) {
             Some(number) => number,
-            _ => return Err("Number part information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Number part information not valid".to_string()))
         };
 
         let percent_part = match get_percent("percent_part", fields) {
             Some(percent) => percent,
-            _ => return Err("Percent part information not valid".to_string())
+            _ => return Err(ErrorKind::RuleMismatch("Percent part information not valid".to_string()))
         };
 
+        if percent_part == 0.0 {
+            return Err(ErrorKind::DivisionByZero);
+        }
+
+        let total = calculate_number(config, calculate_number(config, number_part, 100.0.into(), OperationType::Mul), percent_part.into(), OperationType::Div);
         return Ok(match get_currency(config, "number_part", fields) {
-            Some(currency) => TokenType::Money(do_divition(number_part * 100.0, percent_part), currency),
-            None => TokenType::Number(do_divition(number_part * 100.0, percent_part), NumberType::Decimal)
+            Some(currency) => TokenType::Money(total, currency),
+            None => TokenType::Number(total, NumberType::Decimal)
         });
     }
 
-    Err("Find percent not valid".to_string())
+    Err(ErrorKind::RuleMismatch("Find percent not valid".to_string()))
 }
 
 
@@ -83,7 +94,7 @@ fn find_percent_to_number_1() {
 
     let tokens = execute("20 is 10% of what".to_string());
     assert_eq!(tokens.len(), 6);
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(200.0, NumberType::Decimal)));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(200.0.into(), NumberType::Decimal)));
 
 }
 
@@ -96,7 +107,7 @@ fn find_percent_to_number_2() {
     let tokens = execute("180 is 10% of what".to_string());
 
     assert_eq!(tokens.len(), 6);
-    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(1800.0, NumberType::Decimal)));
+    assert_eq!(tokens[0].token_type.borrow().deref(), &Some(TokenType::Number(1800.0.into(), NumberType::Decimal)));
 
 }
 
@@ -16,6 +16,7 @@ use crate::compiler::duration::DurationItem;
 use crate::compiler::number::NumberItem;
 use crate::compiler::percent::PercentItem;
 use crate::compiler::dynamic_type::DynamicTypeItem;
+use crate::compiler::compound_type::{as_compound, CompoundUnit, UnitKind};
 use crate::compiler::DataItem;
 use crate::compiler::time::TimeItem;
 use crate::types::TimeOffset;
@@ -26,6 +27,7 @@ use crate::config::SmartCalcConfig;
 use crate::config::DynamicType;
 use crate::types::CurrencyInfo;
 use crate::types::Money;
+use crate::types::NumberValue;
 use crate::types::{TokenType, SmartCalcAstType};
 use crate::tokinizer::TokenInfo;
 use crate::compiler::money::MoneyItem;
@@ -38,13 +40,17 @@ pub fn read_currency(config: &SmartCalcConfig, currency: &'_ str) -> Option<Rc<C
 }
 
 pub fn get_number(field_name: &str, fields: &BTreeMap<String, Rc<TokenInfo>>) -> Option<f64> {
+    get_number_value(field_name, fields).map(|number| number.to_f64())
+}
+
+pub fn get_number_value(field_name: &str, fields: &BTreeMap<String, Rc<TokenInfo>>) -> Option<NumberValue> {
     return match fields.get(field_name) {
         Some(data) => match data.token_type.borrow().deref() {
             Some(token) => match &token {
                 TokenType::Number(number, _) => Some(*number),
                 TokenType::Variable(variable) => {
                     match variable.data.borrow().deref().deref() {
-                        SmartCalcAstType::Item(item) => item.as_any().downcast_ref::<NumberItem>().map(|number| number.get_underlying_number()),
+                        SmartCalcAstType::Item(item) => item.as_any().downcast_ref::<NumberItem>().map(|number| number.get_value()),
                         _ => None
                     }
                 },
@@ -162,6 +168,30 @@ pub fn get_dynamic_type(field_name: &str, fields: &BTreeMap<String, Rc<TokenInfo
     }
 }
 
+/* Number and unit of the field, plain numbers do not have any unit */
+pub fn get_unit(config: &SmartCalcConfig, field_name: &str, fields: &BTreeMap<String, Rc<TokenInfo>>) -> Option<(f64, CompoundUnit)> {
+    match &fields.get(field_name) {
+        Some(data) => match &data.token_type.borrow().deref() {
+            Some(token) => match &token {
+                TokenType::Number(number, _) => Some((number.to_f64(), CompoundUnit::default())),
+                TokenType::DynamicType(number, dynamic_type) => Some((*number, CompoundUnit::new(UnitKind::Dynamic(dynamic_type.clone()), 1))),
+                TokenType::Money(price, currency) => Some((price.to_f64(), CompoundUnit::new(UnitKind::Currency(currency.clone()), 1))),
+                TokenType::CompoundType(number, unit) => Some((*number, unit.deref().clone())),
+                TokenType::Duration(duration) => as_compound(config, &DurationItem(*duration)),
+                TokenType::Variable(variable) => {
+                    match variable.data.borrow().deref().deref() {
+                        SmartCalcAstType::Item(item) => as_compound(config, item.deref()),
+                        _ => None
+                    }
+                },
+                _ => None
+            },
+            _ => None
+        },
+        _ => None
+    }
+}
+
 pub fn get_timezone(field_name: &str, fields: &BTreeMap<String, Rc<TokenInfo>>) -> Option<(String, i32)> {
     return match fields.get(field_name) {
         Some(data) => match &data.token_type.borrow().deref() {
@@ -172,6 +202,18 @@ pub fn get_timezone(field_name: &str, fields: &BTreeMap<String, Rc<TokenInfo>>)
     }
 }
 
+/* Timezone or the city and country name as a text, like "tokyo" */
+pub fn get_location(config: &SmartCalcConfig, language: &str, field_name: &str, fields: &BTreeMap<String, Rc<TokenInfo>>) -> Option<TimeOffset> {
+    match fields.get(field_name) {
+        Some(data) => match &data.token_type.borrow().deref() {
+            Some(TokenType::Timezone(timezone, offset)) => Some(TimeOffset::from_timezone(timezone, *offset)),
+            Some(TokenType::Text(text)) => config.find_location(language, text).map(TimeOffset::from_zone),
+            _ => None
+        },
+        _ => None
+    }
+}
+
 pub fn get_month(field_name: &str, fields: &BTreeMap<String, Rc<TokenInfo>>) -> Option<u32> {
     return match &fields.get(field_name) {
         Some(data) =>match &data.token_type.borrow().deref() {
@@ -194,18 +236,19 @@ pub fn get_month(field_name: &str, fields: &BTreeMap<String, Rc<TokenInfo>>) ->
 pub fn get_number_or_time(config: &SmartCalcConfig, field_name: &str, fields: &BTreeMap<String, Rc<TokenInfo>>) -> Option<(NaiveDateTime, TimeOffset)> {
     match get_number(field_name, fields) {
         Some(number) => {
-            let date = Utc::now().naive_local().date();
-            let time = chrono::NaiveTime::from_hms(number as u32, 0, 0);
-            Some((NaiveDateTime::new(date, time), config.get_time_offset()))
+            let time_offset = config.get_time_offset();
+            let date = time_offset.to_local(&Utc::now().naive_utc()).date();
+            let time = chrono::NaiveTime::from_hms_opt(number as u32, 0, 0)?;
+            Some((time_offset.to_utc(&NaiveDateTime::new(date, time)), time_offset))
         },
         None => get_time(field_name, fields)
     }
 }
 
-pub fn get_number_or_price(config: &SmartCalcConfig, field_name: &str, fields: &BTreeMap<String, Rc<TokenInfo>>) -> Option<f64> {
-    match get_number(field_name, fields) {
+pub fn get_number_or_price(config: &SmartCalcConfig, field_name: &str, fields: &BTreeMap<String, Rc<TokenInfo>>) -> Option<NumberValue> {
+    match get_number_value(field_name, fields) {
         Some(number) => Some(number),
-        None => get_money(config, field_name, fields).map(|money| money.get_price())
+        None => get_money(config, field_name, fields).map(|money| money.get_value())
     }
 }
 
@@ -224,7 +267,7 @@ pub fn get_money(_: &SmartCalcConfig, field_name: &str, fields: &BTreeMap<String
                 TokenType::Money(price, currency) => Some(Money(*price, currency.clone())),
                 TokenType::Variable(variable) => {
                     match variable.data.borrow().deref().deref() {
-                        SmartCalcAstType::Item(item) => item.as_any().downcast_ref::<MoneyItem>().map(|money_item| Money(money_item.get_price(), money_item.get_currency())),
+                        SmartCalcAstType::Item(item) => item.as_any().downcast_ref::<MoneyItem>().map(|money_item| Money(money_item.get_value(), money_item.get_currency())),
                         _ => None
                     }
                 },
@@ -4,8 +4,18 @@
  * Licensed under the GNU General Public License v2.0.
  */
 
+use core::str::FromStr;
 use alloc::string::{ToString, String};
+use alloc::collections::btree_map::BTreeMap;
+use chrono_tz::{Tz, TZ_VARIANTS};
+use lazy_static::*;
+use rust_decimal::Decimal;
+use rust_decimal::RoundingStrategy;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::MathematicalOps;
+use crate::compiler::OperationType;
 use crate::config::SmartCalcConfig;
+use crate::types::{NumberValue, NumericMode};
  
 pub fn do_divition(left: f64, right: f64) -> f64 {
     let mut calculation = left / right;
@@ -15,6 +25,140 @@ pub fn do_divition(left: f64, right: f64) -> f64 {
     calculation
 }
 
+/* Result has the same sign with divisor, "-7 mod 3" is "2". Not defined for zero divisor */
+pub fn do_modulo(left: f64, right: f64) -> f64 {
+    if right == 0.0 {
+        return f64::NAN;
+    }
+    left - right * (left / right).floor()
+}
+
+fn calculate_decimal(left: Decimal, right: Decimal, operation_type: OperationType) -> Option<Decimal> {
+    match operation_type {
+        OperationType::Add => left.checked_add(right),
+        OperationType::Sub => left.checked_sub(right),
+        OperationType::Mul => left.checked_mul(right),
+        OperationType::Div => left.checked_div(right),
+        OperationType::Mod => left.checked_sub(right.checked_mul(left.checked_div(right)?.floor())?),
+        OperationType::IntDiv => Some(left.checked_div(right)?.floor()),
+        OperationType::Pow => left.checked_powi(right.to_i64()?)
+    }
+}
+
+fn calculate_float(left: f64, right: f64, operation_type: OperationType) -> f64 {
+    match operation_type {
+        OperationType::Add => left + right,
+        OperationType::Sub => left - right,
+        OperationType::Mul => left * right,
+        OperationType::Div => left / right,
+        OperationType::Mod => do_modulo(left, right),
+        OperationType::IntDiv => (left / right).floor(),
+        OperationType::Pow => left.powf(right)
+    }
+}
+
+/* Decimal values stay decimal, zero divisor and fractional powers are calculated with f64. Results out of decimal range are infinite */
+pub fn calculate_number(config: &SmartCalcConfig, left: NumberValue, right: NumberValue, operation_type: OperationType) -> NumberValue {
+    if config.numeric_mode == NumericMode::Decimal {
+        if let (Some(left), Some(right)) = (left.to_decimal(), right.to_decimal()) {
+            let is_zero_divisor = matches!(operation_type, OperationType::Div | OperationType::Mod | OperationType::IntDiv) && right.is_zero();
+            let is_root = operation_type == OperationType::Pow && !right.fract().is_zero();
+            if !is_zero_divisor && !is_root {
+                return match calculate_decimal(left, right, operation_type) {
+                    Some(result) => NumberValue::Decimal(result),
+                    None => NumberValue::Float(f64::INFINITY)
+                };
+            }
+        }
+    }
+
+    NumberValue::Float(calculate_float(left.to_f64(), right.to_f64(), operation_type))
+}
+
+/* Number text uses '.' as the decimal separator, "1.5e3" is allowed. Decimal mode reads the text without f64, numbers out of decimal range are infinite */
+pub fn parse_number(config: &SmartCalcConfig, number_text: &str) -> Option<NumberValue> {
+    let number = number_text.parse::<f64>().ok()?;
+    if config.numeric_mode != NumericMode::Decimal {
+        return Some(NumberValue::Float(number));
+    }
+
+    let decimal = match number_text.contains(['e', 'E']) {
+        true => Decimal::from_scientific(number_text),
+        false => Decimal::from_str(number_text)
+    };
+
+    Some(match decimal {
+        Ok(decimal) => NumberValue::Decimal(decimal),
+        Err(_) if number.abs() < 1.0 => NumberValue::Decimal(Decimal::from_f64(number).unwrap_or_default()),
+        Err(_) => NumberValue::Float(f64::INFINITY)
+    })
+}
+
+/* Units are stored as f64, results out of decimal range are calculated with f64. Zero divisor gives infinite or NaN, callers report the error */
+pub fn do_calculation(config: &SmartCalcConfig, left: f64, right: f64, operation_type: OperationType) -> f64 {
+    match calculate_number(config, left.into(), right.into(), operation_type) {
+        NumberValue::Decimal(result) => result.to_f64().unwrap_or(f64::NAN),
+        NumberValue::Float(_) => calculate_float(left, right, operation_type)
+    }
+}
+
+pub fn percent_of(config: &SmartCalcConfig, number: f64, percent: f64) -> f64 {
+    percent_of_number(config, number.into(), percent).to_f64()
+}
+
+pub fn percent_of_number(config: &SmartCalcConfig, number: NumberValue, percent: f64) -> NumberValue {
+    calculate_number(config, calculate_number(config, number, percent.into(), OperationType::Mul), 100.0.into(), OperationType::Div)
+}
+
+pub fn round_number(config: &SmartCalcConfig, number: f64, decimal_digits: u8) -> f64 {
+    if config.numeric_mode != NumericMode::Decimal {
+        return number;
+    }
+
+    round_decimal(number, decimal_digits)
+}
+
+pub fn round_decimal(number: f64, decimal_digits: u8) -> f64 {
+    match Decimal::from_f64(number) {
+        Some(decimal) => decimal.round_dp_with_strategy(decimal_digits.into(), RoundingStrategy::MidpointAwayFromZero).to_f64().unwrap_or(number),
+        None => number
+    }
+}
+
+/* City names of the zones that are also used as words */
+const ZONE_CITY_EXCLUDES: &[&str] = &["north", "south", "east", "west", "central", "eastern", "western", "mountain", "pacific", "atlantic", "christmas", "easter", "center", "wake", "midway", "knox", "palmer", "davis", "casey", "troll", "stanley", "general", "universal", "zulu"];
+/* Canonical zones of the cities that are also linked from another region */
+const ZONE_CITY_PREFERRED: &[(&str, Tz)] = &[("istanbul", Tz::Europe__Istanbul)];
+const ZONE_REGIONS: &[&str] = &["africa", "america", "antarctica", "asia", "atlantic", "australia", "europe", "indian", "pacific"];
+
+lazy_static! {
+    /* IANA zone names and the city names in them, like "america/new_york" and "new york" */
+    static ref ZONES: BTreeMap<String, Tz> = {
+        let mut zones = BTreeMap::new();
+        for zone in TZ_VARIANTS.iter() {
+            let name = zone.name().to_lowercase();
+            if let (Some((region, _)), Some((_, city))) = (name.split_once('/'), name.rsplit_once('/')) {
+                let city = city.replace('_', " ");
+                if ZONE_REGIONS.contains(&region) && !ZONE_CITY_EXCLUDES.contains(&city.as_str()) {
+                    zones.entry(city).or_insert(*zone);
+                }
+            }
+            if name != "factory" {
+                zones.insert(name, *zone);
+            }
+        }
+        for (city, zone) in ZONE_CITY_PREFERRED.iter() {
+            zones.insert(city.to_string(), *zone);
+        }
+        zones
+    };
+}
+
+/// IANA zone with the name like `Europe/Istanbul` or the city name in it like `Istanbul`.
+pub fn find_zone(name: &str) -> Option<Tz> {
+    ZONES.get(&name.trim().to_lowercase()).cloned()
+}
+
 pub fn parse_timezone<'t>(config: &SmartCalcConfig, capture: &regex::Captures<'t>) -> Option<(String, i32)> {
     match capture.name("timezone_1") {
         Some(tz) => {
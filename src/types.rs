@@ -11,26 +11,36 @@ use alloc::string::ToString;
 use alloc::string::String;
 use alloc::format;
 use core::ops::Deref;
-use chrono::{NaiveDateTime, TimeZone};
+use chrono::{FixedOffset, LocalResult, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
 
 use serde_derive::{Deserialize, Serialize};
 use alloc::collections::btree_map::BTreeMap;
-use chrono::{Duration, NaiveDate};
+use chrono::{Duration, NaiveDate, NaiveTime};
 use crate::compiler::DataItem;
 use crate::compiler::dynamic_type::DynamicTypeItem;
 use crate::config::DynamicType;
+use crate::compiler::compound_type::CompoundUnit;
 use crate::config::SmartCalcConfig;
+use crate::error::{ErrorKind, SmartCalcError, Span};
 
 use crate::tokinizer::TokenInfoStatus;
 use crate::tokinizer::{TokenInfo, Tokinizer};
 use crate::variable::VariableInfo;
+use crate::tools::{find_zone, round_decimal};
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 
-pub type ExpressionFunc     = fn(config: &SmartCalcConfig, tokinizer: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, String>;
-pub type AstResult          = Result<SmartCalcAstType, (&'static str, u16, u16)>;
+pub type ExpressionFunc     = fn(config: &SmartCalcConfig, tokinizer: &Tokinizer, fields: &BTreeMap<String, Rc<TokenInfo>>) -> core::result::Result<TokenType, ErrorKind>;
+pub type AstResult          = Result<SmartCalcAstType, SmartCalcError>;
 
-pub struct Money(pub f64, pub Rc<CurrencyInfo>);
+pub struct Money(pub NumberValue, pub Rc<CurrencyInfo>);
 impl Money {
     pub fn get_price(&self) -> f64 {
+        self.0.to_f64()
+    }
+
+    pub fn get_value(&self) -> NumberValue {
         self.0
     }
     
@@ -154,7 +164,70 @@ impl Ord for CurrencyInfo {
 #[derive(Debug, Clone, PartialEq)]
 pub struct TimeOffset {
     pub name: String,
-    pub offset: i32
+    pub offset: i32,
+
+    /* IANA timezone, offset and name are calculated for the date if it is set */
+    pub zone: Option<Tz>
+}
+
+impl TimeOffset {
+    /* Timezone tokens have the uppercase abbreviation and its offset, or the IANA zone name like "America/New_York" */
+    pub fn from_timezone(name: &str, offset: i32) -> Self {
+        match find_zone(name) {
+            Some(zone) if name.chars().any(|ch| ch.is_lowercase()) => TimeOffset::from_zone(zone),
+            _ => TimeOffset {
+                name: name.to_uppercase(),
+                offset,
+                zone: None
+            }
+        }
+    }
+
+    pub fn from_zone(zone: Tz) -> Self {
+        let offset = zone.offset_from_utc_datetime(&Utc::now().naive_utc()).fix().local_minus_utc() / 60;
+        TimeOffset {
+            name: zone.name().to_string(),
+            offset,
+            zone: Some(zone)
+        }
+    }
+
+    pub fn offset_at(&self, utc: &NaiveDateTime) -> FixedOffset {
+        match self.zone {
+            Some(zone) => zone.offset_from_utc_datetime(utc).fix(),
+            None => FixedOffset::east_opt(self.offset * 60).unwrap_or_else(|| FixedOffset::east_opt(0).unwrap())
+        }
+    }
+
+    /* Abbreviation of the zone at that time like "EDT", zone name if it does not have one */
+    pub fn name_at(&self, utc: &NaiveDateTime) -> String {
+        match self.zone {
+            Some(zone) => {
+                let abbreviation = zone.offset_from_utc_datetime(utc).to_string();
+                match abbreviation.chars().all(|ch| ch.is_ascii_alphabetic()) {
+                    true => abbreviation,
+                    false => self.name.to_string()
+                }
+            },
+            None => self.name.to_string()
+        }
+    }
+
+    pub fn to_local(&self, utc: &NaiveDateTime) -> NaiveDateTime {
+        *utc + Duration::seconds(self.offset_at(utc).local_minus_utc() as i64)
+    }
+
+    /* Times in the DST gap are moved forward like the clocks */
+    pub fn to_utc(&self, local: &NaiveDateTime) -> NaiveDateTime {
+        match self.zone {
+            Some(zone) => match zone.from_local_datetime(local) {
+                LocalResult::Single(time) => time.naive_utc(),
+                LocalResult::Ambiguous(earliest, _) => earliest.naive_utc(),
+                LocalResult::None => *local - Duration::seconds(self.offset_at(local).local_minus_utc() as i64)
+            },
+            None => *local - Duration::seconds(self.offset_at(local).local_minus_utc() as i64)
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -166,9 +239,105 @@ pub enum NumberType {
     Raw
 }
 
+/// Arithmetic of numbers and money. `Decimal` mode parses number and money literals into `Decimal` and
+/// calculates them without rounding to `f64`, results out of decimal range are reported as overflow.
+/// Units, percentages and currency rates are still `f64`.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum NumericMode {
+    #[default]
+    Float,
+    Decimal
+}
+
+/// Value of numbers and money, `Decimal` values are only created in `NumericMode::Decimal`
+#[derive(Debug, Copy, Clone)]
+pub enum NumberValue {
+    Float(f64),
+    Decimal(Decimal)
+}
+
+impl NumberValue {
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            NumberValue::Float(number) => *number,
+            NumberValue::Decimal(number) => number.to_f64().unwrap_or(f64::NAN)
+        }
+    }
+
+    /* Infinite and NaN values have no decimal */
+    pub fn to_decimal(&self) -> Option<Decimal> {
+        match self {
+            NumberValue::Float(number) => Decimal::from_f64(*number),
+            NumberValue::Decimal(number) => Some(*number)
+        }
+    }
+
+    pub fn round(&self, decimal_digits: u8) -> NumberValue {
+        match self {
+            NumberValue::Float(number) => NumberValue::Float(round_decimal(*number, decimal_digits)),
+            NumberValue::Decimal(number) => NumberValue::Decimal(number.round_dp_with_strategy(decimal_digits.into(), RoundingStrategy::MidpointAwayFromZero))
+        }
+    }
+
+    pub fn floor(&self) -> NumberValue {
+        match self {
+            NumberValue::Float(number) => NumberValue::Float(number.floor()),
+            NumberValue::Decimal(number) => NumberValue::Decimal(number.floor())
+        }
+    }
+
+    pub fn ceil(&self) -> NumberValue {
+        match self {
+            NumberValue::Float(number) => NumberValue::Float(number.ceil()),
+            NumberValue::Decimal(number) => NumberValue::Decimal(number.ceil())
+        }
+    }
+}
+
+impl From<f64> for NumberValue {
+    fn from(number: f64) -> Self {
+        NumberValue::Float(number)
+    }
+}
+
+impl From<Decimal> for NumberValue {
+    fn from(number: Decimal) -> Self {
+        NumberValue::Decimal(number)
+    }
+}
+
+impl PartialEq for NumberValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (NumberValue::Decimal(left), NumberValue::Decimal(right)) => left == right,
+            (_, _) => self.to_f64() == other.to_f64()
+        }
+    }
+}
+
+impl core::ops::Neg for NumberValue {
+    type Output = NumberValue;
+
+    fn neg(self) -> Self::Output {
+        match self {
+            NumberValue::Float(number) => NumberValue::Float(-number),
+            NumberValue::Decimal(number) => NumberValue::Decimal(-number)
+        }
+    }
+}
+
+impl core::fmt::Display for NumberValue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            NumberValue::Float(number) => write!(f, "{}", number),
+            NumberValue::Decimal(number) => write!(f, "{}", number.normalize())
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum TokenType {
-    Number(f64, NumberType),
+    Number(NumberValue, NumberType),
     Text(String),
     Time(NaiveDateTime, TimeOffset),
     Date(NaiveDate, TimeOffset),
@@ -177,7 +346,8 @@ pub enum TokenType {
     Field(Rc<FieldType>),
     Percent(f64),
     DynamicType(f64, Rc<DynamicType>),
-    Money(f64, Rc<CurrencyInfo>),
+    CompoundType(f64, Rc<CompoundUnit>),
+    Money(NumberValue, Rc<CurrencyInfo>),
     Variable(Rc<VariableInfo>),
     Month(u32),
     Duration(Duration),
@@ -209,23 +379,12 @@ impl ToString for TokenType {
     fn to_string(&self) -> String {
         match &self {
             TokenType::DynamicType(number, dynamic_type) => dynamic_type.format.replace("{value}", &number.to_string()),
+            TokenType::CompoundType(number, unit) => format!("{} {}", number, unit.format()),
             TokenType::Number(number, _) => number.to_string(),
             TokenType::Text(text) => text.to_string(),
-            TokenType::Time(time, tz) => {
-                let tz_offset = chrono::FixedOffset::east(tz.offset * 60);
-                let datetime = tz_offset.from_utc_datetime(time);
-                alloc::format!("{} {}", datetime.format("%H:%M:%S").to_string(), tz.name)
-            },
-            TokenType::Date(date, tz) => {
-                let tz_offset = chrono::FixedOffset::east(tz.offset * 60);
-                let datetime = tz_offset.from_utc_date(date);
-                alloc::format!("{} {}", datetime.format("%d/%m/%Y").to_string(), tz.name)
-            },
-            TokenType::DateTime(datetime, tz) => {
-                let tz_offset = chrono::FixedOffset::east(tz.offset * 60);
-                let datetime = tz_offset.from_utc_datetime(datetime);
-                alloc::format!("{} {}", datetime.format("%d/%m/%Y %H:%M:%S").to_string(), tz.name)
-            },
+            TokenType::Time(time, tz) => alloc::format!("{} {}", tz.to_local(time).format("%H:%M:%S"), tz.name_at(time)),
+            TokenType::Date(date, tz) => alloc::format!("{} {}", date.format("%d/%m/%Y"), tz.name_at(&date.and_time(NaiveTime::MIN))),
+            TokenType::DateTime(datetime, tz) => alloc::format!("{} {}", tz.to_local(datetime).format("%d/%m/%Y %H:%M:%S"), tz.name_at(datetime)),
             TokenType::Operator(ch) => ch.to_string(),
             TokenType::Field(_) => "field".to_string(),
             TokenType::Percent(number) => format!("%{}", number),
@@ -255,7 +414,8 @@ impl TokenType {
             TokenType::Month(_) => "MONTH".to_string(),
             TokenType::Duration(_) => "DURATION".to_string(),
             TokenType::Timezone(_, _) => "TIMEZONE".to_string(),
-            TokenType::DynamicType(_, _) => "DYNAMIC_TYPE".to_string()
+            TokenType::DynamicType(_, _) => "DYNAMIC_TYPE".to_string(),
+            TokenType::CompoundType(_, _) => "COMPOUND_TYPE".to_string()
         }
     }
 
@@ -427,7 +587,8 @@ pub enum SmartCalcAstType {
     Binary {
         left: Rc<SmartCalcAstType>,
         operator: char,
-        right: Rc<SmartCalcAstType>
+        right: Rc<SmartCalcAstType>,
+        span: Span
     },
     PrefixUnary(char, Rc<SmartCalcAstType>),
     Assignment {
@@ -435,7 +596,17 @@ pub enum SmartCalcAstType {
         expression: Rc<SmartCalcAstType>
     },
     Symbol(String),
-    Variable(Rc<VariableInfo>)
+    Variable(Rc<VariableInfo>),
+    Call {
+        name: String,
+        arguments: Vec<Rc<SmartCalcAstType>>,
+        span: Span
+    },
+    Conversion {
+        expression: Rc<SmartCalcAstType>,
+        unit: Rc<CompoundUnit>,
+        span: Span
+    }
 }
 
 impl SmartCalcAstType {
@@ -448,7 +619,8 @@ impl SmartCalcAstType {
             SmartCalcAstType::Binary {
                 left: _,
                 operator: _,
-                right: _
+                right: _,
+                span: _
             } => "BINARY".to_string(),
             SmartCalcAstType::PrefixUnary(_, ast) => ast.type_name(),
             SmartCalcAstType::Assignment {
@@ -456,7 +628,17 @@ impl SmartCalcAstType {
                 expression: _
             } => "ASSIGNMENT".to_string(),
             SmartCalcAstType::Symbol(_) => "SYMBOL".to_string(),
-            SmartCalcAstType::Variable(variable) => variable.data.borrow().type_name()
+            SmartCalcAstType::Variable(variable) => variable.data.borrow().type_name(),
+            SmartCalcAstType::Call {
+                name: _,
+                arguments: _,
+                span: _
+            } => "CALL".to_string(),
+            SmartCalcAstType::Conversion {
+                expression: _,
+                unit: _,
+                span: _
+            } => "CONVERSION".to_string()
         }
     }
 
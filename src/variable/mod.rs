@@ -68,6 +68,7 @@ pub fn update_token_variables(tokenizer: &mut Tokinizer) {
         }
 
         if found {
+            session.add_used_variable(name.to_string());
             let remove_start_index  = token_start_index + closest_variable;
             let remove_end_index    = remove_start_index + variable_size;
             let text_start_position = tokenizer.token_infos[remove_start_index].start;
@@ -0,0 +1,140 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::{CurrencyRateHistory, CurrencyRates, SmartCalc};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TYPESCRIPT_TYPES: &'static str = r#"
+export type ValueKind = "number" | "money" | "percent" | "dynamic_type" | "compound_type" | "date" | "time" | "date_time" | "duration" | "month" | "none";
+
+export interface LineValue {
+    kind: ValueKind;
+    number: number | null;
+    unit: string | null;
+    currency: string | null;
+    rate_date: string | null;
+}
+
+export interface Span {
+    start: number;
+    end: number;
+    char_start: number;
+    char_end: number;
+}
+
+export interface LineError {
+    kind: { type: string; data?: any };
+    span: Span;
+    message: string;
+}
+
+export interface UiToken {
+    start: number;
+    end: number;
+    ui_type: string;
+}
+
+export interface LineResult {
+    output: string | null;
+    value: LineValue | null;
+    error: LineError | null;
+    ui_tokens: UiToken[];
+}
+
+export interface ExecuteResult {
+    status: boolean;
+    lines: (LineResult | null)[];
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "ExecuteResult")]
+    pub type JsExecuteResult;
+}
+
+/// JavaScript wrapper of `SmartCalc`.
+#[derive(Default)]
+#[wasm_bindgen(js_name = SmartCalc)]
+pub struct SmartCalcWeb {
+    smartcalc: SmartCalc
+}
+
+#[wasm_bindgen(js_class = SmartCalc)]
+impl SmartCalcWeb {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        SmartCalcWeb::default()
+    }
+
+    /// Load a complete configuration, problems of the configuration are thrown as one message per line.
+    #[wasm_bindgen(js_name = loadFromJson)]
+    pub fn load_from_json(json_data: &str) -> Result<SmartCalcWeb, JsValue> {
+        SmartCalc::try_load_from_json(json_data)
+            .map(|smartcalc| SmartCalcWeb { smartcalc })
+            .map_err(|errors| JsValue::from_str(&errors.iter().map(|error| error.to_string()).collect::<Vec<_>>().join("\n")))
+    }
+
+    pub fn execute(&self, language: &str, data: &str) -> Result<JsExecuteResult, JsValue> {
+        let result = self.smartcalc.execute_json(language, data);
+        let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+        Ok(result.serialize(&serializer)?.unchecked_into())
+    }
+
+    #[wasm_bindgen(js_name = setTimezone)]
+    pub fn set_timezone(&mut self, timezone: String) -> Result<(), JsValue> {
+        self.smartcalc.set_timezone(timezone).map_err(|error| JsValue::from_str(&error))
+    }
+
+    #[wasm_bindgen(js_name = updateCurrency)]
+    pub fn update_currency(&mut self, currency: &str, rate: f64) -> bool {
+        self.smartcalc.update_currency(currency, rate)
+    }
+
+    /// Update the currency rates from ECB XML, CSV or JSON content and return the number of updated currencies.
+    #[wasm_bindgen(js_name = updateCurrencyRates)]
+    pub fn update_currency_rates(&mut self, data: &str) -> Result<usize, JsValue> {
+        CurrencyRates::parse(data)
+            .and_then(|rates| self.smartcalc.update_currency_rates(&rates))
+            .map_err(|error| JsValue::from_str(&error.to_string()))
+    }
+
+    /// Set the dated rates of historical conversions from ECB XML, CSV or JSON content and return the number of days.
+    #[wasm_bindgen(js_name = setCurrencyRateHistory)]
+    pub fn set_currency_rate_history(&mut self, data: &str) -> Result<usize, JsValue> {
+        CurrencyRateHistory::parse(data)
+            .and_then(|history| self.smartcalc.set_currency_rate_history(&history))
+            .map_err(|error| JsValue::from_str(&error.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = addDynamicType)]
+    pub fn add_dynamic_type(&mut self, name: &str) -> bool {
+        self.smartcalc.add_dynamic_type(name)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[wasm_bindgen(js_name = addDynamicTypeItem)]
+    pub fn add_dynamic_type_item(&mut self, name: &str, index: usize, format: &str, parse: Vec<String>, upgrade_code: &str, downgrade_code: &str, names: Vec<String>, decimal_digits: Option<u8>, use_fract_rounding: Option<bool>, remove_fract_if_zero: Option<bool>) -> bool {
+        let parse = parse.iter().map(|item| item.as_str()).collect::<Vec<_>>();
+        self.smartcalc.add_dynamic_type_item(name, index, format, parse, upgrade_code, downgrade_code, names, decimal_digits, use_fract_rounding, remove_fract_if_zero)
+    }
+
+    #[wasm_bindgen(js_name = setDecimalSeparator)]
+    pub fn set_decimal_separator(&mut self, decimal_separator: &str) {
+        self.smartcalc.set_decimal_seperator(decimal_separator.to_string());
+    }
+
+    #[wasm_bindgen(js_name = setThousandSeparator)]
+    pub fn set_thousand_separator(&mut self, thousand_separator: &str) {
+        self.smartcalc.set_thousand_separator(thousand_separator.to_string());
+    }
+}
@@ -0,0 +1,77 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+#![cfg(all(feature = "ffi", unix))]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/* Cdylib and generated header from a separate build, outputs of the test build could belong to another feature set */
+fn build_library(manifest_dir: &Path) -> (PathBuf, PathBuf) {
+    let output = Command::new(option_env!("CARGO").unwrap_or("cargo"))
+        .args(["build", "--lib", "--features", "ffi", "--message-format", "json"])
+        .arg("--target-dir").arg(PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("ffi"))
+        .current_dir(manifest_dir)
+        .stderr(Stdio::inherit())
+        .output()
+        .expect("cargo could not be started");
+    assert!(output.status.success(), "library could not be built");
+
+    let mut library = None;
+    let mut out_dir = None;
+    for line in String::from_utf8(output.stdout).unwrap().lines() {
+        let message: serde_json::Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(_) => continue
+        };
+
+        if !message["package_id"].as_str().unwrap_or_default().contains("smartcalc") {
+            continue;
+        }
+
+        match message["reason"].as_str() {
+            Some("build-script-executed") => out_dir = message["out_dir"].as_str().map(PathBuf::from),
+            Some("compiler-artifact") => library = message["filenames"].as_array().into_iter().flatten()
+                .filter_map(|file_name| file_name.as_str())
+                .find(|file_name| file_name.ends_with(".so") || file_name.ends_with(".dylib"))
+                .map(PathBuf::from)
+                .or(library),
+            _ => ()
+        }
+    }
+
+    (library.expect("cdylib is not found"), out_dir.expect("build script output is not found"))
+}
+
+/* C tests are compiled with the generated header and linked to the cdylib */
+#[test]
+fn ffi_c_tests() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let (library, out_dir) = build_library(&manifest_dir);
+    let library_dir = library.parent().unwrap();
+    let executable = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("smartcalc_test");
+
+    let header = fs::read_to_string(out_dir.join("smartcalc.h")).unwrap();
+    assert_eq!(fs::read_to_string(manifest_dir.join("include/smartcalc.h")).unwrap(), header, "include/smartcalc.h is outdated, copy the generated header from {}", out_dir.display());
+
+    let status = Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg(manifest_dir.join("tests/ffi/smartcalc_test.c"))
+        .arg("-I").arg(&out_dir)
+        .arg("-L").arg(library_dir)
+        .arg("-lsmartcalc")
+        .arg("-o").arg(&executable)
+        .status()
+        .expect("C compiler could not be started");
+    assert!(status.success(), "C tests could not be compiled");
+
+    let status = Command::new(&executable)
+        .env("LD_LIBRARY_PATH", library_dir)
+        .env("DYLD_LIBRARY_PATH", library_dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "C tests failed");
+}
@@ -0,0 +1,128 @@
+/*
+ * smartcalc v1.0.7
+ * Copyright (c) Erhan BARIS (Ruslan Ognyanov Asenov)
+ * Licensed under the GNU General Public License v2.0.
+ */
+
+#include <stdio.h>
+#include <string.h>
+#include "smartcalc.h"
+
+static int failures = 0;
+
+#define CHECK(condition) do { \
+    if (!(condition)) { \
+        fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #condition); \
+        failures++; \
+    } \
+} while (0)
+
+static void test_execute(void) {
+    SmartCalc *smartcalc = smartcalc_new();
+    SmartCalcResult *result = smartcalc_execute(smartcalc, "en", "$10 + $5\n\n1 / 0");
+    SmartCalcLineError error;
+    SmartCalcUiToken token;
+
+    CHECK(result != NULL);
+    CHECK(smartcalc_result_line_count(result) == 3);
+
+    CHECK(smartcalc_result_line_status(result, 0) == SMART_CALC_LINE_STATUS_SUCCESS);
+    CHECK(strcmp(smartcalc_result_line_output(result, 0), "$15,00") == 0);
+    CHECK(smartcalc_result_ui_token_count(result, 0) > 0);
+    CHECK(smartcalc_result_ui_token(result, 0, 0, &token));
+    CHECK(token.start == 0 && token.ui_type == SMART_CALC_UI_TOKEN_TYPE_SYMBOL1);
+    CHECK(!smartcalc_result_line_error(result, 0, &error));
+
+    CHECK(smartcalc_result_line_status(result, 1) == SMART_CALC_LINE_STATUS_EMPTY);
+    CHECK(smartcalc_result_line_output(result, 1) == NULL);
+
+    CHECK(smartcalc_result_line_status(result, 2) == SMART_CALC_LINE_STATUS_ERROR);
+    CHECK(smartcalc_result_line_output(result, 2) == NULL);
+    CHECK(smartcalc_result_line_error(result, 2, &error));
+    CHECK(strcmp(error.message, "Division by zero") == 0);
+    CHECK(error.start == 0 && error.end == 5);
+
+    CHECK(smartcalc_result_line_status(result, 10) == SMART_CALC_LINE_STATUS_EMPTY);
+    CHECK(!smartcalc_result_ui_token(result, 0, 100, &token));
+
+    smartcalc_result_free(result);
+    smartcalc_free(smartcalc);
+}
+
+static void test_session(void) {
+    SmartCalc *smartcalc = smartcalc_new();
+    Session *session = smartcalc_session_new();
+    SmartCalcResult *result;
+
+    CHECK(smartcalc_set_decimal_separator(smartcalc, "."));
+    CHECK(smartcalc_set_thousand_separator(smartcalc, ","));
+    CHECK(smartcalc_update_currency(smartcalc, "try", 10.0));
+    CHECK(smartcalc_set_timezone(smartcalc, "UTC"));
+    CHECK(!smartcalc_set_timezone(smartcalc, "Unknown"));
+
+    CHECK(smartcalc_session_set_language(session, "en"));
+    CHECK(smartcalc_session_set_text(session, "price = 1200.5\nprice * 2\n1 usd to try"));
+    result = smartcalc_execute_session(smartcalc, session);
+
+    CHECK(smartcalc_result_line_count(result) == 3);
+    CHECK(strcmp(smartcalc_result_line_output(result, 0), "1,200.50") == 0);
+    CHECK(strcmp(smartcalc_result_line_output(result, 1), "2,401") == 0);
+    CHECK(strcmp(smartcalc_result_line_output(result, 2), "\xe2\x82\xba" "10.00") == 0);
+
+    smartcalc_result_free(result);
+    smartcalc_session_free(session);
+    smartcalc_free(smartcalc);
+}
+
+static void test_currency_rates(void) {
+    SmartCalc *smartcalc = smartcalc_new();
+    SmartCalcResult *result;
+
+    CHECK(smartcalc_update_currency_rates(smartcalc, "Date, USD, TRY,\n17 May 2024, 1.0866, 34.9903,\n"));
+    CHECK(!smartcalc_update_currency_rates(smartcalc, "usd,abc\ntry,abc"));
+
+    result = smartcalc_execute(smartcalc, "en", "10 eur to usd\n$10");
+    CHECK(strcmp(smartcalc_result_line_output(result, 0), "$10,87") == 0);
+    CHECK(strcmp(smartcalc_result_line_rate_date(result, 0), "2024-05-17T00:00:00Z") == 0);
+    CHECK(smartcalc_result_line_rate_date(result, 1) == NULL);
+    smartcalc_result_free(result);
+
+    CHECK(smartcalc_set_currency_rate_history(smartcalc, "date,currency,rate\n2021-03-01,eur,0.8288\n"));
+    CHECK(!smartcalc_set_currency_rate_history(smartcalc, "2021-03-01,eur"));
+
+    result = smartcalc_execute(smartcalc, "en", "$100 to eur on 2021-03-01");
+    CHECK(strcmp(smartcalc_result_line_output(result, 0), "82,88 €") == 0);
+    CHECK(strcmp(smartcalc_result_line_rate_date(result, 0), "2021-03-01T00:00:00Z") == 0);
+
+    smartcalc_result_free(result);
+    smartcalc_free(smartcalc);
+}
+
+static void test_invalid_arguments(void) {
+    SmartCalcLineError error;
+
+    CHECK(smartcalc_execute(NULL, "en", "1 + 2") == NULL);
+    CHECK(smartcalc_execute_session(NULL, NULL) == NULL);
+    CHECK(smartcalc_result_line_count(NULL) == 0);
+    CHECK(smartcalc_result_line_output(NULL, 0) == NULL);
+    CHECK(!smartcalc_result_line_error(NULL, 0, &error));
+    CHECK(smartcalc_load_from_json(NULL) == NULL);
+    CHECK(smartcalc_load_from_json("{ not json") == NULL);
+    CHECK(smartcalc_load_from_json("{}") == NULL);
+    smartcalc_result_free(NULL);
+    smartcalc_session_free(NULL);
+    smartcalc_free(NULL);
+}
+
+int main(void) {
+    test_execute();
+    test_session();
+    test_currency_rates();
+    test_invalid_arguments();
+
+    if (failures != 0) {
+        fprintf(stderr, "%d check(s) failed\n", failures);
+        return 1;
+    }
+    return 0;
+}